@@ -10,7 +10,9 @@ use tracing::{debug, error, info, warn};
 use crate::config::Config;
 use crate::constants::constants;
 use crate::display::DisplayMode;
+use crate::index;
 use crate::player::{MusicPlayer, VideoDetails};
+use crate::store;
 use crate::theme::THEMES;
 use crate::transcript::{self, TranscriptEvent, TranscriptState};
 use crate::wiki::{self, WikiDetail};
@@ -24,6 +26,8 @@ use crate::youtube::{
 
 pub type SearchResult = Vec<SearchEntry>;
 pub type LoadResult = (String, VideoDetails, Option<DynamicImage>);
+/// A transcript search's query and hits.
+pub type GrepResult = (String, Vec<index::Hit>);
 
 /// Video frame display mode during playback.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -69,6 +73,14 @@ pub struct ChannelSource {
   pub loading_more: bool,
 }
 
+/// Results of a `?query` search over stored transcripts.
+#[derive(Debug, Clone)]
+pub struct TranscriptSearch {
+  pub query: String,
+  /// The hits, listed in place of `search_results`.
+  pub hits: Vec<index::Hit>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppMode {
   Input,
@@ -109,6 +121,8 @@ pub(crate) struct AsyncTasks {
   pub(crate) search_rx: Option<oneshot::Receiver<Result<SearchResult>>>,
   pub(crate) load_rx: Option<oneshot::Receiver<Result<LoadResult>>>,
   pub(crate) more_rx: Option<oneshot::Receiver<Result<SearchResult>>>,
+  pub(crate) grep_rx: Option<oneshot::Receiver<Result<GrepResult>>>,
+  pub(crate) clip_rx: Option<oneshot::Receiver<Result<std::path::PathBuf>>>,
   pub(crate) enrich_rx: Option<mpsc::Receiver<VideoMeta>>,
   pub(crate) enrich_handle: Option<JoinHandle<()>>,
   pub(crate) wiki_rx: Option<oneshot::Receiver<Result<Option<WikiDetail>>>>,
@@ -131,6 +145,14 @@ pub struct App {
   pub info_message: Option<String>,
   pub should_quit: bool,
   pub channel_source: Option<ChannelSource>,
+  /// Set when the results are transcript hits rather than videos (`search_results` is then empty).
+  pub transcript_search: Option<TranscriptSearch>,
+  /// Where the next loaded video starts playing (a transcript hit's time).
+  pending_start: Option<f64>,
+  /// Clip start marked with Ctrl+B (seconds into the current track).
+  pub clip_in: Option<f64>,
+  /// Clip end marked with Ctrl+E.
+  pub clip_out: Option<f64>,
   pub input_scroll: usize,
   pub gfx: GraphicsCache,
   /// Filter text for narrowing search results by title/tags.
@@ -139,7 +161,7 @@ pub struct App {
   pub filter_cursor: usize,
   /// Horizontal scroll offset for the filter input.
   pub filter_scroll: usize,
-  /// Indices into `search_results` that match the current filter.
+  /// Indices into `search_results` (or the transcript hits) that match the current filter.
   /// When filter is empty, contains all indices.
   pub filtered_indices: Vec<usize>,
   pub(crate) frames: FrameState,
@@ -151,6 +173,12 @@ pub struct App {
   pub utterances: Vec<whisper_cli::Utternace>,
   /// Whether the transcript pane is visible (toggled with Ctrl+A).
   pub transcript_visible: bool,
+  /// Whether the transcript pane groups utterances into paragraphs (toggled with Ctrl+P).
+  pub transcript_paragraphs: bool,
+  /// `utterances` grouped into paragraphs; `None` after the transcript changes.
+  pub(crate) paragraph_cache: Option<Vec<crate::segment::Paragraph>>,
+  /// Whether the current track has an interrupted job whose checkpoint Ctrl+A can resume.
+  pub transcript_resumable: bool,
   /// Whisper model download progress (downloaded, total) for progress bar display.
   pub download_progress: Option<(u64, u64)>,
   /// Cached whisper model instance — loaded once, reused across transcriptions.
@@ -188,7 +216,10 @@ pub struct App {
 
 impl App {
   pub fn new(display_mode: DisplayMode) -> Self {
-    let config = Config::load();
+    let (config, config_error) = match Config::read() {
+      Ok(config) => (config, None),
+      Err(e) => (Config::default(), Some(format!("{e:#}"))),
+    };
     let theme_index =
       if let Some(ref name) = config.theme_name { THEMES.iter().position(|t| t.name == name).unwrap_or(0) } else { 0 };
     let frame_mode =
@@ -206,11 +237,16 @@ impl App {
       search_results: Vec::new(),
       list_state: ListState::default(),
       player: MusicPlayer::new(display_mode),
-      last_error: None,
+      error_time: config_error.as_ref().map(|_| Instant::now()),
+      last_error: config_error,
       status_message: None,
       info_message: None,
       should_quit: false,
       channel_source: None,
+      transcript_search: None,
+      pending_start: None,
+      clip_in: None,
+      clip_out: None,
       input_scroll: 0,
       gfx: GraphicsCache::default(),
       filter: String::new(),
@@ -223,13 +259,15 @@ impl App {
       transcript_rx: None,
       utterances: Vec::new(),
       transcript_visible: true,
+      transcript_paragraphs: false,
+      paragraph_cache: None,
+      transcript_resumable: false,
       download_progress: None,
       whisper_cache: Arc::new(StdMutex::new(None)),
       started_at: Instant::now(),
       pip_mode: false,
       pip_original_geometry: None,
       pip_was_fullscreen: false,
-      error_time: None,
       wiki_detail: None,
       wiki_visible: false,
       wiki_scroll: 0,
@@ -338,10 +376,19 @@ impl App {
     }
   }
 
-  fn save_config(&self) {
-    let config =
-      Config { theme_name: Some(self.theme().name.to_string()), frame_mode: Some(self.frame_mode.label().to_string()) };
-    config.save();
+  fn save_config(&mut self) {
+    // Start from the file on disk so hand-edited tables (e.g. `[classifier]`) survive.
+    let saved = Config::read().and_then(|on_disk| {
+      Config {
+        theme_name: Some(self.theme().name.to_string()),
+        frame_mode: Some(self.frame_mode.label().to_string()),
+        ..on_disk
+      }
+      .save()
+    });
+    if let Err(e) = saved {
+      self.set_error(format!("Preferences not saved: {e:#}"));
+    }
   }
 
   /// Check if a search entry matches the given filter string.
@@ -362,11 +409,32 @@ impl App {
     false
   }
 
-  /// Rebuild `filtered_indices` from `search_results` and the current filter.
+  /// Check if a transcript hit matches the given filter string.
+  /// Matches case-insensitively against the snippet and the video title.
+  pub fn hit_matches_filter(hit: &index::Hit, filter: &str) -> bool {
+    let needle = filter.to_lowercase();
+    hit.snippet.to_lowercase().contains(&needle)
+      || hit.title.as_ref().is_some_and(|t| t.to_lowercase().contains(&needle))
+  }
+
+  /// Number of rows in the results list: videos, or transcript hits.
+  pub fn result_count(&self) -> usize {
+    self.transcript_search.as_ref().map_or(self.search_results.len(), |s| s.hits.len())
+  }
+
+  /// Rebuild `filtered_indices` from the results and the current filter.
   /// Clamps the list selection to stay within the filtered range.
   pub fn recompute_filter(&mut self) {
     if self.filter.is_empty() {
-      self.filtered_indices = (0..self.search_results.len()).collect();
+      self.filtered_indices = (0..self.result_count()).collect();
+    } else if let Some(ref search) = self.transcript_search {
+      self.filtered_indices = search
+        .hits
+        .iter()
+        .enumerate()
+        .filter(|(_, hit)| Self::hit_matches_filter(hit, &self.filter))
+        .map(|(i, _)| i)
+        .collect();
     } else {
       self.filtered_indices = self
         .search_results
@@ -472,6 +540,15 @@ impl App {
 
   // --- Auto-transcription ---
 
+  /// The transcript grouped into paragraphs, rebuilt only after it changed.
+  pub fn paragraphs(&mut self) -> &[crate::segment::Paragraph] {
+    let utterances = &self.utterances;
+    self.paragraph_cache.get_or_insert_with(|| {
+      let triples: Vec<(i64, i64, String)> = utterances.iter().map(|u| (u.start, u.stop, u.text.clone())).collect();
+      crate::segment::paragraphs(&crate::segment::sentences(&triples))
+    })
+  }
+
   /// Start the auto-transcription pipeline for the given `YouTube` URL.
   ///
   /// Architecture: chunked transcription for fast first results.
@@ -480,11 +557,30 @@ impl App {
   /// 3. Loop: download 30s chunk via ffmpeg → transcribe → send utterances → next chunk
   ///
   /// First transcript appears in ~5-8s instead of ~50s.
+  ///
+  /// If the transcript store holds a checkpoint for this video, its utterances
+  /// are shown immediately and the pipeline resumes from the stored offset.
+  /// A completed checkpoint skips the pipeline entirely.
   pub fn trigger_transcription(&mut self, url: &str) {
     // Cancel any in-progress transcription
     self.cancel_transcription();
     self.utterances.clear();
+    self.paragraph_cache = None;
     self.download_progress = None;
+    self.transcript_resumable = false;
+
+    let video_id = crate::cli::extract_video_id(url);
+    let mut start_offset = 0;
+    if let Some(record) = store::load(&video_id) {
+      self.utterances = record.to_utterances();
+      self.paragraph_cache = None;
+      if record.complete {
+        info!(video_id = %video_id, segments = self.utterances.len(), "transcript: loaded stored transcript");
+        self.transcript_state = TranscriptState::Ready;
+        return;
+      }
+      start_offset = record.offset_secs;
+    }
 
     let (tx, rx) = mpsc::unbounded_channel();
     self.transcript_rx = Some(rx);
@@ -492,24 +588,35 @@ impl App {
     let url = url.to_string();
     let whisper_cache = Arc::clone(&self.whisper_cache);
     let ipc_socket = self.player.ipc_socket_path().map(std::string::ToString::to_string);
+    let duration_hint = self
+      .player
+      .current_details
+      .as_ref()
+      .and_then(|d| d.duration.as_deref())
+      .and_then(crate::cli::parse_duration_secs);
 
-    info!(url = %url, "transcript: starting chunked transcription pipeline");
+    info!(url = %url, start_offset, "transcript: starting chunked transcription pipeline");
 
-    let handle = transcript::spawn_transcription_pipeline(tx, url, whisper_cache, ipc_socket, None);
+    let handle =
+      transcript::spawn_transcription_pipeline(tx, url, whisper_cache, ipc_socket, duration_hint, start_offset);
 
     self.transcript_state = TranscriptState::ExtractingAudio { handle };
   }
 
   /// Cancel any in-progress transcription pipeline.
+  ///
+  /// Chunks transcribed so far stay checkpointed in the transcript store.
   pub fn cancel_transcription(&mut self) {
     match std::mem::replace(&mut self.transcript_state, TranscriptState::Idle) {
       TranscriptState::ExtractingAudio { handle } => {
         info!("transcript: cancelling audio extraction");
         handle.abort();
+        self.transcript_resumable = true;
       }
       TranscriptState::Transcribing { handle } => {
         info!("transcript: cancelling transcription");
         handle.abort();
+        self.transcript_resumable = true;
       }
       _ => {}
     }
@@ -517,6 +624,70 @@ impl App {
     self.download_progress = None;
   }
 
+  /// Current playback position from mpv's status line.
+  fn playback_secs(&self) -> Option<f64> {
+    self.player.get_last_mpv_status().and_then(|s| crate::parse_mpv_time_secs(&s))
+  }
+
+  /// Handle Ctrl+B: mark the clip start at the current playback position.
+  pub fn clip_mark_in(&mut self) {
+    let Some(secs) = self.playback_secs() else { return };
+    self.clip_in = Some(secs);
+    if self.clip_out.is_some_and(|out| out <= secs) {
+      self.clip_out = None;
+    }
+    self.info_message = Some(format!("Clip in at {} — ^e to mark out", crate::summarize::timestamp(secs)));
+  }
+
+  /// Handle Ctrl+E: mark the clip end at the current playback position.
+  pub fn clip_mark_out(&mut self) {
+    let Some(secs) = self.playback_secs() else { return };
+    match self.clip_in {
+      Some(start) if secs > start => {
+        self.clip_out = Some(secs);
+        self.info_message = Some(format!(
+          "Clip {} – {} — ^x to export",
+          crate::summarize::timestamp(start),
+          crate::summarize::timestamp(secs)
+        ));
+      }
+      Some(_) => self.set_error("Mark out must be after mark in.".to_string()),
+      None => self.set_error("Mark in first (^b).".to_string()),
+    }
+  }
+
+  /// Handle Ctrl+X: cut the marked range of the current track to the downloads directory.
+  pub fn trigger_clip_export(&mut self) {
+    let (Some(from), Some(to)) = (self.clip_in, self.clip_out) else {
+      self.set_error("Mark in (^b) and out (^e) first.".to_string());
+      return;
+    };
+    let Some(ref details) = self.player.current_details else { return };
+    if self.tasks.clip_rx.is_some() {
+      return;
+    }
+    let url = details.url.clone();
+    let video_id = crate::cli::extract_video_id(&url);
+    let dir = directories::UserDirs::new()
+      .and_then(|d| d.download_dir().map(std::path::Path::to_path_buf))
+      .unwrap_or_else(|| std::path::PathBuf::from("."));
+    let path = dir.join(crate::clip::default_name(&video_id, from, to, "m4a"));
+    let socket = self.player.ipc_socket_path().map(str::to_string);
+    self.status_message = Some("Exporting clip…".to_string());
+
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+      let result = async {
+        let stream_url = transcript::resolve_stream_url(socket.as_deref(), &url).await?;
+        crate::clip::export(&stream_url, from, to, &path).await?;
+        Ok(path)
+      }
+      .await;
+      let _ = tx.send(result);
+    });
+    self.tasks.clip_rx = Some(rx);
+  }
+
   /// Handle Ctrl+A: toggle transcript visibility / cancel in-progress transcription.
   pub fn transcript_toggle(&mut self) {
     match self.transcript_state {
@@ -531,6 +702,14 @@ impl App {
         self.transcript_visible = !self.transcript_visible;
         debug!(visible = self.transcript_visible, "transcript: toggle visibility");
       }
+      TranscriptState::Idle if self.transcript_resumable && self.player.is_playing() => {
+        // Resume the cancelled job from its checkpoint
+        debug!("transcript: toggle -> resume");
+        if let Some(url) = self.player.current_details.as_ref().map(|d| d.url.clone()) {
+          self.trigger_transcription(&url);
+          self.transcript_visible = true;
+        }
+      }
       TranscriptState::Idle => {
         // Toggle visibility (show/hide even when empty)
         self.transcript_visible = !self.transcript_visible;
@@ -677,6 +856,7 @@ impl App {
                 }
               }
               self.search_results = results;
+              self.transcript_search = None;
               self.recompute_filter();
               self.list_state.select(Some(0));
               self.mode = AppMode::Results;
@@ -700,6 +880,36 @@ impl App {
       }
     }
 
+    if let Some(mut rx) = self.tasks.grep_rx.take() {
+      match rx.try_recv() {
+        Ok(result) => {
+          self.status_message = None;
+          match result {
+            Ok((query, hits)) if hits.is_empty() => {
+              self.set_error(format!("No stored transcript mentions '{query}'."));
+            }
+            Ok((query, hits)) => {
+              self.search_results.clear();
+              self.transcript_search = Some(TranscriptSearch { query, hits });
+              self.recompute_filter();
+              self.list_state.select(Some(0));
+              self.mode = AppMode::Results;
+            }
+            Err(e) => {
+              self.set_error(format!("Transcript search failed: {e:#}"));
+            }
+          }
+        }
+        Err(oneshot::error::TryRecvError::Empty) => {
+          self.tasks.grep_rx = Some(rx);
+        }
+        Err(oneshot::error::TryRecvError::Closed) => {
+          self.status_message = None;
+          self.set_error("Transcript search task failed.".to_string());
+        }
+      }
+    }
+
     if let Some(mut rx) = self.tasks.load_rx.take() {
       match rx.try_recv() {
         Ok(result) => {
@@ -707,10 +917,12 @@ impl App {
           match result {
             Ok((video_id, details, thumbnail)) => {
               let play_url = details.url.clone();
-              if let Err(e) = self.player.play(details).await {
+              if let Err(e) = self.player.play(details, self.pending_start.take()).await {
                 self.set_error(format!("Playback error: {e}"));
                 let _ = self.player.stop().await;
               } else {
+                self.clip_in = None;
+                self.clip_out = None;
                 // Auto-trigger transcription for the new track
                 self.trigger_transcription(&play_url);
                 // Clear previous wiki state and auto-fetch for new video
@@ -821,6 +1033,27 @@ impl App {
       }
     }
 
+    // Check for a finished clip export
+    if let Some(mut rx) = self.tasks.clip_rx.take() {
+      match rx.try_recv() {
+        Ok(Ok(path)) => {
+          self.status_message = None;
+          self.info_message = Some(format!("Clip saved to {}", path.display()));
+        }
+        Ok(Err(e)) => {
+          self.status_message = None;
+          self.set_error(format!("Clip export failed: {e:#}"));
+        }
+        Err(oneshot::error::TryRecvError::Empty) => {
+          self.tasks.clip_rx = Some(rx);
+        }
+        Err(oneshot::error::TryRecvError::Closed) => {
+          self.status_message = None;
+          self.set_error("Clip export task failed.".to_string());
+        }
+      }
+    }
+
     // --- Auto-transcription polling ---
 
     // Poll transcript pipeline events
@@ -845,6 +1078,7 @@ impl App {
               "transcript: chunk arrived"
             );
             self.utterances.extend(chunk_utterances);
+            self.paragraph_cache = None;
             self.transcript_visible = true;
             self.download_progress = None;
           }
@@ -856,10 +1090,11 @@ impl App {
             self.transcript_rx = None;
             break;
           }
-          TranscriptEvent::Failed(msg) => {
+          TranscriptEvent::Failed(_, msg) => {
             error!(err = %msg, "transcript: pipeline failed");
             self.set_error(msg);
             self.transcript_state = TranscriptState::Idle;
+            self.transcript_resumable = true;
             self.download_progress = None;
             self.transcript_rx = None;
             break;
@@ -928,6 +1163,7 @@ impl App {
     info!(query = %query, "search triggered");
     self.tasks.search_rx = None;
     self.tasks.more_rx = None;
+    self.tasks.grep_rx = None;
     self.cancel_enrich();
     self.clear_error();
     self.info_message = None;
@@ -936,9 +1172,12 @@ impl App {
     self.filter_cursor = 0;
     self.filter_scroll = 0;
 
-    if let Some(channel_url) = detect_channel_url(&query) {
+    if let Some(needle) = query.strip_prefix('?') {
+      self.trigger_transcript_search(needle.trim().to_string());
+    } else if let Some(channel_url) = detect_channel_url(&query) {
       // Channel listing mode
       self.status_message = Some("Loading channel…".to_string());
+      self.transcript_search = None;
       self.channel_source =
         Some(ChannelSource { url: channel_url.clone(), total_fetched: 0, has_more: true, loading_more: false });
 
@@ -951,6 +1190,7 @@ impl App {
       // Regular search mode
       self.status_message = Some(format!("Searching '{query}'…"));
       self.channel_source = None;
+      self.transcript_search = None;
 
       let (tx, rx) = oneshot::channel();
       tokio::spawn(async move {
@@ -960,6 +1200,27 @@ impl App {
     }
   }
 
+  /// Search stored transcripts (`?query` in the search box); hits play from their moment.
+  fn trigger_transcript_search(&mut self, query: String) {
+    if query.is_empty() {
+      self.set_error("Enter words to find after '?'.".to_string());
+      return;
+    }
+    self.status_message = Some(format!("Searching transcripts for '{query}'…"));
+    self.channel_source = None;
+
+    let (tx, rx) = oneshot::channel();
+    tokio::task::spawn_blocking(move || {
+      let result = index::load_or_rebuild().map(|idx| {
+        let titles = crate::cache::read_videos().into_iter().collect();
+        let hits = index::search(&idx, &query, None, &titles, constants().channel_initial_size);
+        (query, hits)
+      });
+      let _ = tx.send(result);
+    });
+    self.tasks.grep_rx = Some(rx);
+  }
+
   /// Trigger a background fetch of the next page of channel videos.
   pub fn trigger_load_more(&mut self) {
     let Some(ref mut source) = self.channel_source else { return };
@@ -1012,15 +1273,21 @@ impl App {
       let Some(&idx) = self.filtered_indices.get(selected) else { return };
       idx
     };
-    let Some(entry) = self.search_results.get(actual_idx) else { return };
-
-    let video_id = entry.video_id.clone();
-    let upload_date = entry.upload_date.clone();
-    let tags: Vec<String> = entry
-      .tags
-      .as_deref()
-      .map(|s| s.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
-      .unwrap_or_default();
+    // Transcript hits start at their moment and carry no listing metadata.
+    let (video_id, upload_date, tags) = if let Some(ref search) = self.transcript_search {
+      let Some(hit) = search.hits.get(actual_idx) else { return };
+      self.pending_start = Some(hit.at_secs);
+      (hit.video_id.clone(), None, Vec::new())
+    } else {
+      let Some(entry) = self.search_results.get(actual_idx) else { return };
+      self.pending_start = None;
+      let tags: Vec<String> = entry
+        .tags
+        .as_deref()
+        .map(|s| s.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+        .unwrap_or_default();
+      (entry.video_id.clone(), entry.upload_date.clone(), tags)
+    };
     let client = self.player.http_client.clone();
     self.clear_error();
     self.status_message = Some("Loading…".to_string());
@@ -1058,6 +1325,7 @@ impl App {
 
   /// Load a video directly by ID (e.g. from wiki related videos).
   pub fn trigger_load_by_id(&mut self, video_id: String) {
+    self.pending_start = None;
     let client = self.player.http_client.clone();
     self.clear_error();
     self.status_message = Some("Loading…".to_string());
@@ -1188,4 +1456,19 @@ mod tests {
     assert!(App::matches_filter(&entry, "jazz"));
     assert!(App::matches_filter(&entry, "FUNK"));
   }
+
+  #[test]
+  fn hit_matches_filter_snippet_and_title() {
+    let hit = index::Hit {
+      video_id: "abc".to_string(),
+      title: Some("Glazing Basics".to_string()),
+      at_secs: 65.0,
+      timestamp: "01:05".to_string(),
+      snippet: "Now wet the paper before the wash.".to_string(),
+      url: "https://youtube.com/watch?v=abc&t=65".to_string(),
+    };
+    assert!(App::hit_matches_filter(&hit, "WET"));
+    assert!(App::hit_matches_filter(&hit, "glazing"));
+    assert!(!App::hit_matches_filter(&hit, "01:05"));
+  }
 }
@@ -0,0 +1,298 @@
+//! Cross-video boilerplate: the intro and outro a channel repeats in every video.
+//!
+//! A channel's model is built from the stored transcripts of its videos
+//! (`yp boilerplate @channel`). Every sentence is cut into overlapping word
+//! shingles; shingles that occur in enough *different* videos are boilerplate.
+//! When summarizing a video from that channel, sentences mostly covered by
+//! those shingles are reclassified as `UtteranceClass::Boilerplate` and
+//! suppressed in `reduce`. Models are stored as
+//! `<cache_dir>/yp/boilerplate/<channel>.json` and looked up by uploader name.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::summarize::{self, ClassifiedUtterance, ClassifierConfig, UtteranceClass};
+
+/// Words per shingle. Shorter sentences are one shingle on their own.
+const SHINGLE_WORDS: usize = 4;
+
+/// A learned boilerplate phrase and how many videos contain it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Phrase {
+  pub text: String,
+  pub videos: usize,
+}
+
+/// Boilerplate shingles for one channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoilerplateModel {
+  /// Channel handle or URL the model was built for.
+  pub channel: String,
+  /// Uploader name, used to find the model for a video being summarized.
+  pub uploader: Option<String>,
+  /// Number of transcripts the model was built from.
+  pub videos: usize,
+  /// Build time (RFC 3339).
+  pub built_at: String,
+  /// Longest runs of boilerplate shingles, most widespread first (for inspection).
+  pub phrases: Vec<Phrase>,
+  /// Sorted boilerplate shingles (normalized text).
+  pub shingles: Vec<String>,
+}
+
+/// Overlapping word shingles of a normalized sentence.
+fn shingles(normalized: &str) -> Vec<String> {
+  let words: Vec<&str> = normalized.split_whitespace().collect();
+  match words.len() {
+    0 | 1 => Vec::new(),
+    n if n < SHINGLE_WORDS => vec![words.join(" ")],
+    _ => words.windows(SHINGLE_WORDS).map(|w| w.join(" ")).collect(),
+  }
+}
+
+/// Minimum number of videos a shingle must appear in to count as boilerplate.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss, clippy::cast_sign_loss)]
+fn min_videos(total: usize, cfg: &ClassifierConfig) -> usize {
+  let by_share = (total as f64 * cfg.boilerplate_min_share).ceil() as usize;
+  by_share.max(cfg.boilerplate_min_videos).max(2)
+}
+
+impl BoilerplateModel {
+  /// Build a model from the sentences of each video's transcript.
+  ///
+  /// With fewer than `cfg.boilerplate_min_videos` transcripts the model is empty.
+  pub fn build(channel: &str, uploader: Option<String>, videos: &[Vec<String>], cfg: &ClassifierConfig) -> Self {
+    let threshold = min_videos(videos.len(), cfg);
+    let normalized: Vec<Vec<String>> =
+      videos.iter().map(|sentences| sentences.iter().map(|s| summarize::normalize(s)).collect()).collect();
+
+    // Document frequency: in how many videos each shingle appears.
+    let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+    let per_video: Vec<Vec<Vec<String>>> =
+      normalized.iter().map(|sentences| sentences.iter().map(|s| shingles(s)).collect()).collect();
+    for video in &per_video {
+      let seen: HashSet<&str> = video.iter().flatten().map(String::as_str).collect();
+      for shingle in seen {
+        *doc_freq.entry(shingle).or_default() += 1;
+      }
+    }
+    let common: HashSet<&str> = doc_freq.iter().filter(|&(_, &n)| n >= threshold).map(|(s, _)| *s).collect();
+
+    // Phrases: maximal runs of common shingles within a sentence, counted per video.
+    let mut phrase_videos: HashMap<String, usize> = HashMap::new();
+    for (sentences, video) in normalized.iter().zip(&per_video) {
+      let mut seen: HashSet<String> = HashSet::new();
+      for (sentence, sentence_shingles) in sentences.iter().zip(video) {
+        let words: Vec<&str> = sentence.split_whitespace().collect();
+        let mut i = 0;
+        while i < sentence_shingles.len() {
+          if !common.contains(sentence_shingles[i].as_str()) {
+            i += 1;
+            continue;
+          }
+          let start = i;
+          while i < sentence_shingles.len() && common.contains(sentence_shingles[i].as_str()) {
+            i += 1;
+          }
+          let end = (i - 1 + SHINGLE_WORDS).min(words.len());
+          seen.insert(words[start..end].join(" "));
+        }
+      }
+      for phrase in seen {
+        *phrase_videos.entry(phrase).or_default() += 1;
+      }
+    }
+    let mut ranked: Vec<Phrase> = phrase_videos
+      .into_iter()
+      .filter(|&(_, n)| n >= threshold)
+      .map(|(text, videos)| Phrase { text, videos })
+      .collect();
+    ranked.sort_by(|a, b| b.videos.cmp(&a.videos).then(b.text.len().cmp(&a.text.len())).then(a.text.cmp(&b.text)));
+    let mut phrases: Vec<Phrase> = Vec::new();
+    for phrase in ranked {
+      if !phrases.iter().any(|p| p.text.contains(&phrase.text)) {
+        phrases.push(phrase);
+      }
+    }
+
+    let mut shingles: Vec<String> = common.into_iter().map(str::to_string).collect();
+    shingles.sort_unstable();
+    Self {
+      channel: channel.to_string(),
+      uploader,
+      videos: videos.len(),
+      built_at: chrono::Local::now().to_rfc3339(),
+      phrases,
+      shingles,
+    }
+  }
+
+  /// Share (0–1) of a sentence's shingles that are boilerplate.
+  #[allow(clippy::cast_precision_loss)]
+  pub fn coverage(&self, text: &str) -> f64 {
+    let sentence_shingles = shingles(&summarize::normalize(text));
+    if sentence_shingles.is_empty() {
+      return 0.0;
+    }
+    let hits = sentence_shingles.iter().filter(|s| self.shingles.binary_search(s).is_ok()).count();
+    hits as f64 / sentence_shingles.len() as f64
+  }
+
+  /// Reclassify kept utterances mostly covered by boilerplate. Returns how many changed.
+  ///
+  /// `TopicShift` utterances are left alone: they start the topic segments.
+  pub fn mark(&self, classified: &mut [ClassifiedUtterance], cfg: &ClassifierConfig) -> usize {
+    let mut marked = 0;
+    for u in classified.iter_mut() {
+      if summarize::is_kept(&u.class)
+        && u.class != UtteranceClass::TopicShift
+        && self.coverage(&u.text) >= cfg.boilerplate_coverage
+      {
+        u.class = UtteranceClass::Boilerplate;
+        marked += 1;
+      }
+    }
+    marked
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Persistence
+// ---------------------------------------------------------------------------
+
+/// Return the model directory: `<cache_dir>/yp/boilerplate`.
+fn model_dir() -> Option<PathBuf> {
+  directories::ProjectDirs::from("", "", "yp").map(|d| d.cache_dir().join("boilerplate"))
+}
+
+/// File-name-safe key for a channel handle or URL.
+fn channel_key(channel: &str) -> String {
+  let trimmed = channel.trim().trim_end_matches('/');
+  let tail = match trimmed.find('@') {
+    Some(i) => trimmed[i + 1..].split('/').next().unwrap_or_default(),
+    None => trimmed.rsplit('/').next().unwrap_or(trimmed),
+  };
+  let key: String = tail
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c.to_ascii_lowercase() } else { '-' })
+    .collect();
+  let key = key.trim_matches('-');
+  if key.is_empty() { "channel".to_string() } else { key.to_string() }
+}
+
+/// Write a model, replacing any earlier model for the same channel.
+pub fn save(model: &BoilerplateModel) -> Result<PathBuf> {
+  let dir = model_dir().context("No cache directory available")?;
+  fs::create_dir_all(&dir).context("Failed to create boilerplate directory")?;
+  let path = dir.join(format!("{}.json", channel_key(&model.channel)));
+  let json = serde_json::to_string(model).context("Failed to serialize boilerplate model")?;
+  fs::write(&path, json).context("Failed to write boilerplate model")?;
+  Ok(path)
+}
+
+/// Find the stored model for a video's uploader.
+pub fn load_for_uploader(uploader: &str) -> Option<BoilerplateModel> {
+  let entries = fs::read_dir(model_dir()?).ok()?;
+  entries
+    .flatten()
+    .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+    .filter_map(|e| fs::read_to_string(e.path()).ok())
+    .filter_map(|content| serde_json::from_str::<BoilerplateModel>(&content).ok())
+    .find(|m| m.uploader.as_deref() == Some(uploader))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn video(i: usize) -> Vec<String> {
+    vec![
+      "Hey everyone, welcome back to the studio!".to_string(),
+      format!("Today we paint subject number {i} with a fresh palette {i}."),
+      format!("The technique for lesson {i} is different from the others."),
+      "Thanks for watching, and don't forget to subscribe.".to_string(),
+    ]
+  }
+
+  fn model() -> BoilerplateModel {
+    let videos: Vec<Vec<String>> = (0..5).map(video).collect();
+    BoilerplateModel::build("@Painter", Some("Painter".to_string()), &videos, &ClassifierConfig::default())
+  }
+
+  #[test]
+  fn shingles_overlap_and_short_sentences_stay_whole() {
+    assert_eq!(shingles("a b c d e"), vec!["a b c d", "b c d e"]);
+    assert_eq!(shingles("thanks for watching"), vec!["thanks for watching"]);
+    assert!(shingles("okay").is_empty());
+  }
+
+  #[test]
+  fn learns_intro_and_outro() {
+    let model = model();
+    assert_eq!(model.videos, 5);
+    let phrases: Vec<&str> = model.phrases.iter().map(|p| p.text.as_str()).collect();
+    assert!(phrases.contains(&"hey everyone welcome back to the studio"), "{phrases:?}");
+    assert!(phrases.contains(&"thanks for watching and don t forget to subscribe"), "{phrases:?}");
+    assert!(model.phrases.iter().all(|p| p.videos == 5));
+    assert!(model.coverage("Hey everyone, welcome back to the studio.") > 0.99);
+    assert!(model.coverage("Today we paint subject number 9 with a fresh palette 9.") < 0.6);
+  }
+
+  #[test]
+  fn too_few_videos_learn_nothing() {
+    let videos: Vec<Vec<String>> = (0..2).map(video).collect();
+    let model = BoilerplateModel::build("@Painter", None, &videos, &ClassifierConfig::default());
+    assert!(model.shingles.is_empty());
+    assert!(model.phrases.is_empty());
+  }
+
+  #[test]
+  fn mark_reclassifies_kept_utterances_but_not_topic_shifts() {
+    let mut classified = vec![
+      ClassifiedUtterance {
+        start: 0.0,
+        end: 2.0,
+        text: "Hey everyone, welcome back to the studio!".to_string(),
+        class: UtteranceClass::Normal,
+      },
+      ClassifiedUtterance {
+        start: 2.0,
+        end: 4.0,
+        text: "Today we glaze the mountains.".to_string(),
+        class: UtteranceClass::Normal,
+      },
+      ClassifiedUtterance {
+        start: 4.0,
+        end: 6.0,
+        text: "Thanks for watching, and don't forget to subscribe.".to_string(),
+        class: UtteranceClass::Repetition,
+      },
+      ClassifiedUtterance {
+        start: 6.0,
+        end: 8.0,
+        text: "Hey everyone, welcome back to the studio!".to_string(),
+        class: UtteranceClass::TopicShift,
+      },
+    ];
+    assert_eq!(model().mark(&mut classified, &ClassifierConfig::default()), 1);
+    assert_eq!(classified[0].class, UtteranceClass::Boilerplate);
+    assert_eq!(classified[1].class, UtteranceClass::Normal);
+    assert_eq!(classified[2].class, UtteranceClass::Repetition);
+    assert_eq!(classified[3].class, UtteranceClass::TopicShift);
+  }
+
+  #[test]
+  fn channel_keys_are_file_safe() {
+    assert_eq!(channel_key("@ChrisH-v4e"), "chrish-v4e");
+    assert_eq!(channel_key("https://www.youtube.com/@Pastel.Sketchbook/videos"), "pastel-sketchbook");
+    assert_eq!(channel_key("https://www.youtube.com/channel/UCabc123"), "ucabc123");
+    assert_eq!(channel_key("///"), "channel");
+  }
+}
@@ -1,9 +1,10 @@
-//! Video ID cache for shell completions.
+//! Video ID and channel caches for shell completions.
 //!
 //! Stores `video_id\ttitle` lines in a TSV file under the OS cache directory
 //! (`~/Library/Caches/yp/videos.tsv` on macOS). Entries are appended by CLI
-//! commands (`channel`, `search`, `info`) and read by `_complete-ids` to
-//! provide dynamic zsh completions.
+//! commands (`channel`, `search`, `info`); channel handles that a command
+//! listed successfully go to `channels.txt` next to it. Both are read by
+//! `_complete` to provide dynamic shell completions.
 
 use anyhow::{Context, Result};
 use std::collections::HashMap;
@@ -14,6 +15,9 @@ use std::path::PathBuf;
 /// Maximum number of entries to keep in the cache (oldest are evicted on compaction).
 const MAX_ENTRIES: usize = 2000;
 
+/// Maximum number of recently used channels to remember.
+const MAX_CHANNELS: usize = 100;
+
 /// Return the cache file path: `<cache_dir>/yp/videos.tsv`.
 fn cache_path() -> Option<PathBuf> {
   directories::ProjectDirs::from("", "", "yp").map(|d| d.cache_dir().join("videos.tsv"))
@@ -73,6 +77,66 @@ pub fn read_videos() -> Vec<(String, String)> {
   dedup(read_raw(&path))
 }
 
+fn channels_path() -> Option<PathBuf> {
+  directories::ProjectDirs::from("", "", "yp").map(|d| d.cache_dir().join("channels.txt"))
+}
+
+/// Remember a channel the user just used, as its `@handle` when it has one.
+pub fn remember_channel(channel: &str) -> Result<()> {
+  let Some(path) = channels_path() else { return Ok(()) }; // silently skip if no cache dir
+  let Some(channel) = completion_form(channel) else { return Ok(()) };
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).context("Failed to create cache directory")?;
+  }
+
+  // Concurrent yp processes would otherwise drop each other's channels.
+  let lock = fs::OpenOptions::new()
+    .create(true)
+    .write(true)
+    .truncate(false)
+    .open(path.with_extension("lock"))
+    .context("Failed to open channel cache lock")?;
+  lock.lock().context("Failed to lock channel cache")?;
+
+  let mut channels = read_lines(&path);
+  channels.retain(|c| *c != channel);
+  channels.push(channel);
+  if channels.len() > MAX_CHANNELS {
+    channels.drain(..channels.len() - MAX_CHANNELS);
+  }
+
+  let tmp = path.with_extension("txt.tmp");
+  fs::write(&tmp, channels.join("\n") + "\n").context("Failed to write channel cache")?;
+  fs::rename(&tmp, &path).context("Failed to rename channel cache temp file")?;
+  Ok(())
+}
+
+/// Recently used channels, most recent first.
+pub fn read_channels() -> Vec<String> {
+  let Some(path) = channels_path() else { return Vec::new() };
+  let mut channels = read_lines(&path);
+  channels.reverse();
+  channels
+}
+
+/// `@handle` for handles and handle URLs; other channel URLs as given.
+fn completion_form(channel: &str) -> Option<String> {
+  let trimmed = channel.trim().trim_end_matches('/');
+  if trimmed.is_empty() || trimmed.contains(char::is_whitespace) {
+    return None;
+  }
+  match trimmed.find('@') {
+    Some(i) => trimmed[i..].split('/').next().filter(|h| h.len() > 1).map(str::to_string),
+    None => Some(trimmed.to_string()),
+  }
+}
+
+fn read_lines(path: &PathBuf) -> Vec<String> {
+  fs::read_to_string(path)
+    .map(|content| content.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect())
+    .unwrap_or_default()
+}
+
 /// Internal: read lines from the TSV file without deduplication.
 fn read_raw(path: &PathBuf) -> Vec<(String, String)> {
   let Ok(file) = fs::File::open(path) else { return Vec::new() };
@@ -145,6 +209,17 @@ mod tests {
     assert!(entries.is_empty());
   }
 
+  #[test]
+  fn channels_are_remembered_as_handles() {
+    assert_eq!(completion_form("https://www.youtube.com/@ChrisH-v4e/videos").as_deref(), Some("@ChrisH-v4e"));
+    assert_eq!(completion_form("@TwoSetViolin").as_deref(), Some("@TwoSetViolin"));
+    assert_eq!(
+      completion_form("https://www.youtube.com/channel/UC123/").as_deref(),
+      Some("https://www.youtube.com/channel/UC123")
+    );
+    assert_eq!(completion_form("two words"), None);
+  }
+
   #[test]
   fn read_videos_no_panic() {
     // Smoke test: doesn't panic even if cache doesn't exist.
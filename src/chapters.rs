@@ -0,0 +1,273 @@
+//! YouTube chapter lists drafted from a summary's topic segments.
+//!
+//! Every `TopicSegment` start is a candidate boundary. YouTube only shows
+//! chapters when the list starts at 0:00, has at least three entries and every
+//! chapter lasts at least ten seconds, so the first boundary is moved to 0:00,
+//! short chapters are merged into a neighbour, and when fewer than three remain
+//! the longest chapter is split at the utterance closest to its middle.
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+
+use crate::keywords;
+use crate::summarize::{self, SummaryOutput};
+
+/// Shortest chapter YouTube accepts, in seconds.
+pub const MIN_CHAPTER_SECS: f64 = 10.0;
+/// Fewest chapters YouTube accepts.
+pub const MIN_CHAPTERS: usize = 3;
+/// Longest chapter title, in characters.
+const MAX_TITLE_CHARS: usize = 60;
+
+/// One chapter of the list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Chapter {
+  pub start_secs: f64,
+  pub end_secs: f64,
+  /// `m:ss` / `h:mm:ss` start, as written in the description.
+  pub timestamp: String,
+  pub title: String,
+}
+
+impl Chapter {
+  fn len(&self) -> f64 {
+    self.end_secs - self.start_secs
+  }
+}
+
+/// Sentence-case a title and cap its length.
+fn tidy_title(text: &str) -> String {
+  let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+  let text = text.trim_end_matches(['.', '!', '?', ',', ';', ':']);
+  let mut chars = text.chars();
+  let title: String = match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars).collect(),
+    None => String::new(),
+  };
+  if title.chars().count() <= MAX_TITLE_CHARS {
+    return title;
+  }
+  let cut: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+  format!("{}…", cut.trim_end())
+}
+
+/// Title from the LLM topic title, else the top keyphrase, else the first sentence.
+fn title_for(output: &SummaryOutput, topic: &summarize::TopicSegment) -> String {
+  if let Some(title) = summarize::topic_title(output, topic.start_secs) {
+    return tidy_title(title);
+  }
+  match topic.keywords.first() {
+    Some(k) => tidy_title(&k.phrase),
+    None => tidy_title(&summarize::first_sentence(&topic.summary)),
+  }
+}
+
+/// Title for a stretch of the video without its own topic: its top keyphrase or first kept sentence.
+fn title_between(output: &SummaryOutput, from: f64, to: f64) -> String {
+  let sentences: Vec<(f64, &str)> = output
+    .utterances
+    .iter()
+    .filter(|u| summarize::is_kept(&u.class) && u.start >= from && u.start < to)
+    .map(|u| (u.start, u.text.as_str()))
+    .collect();
+  if let Some(k) = keywords::video_keywords(&sentences, 1).first() {
+    return tidy_title(&k.phrase);
+  }
+  sentences.first().map(|(_, text)| tidy_title(&summarize::first_sentence(text))).unwrap_or_default()
+}
+
+/// Merge chapters shorter than `MIN_CHAPTER_SECS` into a neighbour.
+///
+/// The first chapter merges forward, any other backward; the merged chapter
+/// keeps the title of the longer part.
+fn merge_short(chapters: &mut Vec<Chapter>) {
+  while chapters.len() > 1 {
+    let Some(i) = chapters.iter().position(|c| c.len() < MIN_CHAPTER_SECS) else { break };
+    let (keep, gone) = if i == 0 { (0, 1) } else { (i - 1, i) };
+    let removed = chapters.remove(gone);
+    let kept = &mut chapters[keep];
+    if removed.len() > kept.len() {
+      kept.title = removed.title;
+    }
+    kept.start_secs = kept.start_secs.min(removed.start_secs);
+    kept.end_secs = kept.end_secs.max(removed.end_secs);
+  }
+}
+
+/// Split the longest chapter near its middle (at a kept utterance start when one
+/// leaves both halves long enough). Returns `false` when no chapter can be split.
+fn split_longest(chapters: &mut Vec<Chapter>, output: &SummaryOutput) -> bool {
+  let Some((i, longest)) = chapters.iter().enumerate().max_by(|a, b| a.1.len().total_cmp(&b.1.len())) else {
+    return false;
+  };
+  if longest.len() < 2.0 * MIN_CHAPTER_SECS {
+    return false;
+  }
+  let (start, end) = (longest.start_secs, longest.end_secs);
+  let middle = f64::midpoint(start, end);
+  let at = output
+    .utterances
+    .iter()
+    .filter(|u| summarize::is_kept(&u.class))
+    .map(|u| u.start)
+    .filter(|&s| s - start >= MIN_CHAPTER_SECS && end - s >= MIN_CHAPTER_SECS)
+    .min_by(|a, b| (a - middle).abs().total_cmp(&(b - middle).abs()))
+    .unwrap_or(middle);
+
+  let mut second = chapters[i].clone();
+  chapters[i].end_secs = at;
+  second.start_secs = at;
+  second.timestamp = summarize::timestamp(at);
+  second.title = title_between(output, at, end);
+  if second.title.is_empty() || second.title == chapters[i].title {
+    second.title = format!("{} (continued)", chapters[i].title);
+  }
+  chapters.insert(i + 1, second);
+  true
+}
+
+/// Draft chapters for a summarized video that lasts `duration_secs`.
+///
+/// Fails when the video is too short for YouTube to show chapters at all.
+pub fn build(output: &SummaryOutput, duration_secs: f64) -> Result<Vec<Chapter>> {
+  let end = duration_secs.max(output.summary.time_range.1);
+  if end < MIN_CHAPTER_SECS * MIN_CHAPTERS as f64 {
+    bail!(
+      "The video is {} long; YouTube needs at least {MIN_CHAPTERS} chapters of {MIN_CHAPTER_SECS}s",
+      summarize::timestamp(end)
+    );
+  }
+
+  let topics = &output.summary.topics;
+  let mut chapters: Vec<Chapter> = topics
+    .iter()
+    .enumerate()
+    .map(|(i, topic)| {
+      let start = if i == 0 { 0.0 } else { topic.start_secs };
+      let next = topics.get(i + 1).map_or(end, |t| t.start_secs);
+      Chapter { start_secs: start, end_secs: next, timestamp: String::new(), title: title_for(output, topic) }
+    })
+    .collect();
+  if chapters.is_empty() {
+    chapters.push(Chapter {
+      start_secs: 0.0,
+      end_secs: end,
+      timestamp: String::new(),
+      title: title_between(output, 0.0, end),
+    });
+  }
+  if let Some(last) = chapters.last_mut() {
+    last.end_secs = end;
+  }
+
+  merge_short(&mut chapters);
+  while chapters.len() < MIN_CHAPTERS && split_longest(&mut chapters, output) {}
+
+  for (i, chapter) in chapters.iter_mut().enumerate() {
+    chapter.timestamp = summarize::timestamp(chapter.start_secs);
+    if chapter.title.is_empty() {
+      chapter.title = format!("Part {}", i + 1);
+    }
+  }
+  Ok(chapters)
+}
+
+/// The `0:00 Title` lines to paste into a video description.
+pub fn description(chapters: &[Chapter]) -> String {
+  chapters.iter().map(|c| format!("{} {}\n", c.timestamp, c.title)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::keywords::Keyword;
+  use crate::summarize::{ClassifiedUtterance, TopicSegment, UtteranceClass};
+
+  fn topic(start: f64, end: f64, keyword: &str) -> TopicSegment {
+    TopicSegment {
+      start_secs: start,
+      end_secs: end,
+      summary: format!("We talk about {keyword}. More."),
+      utterance_count: 3,
+      keywords: vec![Keyword { phrase: keyword.to_string(), score: 1.0, first_at_secs: start }],
+    }
+  }
+
+  fn output(topics: Vec<TopicSegment>, end: f64) -> SummaryOutput {
+    let utterances = (0..)
+      .map(|i| f64::from(i) * 5.0)
+      .take_while(|s| *s < end)
+      .map(|start| ClassifiedUtterance {
+        start,
+        end: start + 4.0,
+        text: format!("Now the shading pass number {start}."),
+        class: UtteranceClass::Normal,
+      })
+      .collect();
+    let mut output = SummaryOutput::fixture("Test", end);
+    output.summary.topics = topics;
+    output.utterances = utterances;
+    output
+  }
+
+  fn starts(chapters: &[Chapter]) -> Vec<f64> {
+    chapters.iter().map(|c| c.start_secs).collect()
+  }
+
+  #[test]
+  fn first_chapter_starts_at_zero() {
+    let out =
+      output(vec![topic(3.0, 60.0, "sky wash"), topic(60.0, 120.0, "mountains"), topic(120.0, 180.0, "glaze")], 180.0);
+    let chapters = build(&out, 180.0).unwrap();
+    assert_eq!(starts(&chapters), vec![0.0, 60.0, 120.0]);
+    assert_eq!(description(&chapters), "0:00 Sky wash\n1:00 Mountains\n2:00 Glaze\n");
+  }
+
+  #[test]
+  fn short_chapters_merge_into_neighbours() {
+    let out = output(
+      vec![
+        topic(0.0, 4.0, "hello"),
+        topic(4.0, 60.0, "sky wash"),
+        topic(60.0, 65.0, "aside"),
+        topic(65.0, 120.0, "mountains"),
+        topic(120.0, 180.0, "glaze"),
+      ],
+      180.0,
+    );
+    let chapters = build(&out, 180.0).unwrap();
+    assert_eq!(starts(&chapters), vec![0.0, 65.0, 120.0]);
+    let titles: Vec<&str> = chapters.iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, vec!["Sky wash", "Mountains", "Glaze"]);
+    assert!(chapters.iter().all(|c| c.len() >= MIN_CHAPTER_SECS));
+  }
+
+  #[test]
+  fn too_few_topics_are_split() {
+    let out = output(vec![topic(0.0, 100.0, "sky wash")], 100.0);
+    let chapters = build(&out, 100.0).unwrap();
+    assert_eq!(chapters.len(), MIN_CHAPTERS);
+    assert_eq!(chapters[0].start_secs, 0.0);
+    assert!(chapters.iter().all(|c| c.len() >= MIN_CHAPTER_SECS), "{chapters:?}");
+    assert!(chapters.windows(2).all(|w| w[0].end_secs == w[1].start_secs));
+    assert_eq!(chapters.last().unwrap().end_secs, 100.0);
+  }
+
+  #[test]
+  fn short_videos_have_no_chapters() {
+    let out = output(vec![topic(0.0, 20.0, "sky wash")], 20.0);
+    assert!(build(&out, 20.0).is_err());
+  }
+
+  #[test]
+  fn titles_are_tidied() {
+    assert_eq!(tidy_title("  wet   glaze. "), "Wet glaze");
+    let long = tidy_title(&"word ".repeat(30));
+    assert_eq!(long.chars().count(), MAX_TITLE_CHARS);
+    assert!(long.ends_with('…'));
+  }
+}
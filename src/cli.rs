@@ -3,13 +3,27 @@
 //! Each handler calls the core YouTube/transcription functions directly,
 //! writes JSON to stdout, and progress/errors to stderr.
 
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result};
 use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::mpsc;
 
+use crate::boilerplate;
 use crate::cache;
+use crate::chapters;
+use crate::clip;
+use crate::completions;
+use crate::digest;
+use crate::error::{self, ErrorKind};
+use crate::index;
+use crate::output::{ListOptions, OutputFormat, RecordWriter};
+use crate::schema::{self, VideoEntry, VideoInfo};
+use crate::segment;
+use crate::store;
 use crate::summarize;
-use crate::transcript::TranscriptEvent;
+use crate::textrank;
+use crate::transcript::{TranscriptEvent, WhisperCache};
+use crate::transcript_file;
+use crate::watch;
 use crate::youtube;
 
 // ---------------------------------------------------------------------------
@@ -47,185 +61,56 @@ pub fn extract_video_id(input: &str) -> String {
   trimmed.to_string()
 }
 
-/// Print a JSON error to stdout and return Ok(()) for clean CLI exit.
-fn print_json_error(error_code: &str, message: &str) -> Result<()> {
-  let err = serde_json::json!({
-    "error": error_code,
-    "message": message,
-  });
-  println!("{}", serde_json::to_string(&err).context("Failed to serialize error JSON")?);
-  Ok(())
+/// Resolve a channel handle or URL to its videos page.
+pub(crate) fn channel_url(channel: &str) -> Result<String> {
+  youtube::detect_channel_url(channel)
+    .ok_or_else(|| error::fail(ErrorKind::InvalidInput, format!("Could not detect channel URL from: {channel}")))
 }
 
-// ---------------------------------------------------------------------------
-// Shell completions
-// ---------------------------------------------------------------------------
+/// Cache listed video IDs and titles for shell completion (also used by `mcp` and `serve`).
+pub(crate) fn remember_videos(entries: &[youtube::SearchEntry]) {
+  let pairs: Vec<(&str, &str)> = entries.iter().map(|e| (e.video_id.as_str(), e.title.as_str())).collect();
+  if let Err(e) = cache::append_videos(&pairs) {
+    tracing::warn!("Failed to update video cache: {}", e);
+  }
+}
 
-/// Generate a custom zsh completion script with dynamic video ID support.
-///
-/// For the `info`, `transcript`, and `summarize` subcommands, the `video`
-/// positional argument is completed by calling `yp _complete-ids` which reads
-/// from the local cache (populated by `channel`, `search`, and `info`).
-pub fn generate_zsh_completions() {
-  print!(
-    r#"#compdef yp
-
-_yp_video_ids() {{
-  local -a ids
-  ids=(${{(f)"$(yp _complete-ids 2>/dev/null)"}})
-  _describe 'video' ids
-}}
-
-_yp() {{
-  local -a commands
-  commands=(
-    'completions:Generate shell completions'
-    'search:Search YouTube and return results as JSON'
-    'channel:List videos from a YouTube channel (JSONL)'
-    'info:Fetch metadata for a specific video (JSON)'
-    'transcript:Transcribe a video (JSONL)'
-    'summarize:Transcribe + classify + reduce to a summary (JSON)'
-  )
-
-  _arguments -C \
-    '-d+[Display mode]:mode:(auto kitty sixel direct ascii)' \
-    '--display-mode+[Display mode]:mode:(auto kitty sixel direct ascii)' \
-    '-h[Show help]' \
-    '--help[Show help]' \
-    '-V[Show version]' \
-    '--version[Show version]' \
-    '1:command:->cmd' \
-    '*::arg:->args'
-
-  case $state in
-    cmd)
-      _describe 'command' commands
-      ;;
-    args)
-      case $words[1] in
-        completions)
-          _arguments '1:shell:(bash zsh fish elvish powershell)'
-          ;;
-        search)
-          _arguments \
-            '1:query:' \
-            '-l+[Max results]:limit:' \
-            '--limit+[Max results]:limit:'
-          ;;
-        channel)
-          _arguments \
-            '1::channel:' \
-            '-l+[Max videos]:limit:' \
-            '--limit+[Max videos]:limit:' \
-            '-a[Fetch all videos]' \
-            '--all[Fetch all videos]' \
-            '-e[Enrich with tags]' \
-            '--enrich[Enrich with tags]' \
-            '-j+[Concurrent jobs]:jobs:' \
-            '--jobs+[Concurrent jobs]:jobs:'
-          ;;
-        info)
-          _arguments '1:video:_yp_video_ids'
-          ;;
-        transcript)
-          _arguments \
-            '1::video:_yp_video_ids' \
-            '-r[Output raw utterances]' \
-            '--raw[Output raw utterances]'
-          ;;
-        summarize)
-          _arguments \
-            '1::video:_yp_video_ids' \
-            '--latest+[Summarize latest N from channel]:count:' \
-            '-r[Output raw transcript]' \
-            '--raw[Output raw transcript]'
-          ;;
-      esac
-      ;;
-  esac
-}}
-
-_yp "$@"
-"#
-  );
+/// Remember a channel for shell completion once listing it has succeeded,
+/// so typos never become completions.
+fn remember_channel(channel: &str) {
+  if let Err(e) = cache::remember_channel(channel) {
+    tracing::warn!("Failed to update channel cache: {}", e);
+  }
 }
 
 // ---------------------------------------------------------------------------
 // Subcommand: search
 // ---------------------------------------------------------------------------
 
-/// Search `YouTube` and output results as a JSON array.
-pub async fn cmd_search(query: &str, limit: usize) -> Result<()> {
+/// Search `YouTube` and output results (a JSON array by default).
+pub async fn cmd_search(query: &str, limit: usize, list: &ListOptions) -> Result<()> {
+  let mut out = RecordWriter::new(list, OutputFormat::Json, &VideoEntry::FIELDS)?;
   eprintln!("Searching YouTube for: {query}");
   let mut results = youtube::search_youtube(query).await.context("YouTube search failed")?;
   results.truncate(limit);
+  remember_videos(&results);
 
-  // Cache video IDs for shell completion.
-  let cache_pairs: Vec<(&str, &str)> = results.iter().map(|e| (e.video_id.as_str(), e.title.as_str())).collect();
-  if let Err(e) = cache::append_videos(&cache_pairs) {
-    tracing::warn!("Failed to update video cache: {}", e);
+  for entry in &results {
+    out.write(&VideoEntry::new(entry, None))?;
   }
-
-  let json = serde_json::to_string_pretty(&results).context("Failed to serialize search results")?;
-  println!("{json}");
-  Ok(())
+  out.finish()
 }
 
 // ---------------------------------------------------------------------------
 // Subcommand: channel
 // ---------------------------------------------------------------------------
 
-/// Build a JSON object for a channel entry, optionally merging enriched metadata.
-/// `SearchEntry` fields (from flat-playlist) are used as defaults; `VideoMeta` overrides when present.
-fn channel_entry_json(entry: &youtube::SearchEntry, meta: Option<&youtube::VideoMeta>) -> serde_json::Value {
-  let mut obj = serde_json::json!({
-    "video_id": entry.video_id,
-    "title": entry.title,
-    "url": format!("https://youtube.com/watch?v={}", entry.video_id),
-  });
-  // Populate from SearchEntry (flat-playlist data)
-  if let Some(date) = &entry.upload_date {
-    obj["upload_date"] = serde_json::Value::String(date.clone());
-  }
-  if let Some(tags) = &entry.tags {
-    obj["tags"] = serde_json::Value::String(tags.clone());
-  }
-  if let Some(dur) = &entry.duration {
-    obj["duration"] = serde_json::Value::String(dur.clone());
-  }
-  if let Some(vc) = &entry.view_count {
-    obj["view_count"] = serde_json::Value::String(vc.clone());
-  }
-  if let Some(up) = &entry.uploader {
-    obj["uploader"] = serde_json::Value::String(up.clone());
-  }
-  // Override with enriched metadata when available
-  if let Some(meta) = meta {
-    if let Some(date) = &meta.upload_date {
-      obj["upload_date"] = serde_json::Value::String(date.clone());
-    }
-    if let Some(tags) = &meta.tags {
-      obj["tags"] = serde_json::Value::String(tags.clone());
-    }
-    if let Some(dur) = &meta.duration {
-      obj["duration"] = serde_json::Value::String(dur.clone());
-    }
-    if let Some(vc) = &meta.view_count {
-      obj["view_count"] = serde_json::Value::String(vc.clone());
-    }
-    if let Some(up) = &meta.uploader {
-      obj["uploader"] = serde_json::Value::String(up.clone());
-    }
-  }
-  obj
-}
-
 /// Write a single JSONL line to stdout and flush immediately.
 ///
 /// Explicit flushing is required because Rust uses full buffering when stdout
 /// is piped. Without it, downstream processes (e.g. `fzf`) won't see data
 /// until the 8 KB buffer fills or the process exits.
-fn write_jsonl(obj: &serde_json::Value) -> Result<()> {
+fn write_jsonl<T: serde::Serialize>(obj: &T) -> Result<()> {
   use std::io::Write;
   let stdout = std::io::stdout();
   let mut lock = stdout.lock();
@@ -235,24 +120,27 @@ fn write_jsonl(obj: &serde_json::Value) -> Result<()> {
   Ok(())
 }
 
-/// List videos from a `YouTube` channel, output as JSONL.
+/// List videos from a `YouTube` channel (JSONL by default).
 ///
 /// `count`: `Some(n)` for n videos, `None` for all.
 /// `jobs`: number of concurrent enrichment processes.
-pub async fn cmd_channel(channel: &str, count: Option<usize>, enrich: bool, jobs: usize) -> Result<()> {
-  let channel_url =
-    youtube::detect_channel_url(channel).ok_or_else(|| anyhow!("Could not detect channel URL from: {channel}"))?;
+pub async fn cmd_channel(
+  channel: &str,
+  count: Option<usize>,
+  enrich: bool,
+  jobs: usize,
+  list: &ListOptions,
+) -> Result<()> {
+  let mut out = RecordWriter::new(list, OutputFormat::Jsonl, &VideoEntry::FIELDS)?;
+  let channel_url = channel_url(channel)?;
 
   let label = count.map_or("all".to_string(), |n| n.to_string());
   eprintln!("Listing {label} videos from: {channel_url}");
   let entries = youtube::list_channel_videos(&channel_url, 1, count).await.context("Failed to list channel videos")?;
+  remember_channel(channel);
   eprintln!("Found {} videos", entries.len());
 
-  // Cache video IDs for shell completion.
-  let cache_pairs: Vec<(&str, &str)> = entries.iter().map(|e| (e.video_id.as_str(), e.title.as_str())).collect();
-  if let Err(e) = cache::append_videos(&cache_pairs) {
-    tracing::warn!("Failed to update video cache: {}", e);
-  }
+  remember_videos(&entries);
 
   if enrich && !entries.is_empty() {
     eprintln!("Enriching {} videos with metadata ({} concurrent)...", entries.len(), jobs);
@@ -271,7 +159,7 @@ pub async fn cmd_channel(channel: &str, count: Option<usize>, enrich: bool, jobs
     // Stream each enriched entry to stdout as it arrives.
     while let Some(meta) = rx.recv().await {
       if let Some(entry) = entry_map.get(&meta.video_id) {
-        write_jsonl(&channel_entry_json(entry, Some(&meta)))?;
+        out.write(&VideoEntry::new(entry, Some(&meta)))?;
         emitted.insert(meta.video_id.clone());
       }
     }
@@ -280,25 +168,26 @@ pub async fn cmd_channel(channel: &str, count: Option<usize>, enrich: bool, jobs
     // Output any entries that failed enrichment (so no data is silently lost).
     for entry in &entries {
       if !emitted.contains(&entry.video_id) {
-        write_jsonl(&channel_entry_json(entry, None))?;
+        out.write(&VideoEntry::new(entry, None))?;
       }
     }
   } else {
-    // Output as JSONL without enrichment (fast mode)
+    // Output without enrichment (fast mode)
     for entry in &entries {
-      write_jsonl(&channel_entry_json(entry, None))?;
+      out.write(&VideoEntry::new(entry, None))?;
     }
   }
 
-  Ok(())
+  out.finish()
 }
 
 // ---------------------------------------------------------------------------
 // Subcommand: info
 // ---------------------------------------------------------------------------
 
-/// Fetch metadata for a specific video, output as JSON.
-pub async fn cmd_info(video: &str) -> Result<()> {
+/// Fetch metadata for a specific video (a JSON object by default).
+pub async fn cmd_info(video: &str, list: &ListOptions) -> Result<()> {
+  let out = RecordWriter::new(list, OutputFormat::Json, &VideoInfo::FIELDS)?;
   let video_id = extract_video_id(video);
   eprintln!("Fetching info for video: {video_id}");
 
@@ -309,11 +198,7 @@ pub async fn cmd_info(video: &str) -> Result<()> {
     tracing::warn!("Failed to update video cache: {}", e);
   }
 
-  let mut obj = serde_json::to_value(&details).context("Failed to serialize video details")?;
-  obj["video_id"] = serde_json::Value::String(video_id);
-  let json = serde_json::to_string_pretty(&obj).context("Failed to format video info JSON")?;
-  println!("{json}");
-  Ok(())
+  out.write_one(&VideoInfo { video_id: &video_id, details: &details })
 }
 
 // ---------------------------------------------------------------------------
@@ -323,85 +208,191 @@ pub async fn cmd_info(video: &str) -> Result<()> {
 /// Transcribe a video and output utterances as JSONL.
 ///
 /// This runs the full whisper pipeline headlessly (no mpv, no TUI).
-#[allow(clippy::cast_precision_loss)]
-pub async fn cmd_transcript(video: &str, raw: bool) -> Result<()> {
+pub async fn cmd_transcript(video: &str, raw: bool, fresh: bool) -> Result<()> {
   let video_id = extract_video_id(video);
-  let url = format!("https://youtube.com/watch?v={video_id}");
   eprintln!("Transcribing video: {video_id}");
 
-  let utterances = run_transcription(&url, None).await?;
-
-  if raw {
-    // Output raw utterances as JSONL
-    for u in &utterances {
-      let obj = serde_json::json!({
-        "start": u.start as f64 / 100.0,
-        "end": u.stop as f64 / 100.0,
-        "text": u.text,
-      });
-      println!("{}", serde_json::to_string(&obj).context("Failed to serialize utterance")?);
-    }
-  } else {
-    // Classify and output with tags
-    let triples: Vec<(i64, i64, String)> = utterances.iter().map(|u| (u.start, u.stop, u.text.clone())).collect();
-    let classified = summarize::classify(&triples);
-    for u in &classified {
-      let json = serde_json::to_string(u).context("Failed to serialize classified utterance")?;
-      println!("{json}");
-    }
+  let utterances = run_transcription(&video_id, None, fresh).await?;
+  for line in transcript_lines(&utterances, raw)? {
+    println!("{line}");
   }
 
   Ok(())
 }
 
-/// Read JSONL from stdin (pipe mode), extract `video_id`, and transcribe.
-///
-/// Enables: `yp channel | fzf | yp transcript`
-pub async fn cmd_transcript_stdin(raw: bool) -> Result<()> {
-  use std::io::BufRead;
+/// Render utterances as versioned JSONL lines: raw `{start,end,text}` or classified.
+fn transcript_lines(utterances: &[whisper_cli::Utternace], raw: bool) -> Result<Vec<String>> {
+  versioned_lines(&transcript_values(utterances, raw)?)
+}
 
-  let stdin = std::io::stdin();
-  let line = stdin
-    .lock()
-    .lines()
-    .next()
-    .ok_or_else(|| anyhow!("No input on stdin. Provide a video ID or pipe from `yp channel | fzf`."))?
-    .context("Failed to read from stdin")?;
+/// Serialize values as JSONL lines, each carrying `schema_version`.
+fn versioned_lines(values: &[serde_json::Value]) -> Result<Vec<String>> {
+  values.iter().map(|v| serde_json::to_string(&schema::versioned(v)).context("Failed to serialize utterance")).collect()
+}
 
-  let trimmed = line.trim();
-  if trimmed.is_empty() {
-    return Err(anyhow!("Empty input on stdin."));
-  }
+/// Utterances as JSON values: raw `{start,end,text}` or classified.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn transcript_values(utterances: &[whisper_cli::Utternace], raw: bool) -> Result<Vec<serde_json::Value>> {
+  Ok(if raw {
+    utterances
+      .iter()
+      .map(|u| {
+        serde_json::json!({
+          "start": u.start as f64 / 100.0,
+          "end": u.stop as f64 / 100.0,
+          "text": u.text,
+        })
+      })
+      .collect()
+  } else {
+    classify_utterances(utterances, &summarize::ClassifierConfig::load()?)
+      .iter()
+      .map(|u| serde_json::to_value(u).unwrap_or_default())
+      .collect()
+  })
+}
 
-  // Try parsing as JSON to extract video_id field
-  if let Ok(obj) = serde_json::from_str::<serde_json::Value>(trimmed)
-    && let Some(id) = obj.get("video_id").and_then(|v| v.as_str())
+/// Classify + reduce a transcript, suppressing the channel's boilerplate unless disabled.
+pub(crate) fn reduce_transcript(
+  details: &crate::player::VideoDetails,
+  utterances: &[whisper_cli::Utternace],
+  opts: &SummarizeOptions<'_>,
+) -> summarize::SummaryOutput {
+  let mut classified = classify_utterances(utterances, opts.classifier);
+  if opts.boilerplate
+    && let Some(model) = details.uploader.as_deref().and_then(boilerplate::load_for_uploader)
   {
-    return cmd_transcript(id, raw).await;
+    let marked = model.mark(&mut classified, opts.classifier);
+    eprintln!("Suppressed {marked} boilerplate utterance(s) learned from {} videos of {}", model.videos, model.channel);
   }
+  summarize::reduce(details, &classified, opts.classifier)
+}
 
-  // Fall back: treat the whole line as a video ID or URL
-  cmd_transcript(trimmed, raw).await
+/// Read video IDs from stdin (pipe mode) and transcribe each of them.
+///
+/// Every line is a JSONL object with `video_id`, a URL, or a bare ID. Each
+/// video prints one `{video_id, status, utterances | error}` record, as
+/// `yp transcribe-batch` does, with up to `jobs` in flight — the same shape
+/// however many IDs arrive.
+///
+/// Enables: `yp channel | fzf -m | yp transcript`
+pub async fn cmd_transcript_stdin(raw: bool, fresh: bool, jobs: usize) -> Result<()> {
+  let video_ids = read_batch_ids(None)?;
+  match video_ids.as_slice() {
+    [] => Err(error::fail(
+      ErrorKind::InvalidInput,
+      "No input on stdin. Provide a video ID or pipe from `yp channel | fzf`.",
+    )),
+    _ => transcribe_ids(video_ids, jobs, None, raw, fresh).await,
+  }
 }
 
 // ---------------------------------------------------------------------------
 // Subcommand: summarize
 // ---------------------------------------------------------------------------
 
+/// Options shared by every `yp summarize` input mode.
+pub struct SummarizeOptions<'a> {
+  /// Output the unprocessed transcript instead of a summary.
+  pub raw: bool,
+  /// Ignore stored checkpoints and transcribe from the start.
+  pub fresh: bool,
+  pub classifier: &'a summarize::ClassifierConfig,
+  /// LLM backend settings when `--llm` is set.
+  pub llm: Option<&'a crate::llm::LlmConfig>,
+  /// Trim the summary to roughly this many tokens.
+  pub max_tokens: Option<usize>,
+  /// Output format for the summary (raw output is always JSON).
+  pub format: summarize::SummaryFormat,
+  /// Append the filtered transcript to Markdown/text output.
+  pub with_transcript: bool,
+  /// Suppress the channel's learned intro/outro (see `yp boilerplate`).
+  pub boilerplate: bool,
+}
+
 /// Transcribe + classify + reduce a video to a summary, output as JSON.
-#[allow(clippy::cast_precision_loss)]
-pub async fn cmd_summarize(video: &str, raw: bool) -> Result<()> {
+pub async fn cmd_summarize(video: &str, opts: &SummarizeOptions<'_>) -> Result<()> {
   let video_id = extract_video_id(video);
-  let url = format!("https://youtube.com/watch?v={video_id}");
 
   eprintln!("Fetching video info...");
   let details = youtube::get_video_info(&video_id).await.context("Failed to get video info")?;
 
   eprintln!("Transcribing video: {} — {}", video_id, details.title);
   let duration_hint = details.duration.as_deref().and_then(parse_duration_secs);
-  let utterances = run_transcription(&url, duration_hint).await?;
+  let utterances = run_transcription(&video_id, duration_hint, opts.fresh).await?;
+
+  let rendered = render_summary(&details, &utterances, opts).await?;
+  println!("{}", rendered.trim_end_matches('\n'));
+
+  eprintln!("Done.");
+  Ok(())
+}
+
+/// Classify + reduce an existing transcript file offline, output as JSON.
+///
+/// Metadata comes from `info` (`yp info` or yt-dlp JSON), the transcript file
+/// itself (`yp summarize --raw` output), or a placeholder titled after the file.
+pub async fn cmd_summarize_file(
+  path: &std::path::Path,
+  info: Option<&std::path::Path>,
+  opts: &SummarizeOptions<'_>,
+) -> Result<()> {
+  let file = transcript_file::load(path)?;
+  let details = match info {
+    Some(info) => transcript_file::load_info(info)?,
+    None => file.video.unwrap_or_else(|| transcript_file::details_from_path(path)),
+  };
+  eprintln!("Summarizing {} utterances from {} — {}", file.utterances.len(), path.display(), details.title);
+
+  let rendered = render_summary(&details, &file.utterances, opts).await?;
+  println!("{}", rendered.trim_end_matches('\n'));
+  Ok(())
+}
 
-  if raw {
+/// One video's summary before rendering: the reduced summary, or the `--raw` transcript.
+enum SummaryDoc {
+  Summary(Box<summarize::SummaryOutput>),
+  Raw(serde_json::Value),
+}
+
+impl SummaryDoc {
+  /// Render in the requested format (raw output is always JSON).
+  fn render(&self, opts: &SummarizeOptions<'_>) -> Result<String> {
+    match self {
+      Self::Summary(output) => summarize::render(output, opts.format, opts.with_transcript),
+      Self::Raw(value) => {
+        serde_json::to_string_pretty(&schema::versioned(value)).context("Failed to serialize raw output")
+      }
+    }
+  }
+
+  /// The versioned JSON value, for embedding in per-video records.
+  fn to_value(&self) -> Result<serde_json::Value> {
+    match self {
+      Self::Summary(output) => serde_json::to_value(schema::versioned(output)),
+      Self::Raw(value) => serde_json::to_value(schema::versioned(value)),
+    }
+    .context("Failed to serialize summary")
+  }
+}
+
+/// Render the summary (or raw transcript JSON) for one video in the requested format.
+async fn render_summary(
+  details: &crate::player::VideoDetails,
+  utterances: &[whisper_cli::Utternace],
+  opts: &SummarizeOptions<'_>,
+) -> Result<String> {
+  summary_doc(details, utterances, opts).await?.render(opts)
+}
+
+/// The summary (or raw transcript) for one video.
+#[allow(clippy::cast_precision_loss)]
+async fn summary_doc(
+  details: &crate::player::VideoDetails,
+  utterances: &[whisper_cli::Utternace],
+  opts: &SummarizeOptions<'_>,
+) -> Result<SummaryDoc> {
+  if opts.raw {
     // Raw mode: video info + unprocessed transcript
     let output = serde_json::json!({
       "_hint": "YouTube video raw transcript. No classification or filtering applied. Use without --raw for a summarized version.",
@@ -412,121 +403,706 @@ pub async fn cmd_summarize(video: &str, raw: bool) -> Result<()> {
         "text": u.text,
       })).collect::<Vec<_>>(),
     });
-    let json = serde_json::to_string_pretty(&output).context("Failed to serialize raw output")?;
-    println!("{json}");
+    return Ok(SummaryDoc::Raw(output));
+  }
+
+  Ok(SummaryDoc::Summary(Box::new(summary_output(details, utterances, opts).await?)))
+}
+
+/// Reduce a transcript to a summary, with the LLM block and token budget when requested.
+///
+/// An LLM failure is a warning: the extractive summary is returned without the `llm` block.
+pub(crate) async fn summary_output(
+  details: &crate::player::VideoDetails,
+  utterances: &[whisper_cli::Utternace],
+  opts: &SummarizeOptions<'_>,
+) -> Result<summarize::SummaryOutput> {
+  let mut output = reduce_transcript(details, utterances, opts);
+  if let Some(llm) = opts.llm
+    && let Err(e) = attach_llm_summary(&mut output, llm).await
+  {
+    // Keep the extractive summary rather than losing the video.
+    eprintln!("Warning: {e:#}; keeping the local summary");
+    output._hint.push_str(&format!(" LLM summarization failed ({e:#}); only local extraction is included."));
+  }
+  if let Some(max_tokens) = opts.max_tokens {
+    let report = summarize::apply_token_budget(&mut output, max_tokens, opts.format, opts.with_transcript);
+    if report.over_budget() {
+      eprintln!("Warning: summary is ~{} tokens, over the {max_tokens} budget", report.estimated_tokens);
+    }
+  }
+  Ok(output)
+}
+
+/// What a server request can ask of a summary (MCP tool arguments, HTTP query parameters).
+pub(crate) struct SummaryFlags {
+  pub llm: bool,
+  pub max_tokens: Option<usize>,
+  pub keep_boilerplate: bool,
+}
+
+/// Fetch metadata, transcribe and reduce one video to a summary, for `mcp` and `serve`.
+///
+/// `transcribe` gets the video's duration and runs (or loads) the transcription
+/// the server's way: with its progress reporting and locking.
+pub(crate) async fn summarize_video(
+  video_id: &str,
+  flags: &SummaryFlags,
+  transcribe: impl AsyncFnOnce(Option<u32>) -> Result<Vec<whisper_cli::Utternace>>,
+) -> Result<summarize::SummaryOutput> {
+  let details = youtube::get_video_info(video_id).await.context("Failed to get video info")?;
+  let duration = details.duration.as_deref().and_then(parse_duration_secs);
+  let utterances = transcribe(duration).await?;
+
+  let classifier = summarize::ClassifierConfig::load()?;
+  let llm = flags.llm.then(crate::llm::LlmConfig::load).transpose()?;
+  let opts = SummarizeOptions {
+    raw: false,
+    fresh: false,
+    classifier: &classifier,
+    llm: llm.as_ref(),
+    max_tokens: flags.max_tokens,
+    format: summarize::SummaryFormat::Json,
+    with_transcript: false,
+    boilerplate: !flags.keep_boilerplate,
+  };
+  summary_output(&details, &utterances, &opts).await
+}
+
+/// Read video IDs from stdin (pipe mode) and summarize each of them.
+///
+/// Every video goes through [`summarize_many`], so the output has the same
+/// per-video shape however many IDs arrive.
+///
+/// Enables: `yp channel | fzf -m | yp summarize`
+pub async fn cmd_summarize_stdin(opts: &SummarizeOptions<'_>, jobs: usize) -> Result<()> {
+  let video_ids = read_batch_ids(None)?;
+  match video_ids.as_slice() {
+    [] => Err(error::fail(
+      ErrorKind::InvalidInput,
+      "No input on stdin. Provide a video ID, use --latest, or pipe from `yp channel | fzf`.",
+    )),
+    _ => summarize_many(video_ids, opts, jobs).await,
+  }
+}
+
+/// Summarize the latest N videos from a channel, like piping them in (one
+/// record per video, even for `--latest 1`).
+pub async fn cmd_summarize_latest(channel: &str, count: usize, opts: &SummarizeOptions<'_>, jobs: usize) -> Result<()> {
+  let channel_url = channel_url(channel)?;
+
+  eprintln!("Listing latest {count} video(s) from: {channel_url}");
+  let entries =
+    youtube::list_channel_videos(&channel_url, 1, Some(count)).await.context("Failed to list channel videos")?;
+  remember_channel(channel);
+
+  match entries.as_slice() {
+    [] => Err(error::fail(ErrorKind::NotFound, "No videos found in the channel")),
+    _ => summarize_many(entries.into_iter().map(|e| e.video_id).collect(), opts, jobs).await,
+  }
+}
+
+/// Summarize several videos with up to `jobs` in flight.
+///
+/// JSON (and `--raw`) output is one JSONL record per video as it finishes —
+/// `{video_id, status: "ok", summary}` or `{video_id, status: "error", error:
+/// {code, message}}`; Markdown/text/YAML output separates documents with
+/// rules, a failed video's document being its error record in that format.
+/// A failed video never stops the rest, but the run then fails with the first
+/// failure's kind.
+async fn summarize_many(video_ids: Vec<String>, opts: &SummarizeOptions<'_>, jobs: usize) -> Result<()> {
+  use futures::stream::{self, StreamExt};
+
+  let pool_size = crate::transcript::whisper_pool_size(jobs);
+  let pool = crate::transcript::WhisperPool::new(pool_size);
+  let total = video_ids.len();
+  eprintln!("Summarizing {total} video(s): {} concurrent, {pool_size} whisper instance(s)", jobs.max(1));
+
+  let pool = &pool;
+  let mut results = stream::iter(video_ids)
+    .map(|video_id| async move {
+      let label = format!("[{video_id}] ");
+      let result = async {
+        let details = youtube::get_video_info(&video_id).await.context("Failed to get video info")?;
+        let duration_hint = details.duration.as_deref().and_then(parse_duration_secs);
+        // Hold the whisper slot only while transcribing, not through summarizing (and --llm).
+        let utterances = {
+          let whisper = pool.acquire().await;
+          run_transcription_with(&video_id, duration_hint, opts.fresh, whisper.cache(), &label).await?
+        };
+        summary_doc(&details, &utterances, opts).await
+      }
+      .await;
+      (video_id, result)
+    })
+    .buffer_unordered(jobs.max(1));
+
+  let json = opts.raw || opts.format == summarize::SummaryFormat::Json;
+  let (mut succeeded, mut failures) = (0, Vec::new());
+  let mut printed = 0;
+  while let Some((video_id, result)) = results.next().await {
+    let record = match &result {
+      Ok(doc) if json => {
+        write_jsonl(&schema::versioned(
+          &serde_json::json!({ "video_id": video_id, "status": "ok", "summary": doc.to_value()? }),
+        ))?;
+        None
+      }
+      Ok(doc) => Some(doc.render(opts)?),
+      Err(e) => {
+        eprintln!("[{video_id}] failed: {e:#}");
+        let error = error::error_value(e);
+        if json {
+          write_jsonl(&schema::versioned(
+            &serde_json::json!({ "video_id": video_id, "status": "error", "error": error }),
+          ))?;
+          None
+        } else {
+          Some(summarize::render_error(&video_id, &error, opts.format)?)
+        }
+      }
+    };
+    if let Some(rendered) = record {
+      if printed > 0
+        && let Some(separator) = opts.format.separator()
+      {
+        print!("{separator}");
+      }
+      print!("{rendered}");
+      printed += 1;
+    }
+    match result {
+      Ok(_) => succeeded += 1,
+      Err(e) => failures.push(e),
+    }
+    eprintln!("{}/{total} done", succeeded + failures.len());
+  }
+
+  eprintln!("Done: {succeeded} succeeded, {} failed", failures.len());
+  batch_result(failures.first(), failures.len(), total)
+}
+
+/// Fail a batch run whose videos did not all succeed, with the first failure's kind.
+fn batch_result(first_failure: Option<&anyhow::Error>, failed: usize, total: usize) -> Result<()> {
+  match first_failure {
+    None => Ok(()),
+    Some(first) => {
+      Err(error::fail(error::kind_of(first), format!("{failed} of {total} video(s) failed; first: {first:#}")))
+    }
+  }
+}
+
+/// Run the LLM backend over a reduced summary and attach its result.
+async fn attach_llm_summary(output: &mut summarize::SummaryOutput, cfg: &crate::llm::LlmConfig) -> Result<()> {
+  let summary = crate::llm::summarize(output, cfg).await.context("LLM summarization failed")?;
+  output._hint.push_str(&format!(
+    " The `llm` block was generated by {} from this summary; everything else is local extraction.",
+    summary.model
+  ));
+  output.llm = Some(summary);
+  Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Subcommand: chapters
+// ---------------------------------------------------------------------------
+
+/// Draft YouTube chapters for a video (or a transcript file) from its topic segments.
+///
+/// Prints JSON with the chapters and the paste-ready `description`, or only
+/// the `0:00 Title` lines with `text`.
+pub async fn cmd_chapters(
+  video: Option<&str>,
+  transcript: Option<&std::path::Path>,
+  info: Option<&std::path::Path>,
+  text: bool,
+  opts: &SummarizeOptions<'_>,
+) -> Result<()> {
+  let (details, utterances) = if let Some(path) = transcript {
+    let file = transcript_file::load(path)?;
+    let details = match info {
+      Some(info) => transcript_file::load_info(info)?,
+      None => file.video.unwrap_or_else(|| transcript_file::details_from_path(path)),
+    };
+    (details, file.utterances)
   } else {
-    // Classify + reduce
-    let triples: Vec<(i64, i64, String)> = utterances.iter().map(|u| (u.start, u.stop, u.text.clone())).collect();
-    let classified = summarize::classify(&triples);
-    let output = summarize::reduce(&details, &classified);
-    let json = serde_json::to_string_pretty(&output).context("Failed to serialize summary")?;
-    println!("{json}");
+    let video_id = extract_video_id(
+      video.ok_or_else(|| error::fail(ErrorKind::InvalidInput, "Provide a video ID or --from-transcript"))?,
+    );
+    eprintln!("Fetching video info...");
+    let details = youtube::get_video_info(&video_id).await.context("Failed to get video info")?;
+    eprintln!("Transcribing video: {} — {}", video_id, details.title);
+    let duration_hint = details.duration.as_deref().and_then(parse_duration_secs);
+    let utterances = run_transcription(&video_id, duration_hint, opts.fresh).await?;
+    (details, utterances)
+  };
+
+  let output = reduce_transcript(&details, &utterances, opts);
+  let duration = details.duration.as_deref().and_then(parse_duration_secs).map_or(0.0, f64::from);
+  let chapters = chapters::build(&output, duration).map_err(|e| error::or_kind(e, ErrorKind::InvalidInput))?;
+  let description = chapters::description(&chapters);
+  if text {
+    print!("{description}");
+    return Ok(());
   }
 
-  eprintln!("Done.");
+  let json = serde_json::to_string_pretty(&schema::versioned(&serde_json::json!({
+    "_hint": format!(
+      "Chapters drafted from {} topic segment(s): first at 0:00, at least {} chapters of {}s or more (YouTube's rules). \
+       Paste `description` into the video description; titles are keyphrases, so review them first.",
+      output.summary.topics.len(),
+      chapters::MIN_CHAPTERS,
+      chapters::MIN_CHAPTER_SECS
+    ),
+    "video": details,
+    "chapters": chapters,
+    "description": description,
+  })))
+  .context("Failed to serialize chapters")?;
+  println!("{json}");
   Ok(())
 }
 
-/// Read JSONL from stdin (pipe mode), extract `video_id`, and summarize.
+// ---------------------------------------------------------------------------
+// Subcommand: clip
+// ---------------------------------------------------------------------------
+
+/// What `yp clip` cuts.
+pub enum ClipRange {
+  /// One explicit range, in seconds.
+  Range { from: f64, to: f64 },
+  /// Every key segment of the video's summary.
+  KeySegments,
+}
+
+/// Cut audio clips from a video and print them as JSONL.
 ///
-/// Enables: `yp channel | fzf | yp summarize`
-pub async fn cmd_summarize_stdin(raw: bool) -> Result<()> {
-  use std::io::BufRead;
+/// A range goes to `output` (default `<id>-<from>-<to>.<ext>` in the current
+/// directory); key segments go into the `output` directory as numbered files.
+/// Key segments need the transcript, which is reused from the store when present.
+pub async fn cmd_clip(
+  video: &str,
+  range: ClipRange,
+  output: Option<&std::path::Path>,
+  ext: &str,
+  opts: &SummarizeOptions<'_>,
+) -> Result<()> {
+  let video_id = extract_video_id(video);
+  let url = format!("https://youtube.com/watch?v={video_id}");
 
-  let stdin = std::io::stdin();
-  let line = stdin
-    .lock()
-    .lines()
-    .next()
-    .ok_or_else(|| anyhow!("No input on stdin. Provide a video ID, use --latest, or pipe from `yp channel | fzf`."))?
-    .context("Failed to read from stdin")?;
+  let clips: Vec<clip::Clip> = match range {
+    ClipRange::Range { from, to } => {
+      if to <= from {
+        return Err(error::fail(ErrorKind::InvalidInput, "--to must be after --from"));
+      }
+      let file = output.map_or_else(|| clip::default_name(&video_id, from, to, ext), std::path::Path::to_path_buf);
+      vec![clip::Clip { file, from_secs: from, to_secs: to, text: None }]
+    }
+    ClipRange::KeySegments => {
+      eprintln!("Fetching video info...");
+      let details = youtube::get_video_info(&video_id).await.context("Failed to get video info")?;
+      let duration_hint = details.duration.as_deref().and_then(parse_duration_secs);
+      let utterances = run_transcription(&video_id, duration_hint, opts.fresh).await?;
+      let summary = reduce_transcript(&details, &utterances, opts);
+      let dir = output.unwrap_or_else(|| std::path::Path::new("."));
+      clip::key_segment_ranges(&summary)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (from, to, text))| {
+          let name = clip::default_name(&video_id, from, to, ext);
+          clip::Clip {
+            file: dir.join(format!("{:02}-{}", i + 1, name.display())),
+            from_secs: from,
+            to_secs: to,
+            text: Some(text),
+          }
+        })
+        .collect()
+    }
+  };
+  if clips.is_empty() {
+    return Err(error::fail(ErrorKind::NotFound, "The summary has no key segments to cut"));
+  }
 
-  let trimmed = line.trim();
-  if trimmed.is_empty() {
-    return Err(anyhow!("Empty input on stdin."));
+  eprintln!("Resolving audio stream...");
+  let stream_url = crate::transcript::resolve_stream_url(None, &url).await?;
+  for (i, c) in clips.iter().enumerate() {
+    eprintln!(
+      "[{}/{}] {} – {} → {}",
+      i + 1,
+      clips.len(),
+      summarize::timestamp(c.from_secs),
+      summarize::timestamp(c.to_secs),
+      c.file.display()
+    );
+    clip::export(&stream_url, c.from_secs, c.to_secs, &c.file).await?;
+    write_jsonl(&schema::versioned(c))?;
   }
+  Ok(())
+}
 
-  // Try parsing as JSON to extract video_id field
-  if let Ok(obj) = serde_json::from_str::<serde_json::Value>(trimmed)
-    && let Some(id) = obj.get("video_id").and_then(|v| v.as_str())
-  {
-    return cmd_summarize(id, raw).await;
+// ---------------------------------------------------------------------------
+// Subcommand: play
+// ---------------------------------------------------------------------------
+
+/// Play a video, a channel's latest videos, or IDs piped on stdin, without the TUI.
+///
+/// `target` is a video ID or URL; with `latest` it is a channel handle
+/// (default: the configured channel); `-` or nothing reads one ID, URL or
+/// JSONL object per line from stdin.
+pub async fn cmd_play(target: Option<&str>, latest: Option<usize>, transcript: bool) -> Result<()> {
+  let video_ids = match (target, latest) {
+    (channel, Some(count)) => {
+      let channel =
+        channel.map_or_else(|| crate::constants::constants().pastel_sketchbook_channel.clone(), str::to_string);
+      let channel_url = channel_url(&channel)?;
+      eprintln!("Listing latest {count} video(s) from: {channel_url}");
+      let entries =
+        youtube::list_channel_videos(&channel_url, 1, Some(count)).await.context("Failed to list channel videos")?;
+      remember_channel(&channel);
+      entries.into_iter().map(|e| e.video_id).collect()
+    }
+    (None | Some("-"), None) => {
+      if std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        return Err(error::fail(ErrorKind::InvalidInput, "Pass a video, or pipe video IDs on stdin"));
+      }
+      read_batch_ids(None)?
+    }
+    (Some(target), None) if youtube::detect_channel_url(target).is_some() => {
+      return Err(error::fail(
+        ErrorKind::InvalidInput,
+        format!("{target} is a channel; add --latest to play its latest videos"),
+      ));
+    }
+    (Some(video), None) => vec![extract_video_id(video)],
+  };
+  if video_ids.is_empty() {
+    return Err(error::fail(ErrorKind::InvalidInput, "No video IDs to play"));
   }
+  crate::play::play_queue(&video_ids, transcript).await
+}
 
-  // Fall back: treat the whole line as a video ID or URL
-  cmd_summarize(trimmed, raw).await
+// ---------------------------------------------------------------------------
+// Subcommand: digest
+// ---------------------------------------------------------------------------
+
+/// Video details from a channel listing entry, without a per-video `yt-dlp` call.
+fn details_from_entry(entry: &youtube::SearchEntry) -> crate::player::VideoDetails {
+  crate::player::VideoDetails {
+    url: format!("https://youtube.com/watch?v={}", entry.video_id),
+    title: entry.title.clone(),
+    uploader: entry.uploader.clone(),
+    duration: entry.duration.clone(),
+    upload_date: entry.upload_date.clone(),
+    view_count: entry.view_count.clone(),
+    tags: entry
+      .tags
+      .as_deref()
+      .map(|t| t.split(',').map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect())
+      .unwrap_or_default(),
+  }
 }
 
-/// Summarize the latest N videos from a channel.
-#[allow(clippy::cast_precision_loss)]
-pub async fn cmd_summarize_latest(channel: &str, count: usize, raw: bool) -> Result<()> {
-  let channel_url =
-    youtube::detect_channel_url(channel).ok_or_else(|| anyhow!("Could not detect channel URL from: {channel}"))?;
+/// Summarize a channel's latest `count` videos into one digest.
+///
+/// Videos are transcribed one after another with a shared whisper instance;
+/// transcripts already in the store are reused, so reruns only pay for
+/// classify + reduce. Videos that fail are skipped with a warning.
+pub async fn cmd_digest(channel: &str, count: usize, opts: &SummarizeOptions<'_>) -> Result<()> {
+  let channel_url = channel_url(channel)?;
 
   eprintln!("Listing latest {count} video(s) from: {channel_url}");
   let entries =
     youtube::list_channel_videos(&channel_url, 1, Some(count)).await.context("Failed to list channel videos")?;
-
+  remember_channel(channel);
   if entries.is_empty() {
-    return print_json_error("no_videos", "No videos found in the channel");
+    return Err(error::fail(ErrorKind::NotFound, "No videos found in the channel"));
   }
 
-  if count == 1 {
-    // Single video: output as a JSON object (not array)
-    let entry = &entries[0];
-    cmd_summarize(&entry.video_id, raw).await?;
-  } else {
-    // Multiple videos: output as JSON array
-    print!("[");
-    for (i, entry) in entries.iter().enumerate() {
-      eprintln!("\n--- Video {}/{}: {} ---", i + 1, entries.len(), entry.title);
-      let video_id = &entry.video_id;
-      let url = format!("https://youtube.com/watch?v={video_id}");
-
-      let details = match youtube::get_video_info(video_id).await {
-        Ok(d) => d,
+  let whisper_cache: WhisperCache = Arc::new(StdMutex::new(None));
+  let mut summaries: Vec<(String, summarize::SummaryOutput)> = Vec::new();
+  for (i, entry) in entries.iter().enumerate() {
+    let label = format!("[{}/{}] ", i + 1, entries.len());
+    eprintln!("{label}{} — {}", entry.video_id, entry.title);
+    let details = details_from_entry(entry);
+    let duration_hint = details.duration.as_deref().and_then(parse_duration_secs);
+    let utterances =
+      match run_transcription_with(&entry.video_id, duration_hint, opts.fresh, whisper_cache.clone(), &label).await {
+        Ok(u) => u,
         Err(e) => {
-          eprintln!("Warning: failed to get info for {video_id}: {e}");
+          eprintln!("{label}Warning: transcription failed for {}: {e}", entry.video_id);
           continue;
         }
       };
+    summaries.push((entry.video_id.clone(), reduce_transcript(&details, &utterances, opts)));
+  }
+  if summaries.is_empty() {
+    return Err(error::fail(ErrorKind::TranscriptionFailed, "None of the channel's videos could be transcribed"));
+  }
 
-      let duration_hint = details.duration.as_deref().and_then(parse_duration_secs);
-      let utterances = match run_transcription(&url, duration_hint).await {
-        Ok(u) => u,
+  let digest = digest::build(channel, &summaries);
+  let rendered = digest::render(&digest, opts.format)?;
+  println!("{}", rendered.trim_end_matches('\n'));
+  eprintln!("Done.");
+  Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Subcommand: watch
+// ---------------------------------------------------------------------------
+
+/// Settings for `yp watch`.
+pub struct WatchOptions<'a> {
+  pub interval: std::time::Duration,
+  /// Newest videos listed per channel and poll.
+  pub limit: usize,
+  pub action: Option<watch::WatchAction>,
+  /// Shell command run for every new upload, with its last event on stdin.
+  pub exec: Option<&'a str>,
+  /// Write each transcript or summary here instead of inlining it in the event.
+  pub out_dir: Option<&'a std::path::Path>,
+  /// Poll once and exit instead of looping.
+  pub once: bool,
+  pub classifier: &'a summarize::ClassifierConfig,
+}
+
+/// Poll channels for new uploads and emit one JSONL event per upload.
+///
+/// Each new video gets a `new_video` event; with an action it is then
+/// transcribed or summarized (`transcript` / `summary` event). Failures are
+/// `error` events and never stop the watcher. The hook sees the video's last
+/// event on stdin and its ID, channel, title and URL in `YP_*` variables.
+/// With `once`, the run fails with the first error's kind after one poll.
+pub async fn cmd_watch(channels: &[String], opts: &WatchOptions<'_>) -> Result<()> {
+  let channels: Vec<(String, String)> =
+    channels.iter().map(|c| Ok((c.clone(), channel_url(c)?))).collect::<Result<_>>()?;
+  if let Some(dir) = opts.out_dir {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+  }
+  let mut state = watch::WatchState::load()?;
+  let whisper_cache: WhisperCache = Arc::new(StdMutex::new(None));
+
+  loop {
+    let mut first_failure: Option<anyhow::Error> = None;
+    for (channel, url) in &channels {
+      if let Err(e) = watch_channel(channel, url, opts, &mut state, &whisper_cache).await {
+        if error::kind_of(&e) == ErrorKind::DependencyMissing {
+          return Err(e);
+        }
+        eprintln!("[{channel}] failed: {e:#}");
+        write_jsonl(&schema::versioned(&watch_error(channel, None, &e)))?;
+        first_failure.get_or_insert(e);
+      }
+    }
+    if opts.once {
+      return first_failure.map_or(Ok(()), Err);
+    }
+    eprintln!("Next check in {}", summarize::timestamp(opts.interval.as_secs_f64()));
+    tokio::time::sleep(opts.interval).await;
+  }
+}
+
+/// One poll of one channel: announce its new uploads, then process each.
+///
+/// Listing failures are returned; per-video failures are reported as events.
+async fn watch_channel(
+  channel: &str,
+  url: &str,
+  opts: &WatchOptions<'_>,
+  state: &mut watch::WatchState,
+  whisper_cache: &WhisperCache,
+) -> Result<()> {
+  let entries =
+    youtube::list_channel_videos(url, 1, Some(opts.limit)).await.context("Failed to list channel videos")?;
+  remember_channel(channel);
+  let listed: Vec<String> = entries.iter().map(|e| e.video_id.clone()).collect();
+  let baseline = !state.knows(url);
+  let new_ids = state.observe(url, &listed);
+  if baseline {
+    eprintln!("[{channel}] watching; {} existing video(s) recorded", listed.len());
+  } else {
+    eprintln!("[{channel}] {} new video(s)", new_ids.len());
+    if !listed.is_empty() && new_ids.len() == listed.len() {
+      eprintln!(
+        "[{channel}] warning: all {} listed videos are new; older uploads since the last poll may have been missed \
+         (raise --limit or shorten --interval)",
+        listed.len()
+      );
+    }
+  }
+  state.save()?;
+
+  // Announce first; each video is only saved as seen once its action and hook have finished,
+  // so a crash repeats events rather than losing an upload.
+  let new_entries: Vec<&youtube::SearchEntry> =
+    new_ids.iter().filter_map(|id| entries.iter().find(|e| &e.video_id == id)).collect();
+  let mut announced = Vec::new();
+  for entry in &new_entries {
+    let event = serde_json::json!({
+      "event": "new_video",
+      "channel": channel,
+      "detected_at": chrono::Local::now().to_rfc3339(),
+      "video": VideoEntry::new(entry, None),
+    });
+    write_jsonl(&schema::versioned(&event))?;
+    announced.push(event);
+  }
+
+  for (entry, announcement) in new_entries.into_iter().zip(announced) {
+    let event = match opts.action {
+      None => announcement,
+      Some(action) => match process_new_video(channel, entry, action, opts, whisper_cache).await {
+        Ok(event) => event,
         Err(e) => {
-          eprintln!("Warning: transcription failed for {video_id}: {e}");
-          continue;
+          eprintln!("[{}] failed: {e:#}", entry.video_id);
+          watch_error(channel, Some(&entry.video_id), &e)
         }
-      };
+      },
+    };
+    if opts.action.is_some() {
+      write_jsonl(&schema::versioned(&event))?;
+    }
+    if let Some(exec) = opts.exec
+      && let Err(e) = run_watch_hook(exec, entry, &event).await
+    {
+      eprintln!("[{}] hook failed: {e:#}", entry.video_id);
+      write_jsonl(&schema::versioned(&watch_error(channel, Some(&entry.video_id), &e)))?;
+    }
+    state.mark_seen(url, &entry.video_id);
+    state.save()?;
+  }
+  Ok(())
+}
 
-      let json = if raw {
-        serde_json::to_string_pretty(&serde_json::json!({
-          "_hint": "YouTube video raw transcript.",
-          "video": details,
-          "utterances": utterances.iter().map(|u| serde_json::json!({
-            "start": u.start as f64 / 100.0,
-            "end": u.stop as f64 / 100.0,
-            "text": u.text,
-          })).collect::<Vec<_>>(),
-        }))
-        .context("Failed to serialize raw output")?
-      } else {
-        let triples: Vec<(i64, i64, String)> = utterances.iter().map(|u| (u.start, u.stop, u.text.clone())).collect();
-        let classified = summarize::classify(&triples);
-        let output = summarize::reduce(&details, &classified);
-        serde_json::to_string_pretty(&output).context("Failed to serialize summary")?
+/// Transcribe or summarize a new upload; returns its `transcript` or `summary` event.
+async fn process_new_video(
+  channel: &str,
+  entry: &youtube::SearchEntry,
+  action: watch::WatchAction,
+  opts: &WatchOptions<'_>,
+  whisper_cache: &WhisperCache,
+) -> Result<serde_json::Value> {
+  let details = details_from_entry(entry);
+  let video_id = &entry.video_id;
+  let label = format!("[{video_id}] ");
+  let duration_hint = details.duration.as_deref().and_then(parse_duration_secs);
+  let utterances = run_transcription_with(video_id, duration_hint, false, whisper_cache.clone(), &label).await?;
+
+  let mut event = serde_json::json!({ "channel": channel, "video_id": video_id });
+  match action {
+    watch::WatchAction::Transcribe => {
+      event["event"] = "transcript".into();
+      event["segments"] = utterances.len().into();
+      if let Some(dir) = opts.out_dir {
+        let path = dir.join(format!("{video_id}.jsonl"));
+        let mut lines = transcript_lines(&utterances, false)?.join("\n");
+        lines.push('\n');
+        std::fs::write(&path, lines).with_context(|| format!("Failed to write {}", path.display()))?;
+        event["file"] = path.display().to_string().into();
+      }
+    }
+    watch::WatchAction::Summarize => {
+      let summarize_opts = SummarizeOptions {
+        raw: false,
+        fresh: false,
+        classifier: opts.classifier,
+        llm: None,
+        max_tokens: None,
+        format: summarize::SummaryFormat::Json,
+        with_transcript: false,
+        boilerplate: true,
       };
-
-      if i > 0 {
-        print!(",");
+      let summary = summary_output(&details, &utterances, &summarize_opts).await?;
+      event["event"] = "summary".into();
+      if let Some(dir) = opts.out_dir {
+        let path = dir.join(format!("{video_id}.json"));
+        let json = serde_json::to_string_pretty(&schema::versioned(&summary)).context("Failed to serialize summary")?;
+        std::fs::write(&path, json + "\n").with_context(|| format!("Failed to write {}", path.display()))?;
+        event["file"] = path.display().to_string().into();
+      } else {
+        event["summary"] = serde_json::to_value(schema::versioned(&summary)).context("Failed to serialize summary")?;
       }
-      print!("{json}");
     }
-    println!("]");
   }
+  Ok(event)
+}
 
-  eprintln!("Done.");
+/// `error` event for a channel or one of its videos.
+fn watch_error(channel: &str, video_id: Option<&str>, err: &anyhow::Error) -> serde_json::Value {
+  let mut event = serde_json::json!({ "event": "error", "channel": channel, "error": error::error_value(err) });
+  if let Some(video_id) = video_id {
+    event["video_id"] = video_id.into();
+  }
+  event
+}
+
+/// Run the user's hook through `sh -c` with the event on stdin.
+async fn run_watch_hook(exec: &str, entry: &youtube::SearchEntry, event: &serde_json::Value) -> Result<()> {
+  use tokio::io::AsyncWriteExt;
+
+  let mut child = tokio::process::Command::new("sh")
+    .args(["-c", exec])
+    .env("YP_EVENT", event["event"].as_str().unwrap_or_default())
+    .env("YP_VIDEO_ID", &entry.video_id)
+    .env("YP_CHANNEL", event["channel"].as_str().unwrap_or_default())
+    .env("YP_TITLE", &entry.title)
+    .env("YP_URL", format!("https://youtube.com/watch?v={}", entry.video_id))
+    .env("YP_FILE", event["file"].as_str().unwrap_or_default())
+    .stdin(std::process::Stdio::piped())
+    .stdout(std::process::Stdio::null())
+    .spawn()
+    .context("Failed to run hook")?;
+  if let Some(mut stdin) = child.stdin.take() {
+    let line = serde_json::to_string(&schema::versioned(event)).context("Failed to serialize event")?;
+    // A hook that ignores its input may exit before reading it.
+    let _ = stdin.write_all(format!("{line}\n").as_bytes()).await;
+  }
+  let status = child.wait().await.context("Failed to wait for hook")?;
+  if !status.success() {
+    return Err(anyhow::anyhow!("Hook `{exec}` exited with {status}"));
+  }
+  Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Subcommand: grep
+// ---------------------------------------------------------------------------
+
+/// Search every stored transcript for `query` and output hits as JSONL.
+///
+/// With a channel, only that channel's videos are searched (one flat listing,
+/// no per-video requests). Titles come from the completion cache.
+pub async fn cmd_grep(query: &str, channel: Option<&str>, limit: usize, reindex: bool) -> Result<()> {
+  if textrank::words(query).is_empty() {
+    return Err(error::fail(ErrorKind::InvalidInput, "The query has no words to search for"));
+  }
+
+  let only = match channel {
+    Some(channel) => {
+      let channel_url = channel_url(channel)?;
+      eprintln!("Listing videos from: {channel_url}");
+      let entries =
+        youtube::list_channel_videos(&channel_url, 1, None).await.context("Failed to list channel videos")?;
+      remember_channel(channel);
+      Some(entries.into_iter().map(|e| e.video_id).collect::<std::collections::HashSet<String>>())
+    }
+    None => None,
+  };
+
+  let index = if reindex { index::rebuild()? } else { index::load_or_rebuild()? };
+  if index.is_empty() {
+    return Err(error::fail(
+      ErrorKind::NotFound,
+      "No stored transcripts to search. Transcribe some with `yp transcript`.",
+    ));
+  }
+
+  let titles: std::collections::HashMap<String, String> = cache::read_videos().into_iter().collect();
+  let hits = index::search(&index, query, only.as_ref(), &titles, limit);
+  eprintln!("{} hit(s) in {} indexed video(s)", hits.len(), index.len());
+  for hit in &hits {
+    write_jsonl(&schema::versioned(hit))?;
+  }
   Ok(())
 }
 
@@ -535,7 +1111,7 @@ pub async fn cmd_summarize_latest(channel: &str, count: usize, raw: bool) -> Res
 // ---------------------------------------------------------------------------
 
 /// Parse a duration string like "16:30" or "1:23:45" into total seconds.
-fn parse_duration_secs(s: &str) -> Option<u32> {
+pub(crate) fn parse_duration_secs(s: &str) -> Option<u32> {
   let parts: Vec<&str> = s.split(':').collect();
   match parts.len() {
     2 => {
@@ -553,91 +1129,472 @@ fn parse_duration_secs(s: &str) -> Option<u32> {
   }
 }
 
+/// Merge whisper fragments into sentences, then classify them.
+fn classify_utterances(
+  utterances: &[whisper_cli::Utternace],
+  cfg: &summarize::ClassifierConfig,
+) -> Vec<summarize::ClassifiedUtterance> {
+  let triples: Vec<(i64, i64, String)> = utterances.iter().map(|u| (u.start, u.stop, u.text.clone())).collect();
+  summarize::classify(&segment::sentences(&triples), cfg)
+}
+
+/// Run the headless transcription pipeline with a private whisper instance.
+async fn run_transcription(
+  video_id: &str,
+  duration_hint: Option<u32>,
+  fresh: bool,
+) -> Result<Vec<whisper_cli::Utternace>> {
+  run_transcription_with(video_id, duration_hint, fresh, Arc::new(StdMutex::new(None)), "").await
+}
+
 /// Run the headless transcription pipeline and collect all utterances.
 ///
 /// Uses `ipc_socket: None` to skip mpv IPC and go straight to `yt-dlp -g`.
-#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
-async fn run_transcription(url: &str, duration_hint: Option<u32>) -> Result<Vec<whisper_cli::Utternace>> {
-  let (tx, mut rx) = mpsc::unbounded_channel::<TranscriptEvent>();
-  let whisper_cache: Arc<StdMutex<Option<whisper_cli::Whisper>>> = Arc::new(StdMutex::new(None));
+/// Resumes from the video's checkpoint in the transcript store unless `fresh`
+/// is set; a completed checkpoint is returned as-is without re-transcribing.
+/// Without a `duration_hint` the duration is fetched before transcribing, so a
+/// dropped stream is not mistaken for the end of the video.
+/// `label` prefixes progress lines so interleaved batch output stays readable.
+pub(crate) async fn run_transcription_with(
+  video_id: &str,
+  duration_hint: Option<u32>,
+  fresh: bool,
+  whisper_cache: WhisperCache,
+  label: &str,
+) -> Result<Vec<whisper_cli::Utternace>> {
+  run_transcription_reporting(video_id, duration_hint, fresh, whisper_cache, label, &|_| {}).await
+}
+
+/// The video's duration in seconds, for callers that don't have its details at hand.
+pub(crate) async fn video_duration(video_id: &str, label: &str) -> Option<u32> {
+  match youtube::get_video_info(video_id).await {
+    Ok(details) => details.duration.as_deref().and_then(parse_duration_secs),
+    Err(e) => {
+      eprintln!("{label}Could not fetch the video duration ({e:#}); a dropped stream will stop the job early.");
+      None
+    }
+  }
+}
+
+/// Aborts the wrapped task when dropped, so cancelling a transcription stops its pipeline.
+pub(crate) struct AbortOnDrop<T>(pub(crate) tokio::task::JoinHandle<T>);
 
-  let handle = crate::transcript::spawn_transcription_pipeline(tx, url.to_string(), whisper_cache, None, duration_hint);
+impl<T> Drop for AbortOnDrop<T> {
+  fn drop(&mut self) {
+    self.0.abort();
+  }
+}
+
+/// `run_transcription_with`, handing each batch of utterances to `on_utterances` as it
+/// becomes available: stored ones first, then every transcribed chunk, in order.
+///
+/// Dropping the returned future (e.g. a cancelled MCP request) aborts the
+/// pipeline; whatever was checkpointed so far is kept for the next run.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+pub(crate) async fn run_transcription_reporting(
+  video_id: &str,
+  duration_hint: Option<u32>,
+  fresh: bool,
+  whisper_cache: WhisperCache,
+  label: &str,
+  on_utterances: &(dyn Fn(&[whisper_cli::Utternace]) + Sync),
+) -> Result<Vec<whisper_cli::Utternace>> {
+  let url = format!("https://youtube.com/watch?v={video_id}");
 
   let mut all_utterances: Vec<whisper_cli::Utternace> = Vec::new();
+  let mut start_offset: u32 = 0;
+  if fresh {
+    store::discard(video_id)?;
+  } else if let Some(record) = store::load(video_id) {
+    all_utterances = record.to_utterances();
+    if record.complete {
+      eprintln!("{label}Using stored transcript ({} segments). Pass --fresh to re-transcribe.", all_utterances.len());
+      on_utterances(&all_utterances);
+      return Ok(all_utterances);
+    }
+    start_offset = record.offset_secs;
+    eprintln!("{label}Resuming from checkpoint at {}s ({} segments stored)", start_offset, all_utterances.len());
+    on_utterances(&all_utterances);
+  }
+
+  // Without the duration, a failed chunk can't be told apart from the end of the video.
+  let duration_hint = match duration_hint {
+    Some(secs) => Some(secs),
+    None => video_duration(video_id, label).await,
+  };
+
+  let (tx, mut rx) = mpsc::unbounded_channel::<TranscriptEvent>();
+  let mut handle = AbortOnDrop(crate::transcript::spawn_transcription_pipeline(
+    tx,
+    url,
+    whisper_cache,
+    None,
+    duration_hint,
+    start_offset,
+  ));
+
   let mut chunk_count: u32 = 0;
 
   while let Some(event) = rx.recv().await {
     match event {
       TranscriptEvent::AudioExtracted => {
-        eprintln!("Audio URL resolved, transcribing...");
+        eprintln!("{label}Audio URL resolved, transcribing...");
       }
       TranscriptEvent::DownloadProgress(downloaded, total) => {
         if total > 0 {
           let pct = (downloaded as f64 / total as f64 * 100.0) as u32;
           let mb_down = downloaded / (1024 * 1024);
           let mb_total = total / (1024 * 1024);
-          eprintln!("Downloading whisper model... {mb_down}MB / {mb_total}MB [{pct}%]");
+          eprintln!("{label}Downloading whisper model... {mb_down}MB / {mb_total}MB [{pct}%]");
         }
       }
       TranscriptEvent::ChunkTranscribed(utterances) => {
         chunk_count += 1;
         let count = utterances.len();
+        on_utterances(&utterances);
         all_utterances.extend(utterances);
-        eprintln!("Chunk {} transcribed ({} segments, {} total)", chunk_count, count, all_utterances.len());
+        eprintln!("{label}Chunk {} transcribed ({} segments, {} total)", chunk_count, count, all_utterances.len());
       }
       TranscriptEvent::Transcribed => {
-        eprintln!("Transcription complete: {} total segments", all_utterances.len());
+        eprintln!("{label}Transcription complete: {} total segments", all_utterances.len());
         break;
       }
-      TranscriptEvent::Failed(msg) => {
+      TranscriptEvent::Failed(kind, msg) => {
         // Wait for the spawned task to finish before returning.
-        let _ = handle.await;
-        return Err(anyhow!("Transcription failed: {msg}"));
+        let _ = (&mut handle.0).await;
+        return Err(error::fail(kind, format!("Transcription failed: {msg} (progress checkpointed, rerun to resume)")));
       }
     }
   }
 
-  let _ = handle.await;
+  let _ = (&mut handle.0).await;
   Ok(all_utterances)
 }
 
 // ---------------------------------------------------------------------------
-// Subcommand: _complete-ids (hidden, for shell completions)
+// Subcommand: transcribe-batch
 // ---------------------------------------------------------------------------
 
-/// Output cached video IDs for shell completion.
+/// Outcome of one video in a batch run: segment count or the error.
+struct BatchOutcome {
+  video_id: String,
+  result: Result<usize>,
+}
+
+/// Extract a video ID from one input line: a JSONL object with `video_id`,
+/// a URL, or a bare ID. Returns `None` for blank lines and `#` comments.
+fn parse_batch_line(line: &str) -> Option<String> {
+  let trimmed = line.trim();
+  if trimmed.is_empty() || trimmed.starts_with('#') {
+    return None;
+  }
+  if let Ok(obj) = serde_json::from_str::<serde_json::Value>(trimmed)
+    && let Some(id) = obj.get("video_id").and_then(|v| v.as_str())
+  {
+    return Some(extract_video_id(id));
+  }
+  Some(extract_video_id(trimmed))
+}
+
+/// Read video IDs from a file (or stdin), dropping duplicates but keeping order.
+fn read_batch_ids(input: Option<&std::path::Path>) -> Result<Vec<String>> {
+  use std::io::BufRead;
+
+  let lines: Vec<String> = if let Some(path) = input {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    content.lines().map(str::to_string).collect()
+  } else {
+    std::io::stdin().lock().lines().collect::<std::io::Result<_>>().context("Failed to read from stdin")?
+  };
+
+  let mut seen = std::collections::HashSet::new();
+  Ok(lines.iter().filter_map(|l| parse_batch_line(l)).filter(|id| seen.insert(id.clone())).collect())
+}
+
+/// Transcribe many videos with bounded parallelism.
 ///
-/// If the cache is empty and `live` is true, fetches videos from the default
-/// channel to seed the cache before outputting.
+/// Input is one video ID, URL, or JSONL object per line (e.g. the output of
+/// `yp channel --all`). Jobs share a pool of whisper instances sized to the
+/// memory available, so `jobs` beyond what fits simply queue. Each video
+/// checkpoints like `yp transcript`, so rerunning a batch resumes where it
+/// stopped and skips videos already transcribed.
 ///
-/// Output format: one `video_id\ttitle` line per entry (zsh `_describe` format).
-pub async fn cmd_complete_ids(live: bool) -> Result<()> {
-  use std::io::Write;
+/// With `out_dir`, each transcript goes to `<out_dir>/<video_id>.jsonl` and
+/// the final report to `<out_dir>/report.json`; otherwise one JSONL record per
+/// video is written to stdout. The report summary always goes to stderr.
+pub async fn cmd_transcribe_batch(
+  input: Option<&std::path::Path>,
+  jobs: usize,
+  out_dir: Option<&std::path::Path>,
+  raw: bool,
+  fresh: bool,
+) -> Result<()> {
+  let video_ids = read_batch_ids(input)?;
+  if video_ids.is_empty() {
+    return Err(error::fail(ErrorKind::InvalidInput, "No video IDs on input"));
+  }
+  transcribe_ids(video_ids, jobs, out_dir, raw, fresh).await
+}
 
-  let mut entries = cache::read_videos();
+/// Transcribe `video_ids` with up to `jobs` in flight, writing one record per
+/// video (or per-video files and a report with `out_dir`).
+async fn transcribe_ids(
+  video_ids: Vec<String>,
+  jobs: usize,
+  out_dir: Option<&std::path::Path>,
+  raw: bool,
+  fresh: bool,
+) -> Result<()> {
+  use futures::stream::{self, StreamExt};
+
+  if let Some(dir) = out_dir {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+  }
 
-  // Live fallback: seed from default channel if cache is empty.
-  if entries.is_empty() && live {
-    let constants = crate::constants::constants();
-    let channel = &constants.pastel_sketchbook_channel;
-    if let Some(url) = youtube::detect_channel_url(channel)
-      && let Ok(videos) = youtube::list_channel_videos(&url, 1, Some(30)).await
-    {
-      let pairs: Vec<(&str, &str)> = videos.iter().map(|e| (e.video_id.as_str(), e.title.as_str())).collect();
-      let _ = cache::append_videos(&pairs);
-      entries = videos.into_iter().map(|e| (e.video_id, e.title)).collect();
+  let pool_size = crate::transcript::whisper_pool_size(jobs);
+  let pool = crate::transcript::WhisperPool::new(pool_size);
+  let total = video_ids.len();
+  eprintln!("Transcribing {total} video(s): {} concurrent, {pool_size} whisper instance(s)", jobs.max(1));
+
+  let pool = &pool;
+  let mut results = stream::iter(video_ids)
+    .map(|video_id| async move {
+      let label = format!("[{video_id}] ");
+      // Fetch the duration before taking a whisper slot, so the slot is held only while transcribing.
+      let stored = !fresh && store::load(&video_id).is_some_and(|r| r.complete);
+      let duration_hint = if stored { None } else { video_duration(&video_id, &label).await };
+      let utterances = {
+        let whisper = pool.acquire().await;
+        run_transcription_with(&video_id, duration_hint, fresh, whisper.cache(), &label).await
+      };
+      let result = utterances.and_then(|u| Ok((u.len(), transcript_values(&u, raw)?)));
+      (video_id, result)
+    })
+    .buffer_unordered(jobs.max(1));
+
+  let mut outcomes: Vec<BatchOutcome> = Vec::with_capacity(total);
+  while let Some((video_id, result)) = results.next().await {
+    let result = result.and_then(|(count, values)| write_batch_result(&video_id, &values, out_dir).map(|()| count));
+    match &result {
+      Ok(count) => eprintln!("[{video_id}] ok ({count} segments) — {}/{total} done", outcomes.len() + 1),
+      Err(e) => {
+        eprintln!("[{video_id}] failed: {e:#} — {}/{total} done", outcomes.len() + 1);
+        if out_dir.is_none() {
+          write_jsonl(&schema::versioned(
+            &serde_json::json!({ "video_id": video_id, "status": "error", "error": error::error_value(e) }),
+          ))?;
+        }
+      }
+    }
+    outcomes.push(BatchOutcome { video_id, result });
+  }
+
+  let report = batch_report(&outcomes);
+  if let Some(dir) = out_dir {
+    let path = dir.join("report.json");
+    let json = serde_json::to_string_pretty(&report).context("Failed to serialize batch report")?;
+    std::fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+  }
+  let failed = outcomes.iter().filter(|o| o.result.is_err()).count();
+  eprintln!("Batch done: {} succeeded, {failed} failed", outcomes.len() - failed);
+  for outcome in &outcomes {
+    if let Err(e) = &outcome.result {
+      eprintln!("  {} — {e:#}", outcome.video_id);
+    }
+  }
+  batch_result(outcomes.iter().find_map(|o| o.result.as_ref().err()), failed, total)
+}
+
+/// Write one successful transcript: to `<out_dir>/<id>.jsonl`, or as a JSONL record on stdout.
+fn write_batch_result(
+  video_id: &str,
+  utterances: &[serde_json::Value],
+  out_dir: Option<&std::path::Path>,
+) -> Result<()> {
+  if let Some(dir) = out_dir {
+    let path = dir.join(format!("{video_id}.jsonl"));
+    let mut content = versioned_lines(utterances)?.join("\n");
+    content.push('\n');
+    return std::fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()));
+  }
+  write_jsonl(&schema::versioned(
+    &serde_json::json!({ "video_id": video_id, "status": "ok", "utterances": utterances }),
+  ))
+}
+
+/// Build the final batch report: totals plus per-video status.
+fn batch_report(outcomes: &[BatchOutcome]) -> serde_json::Value {
+  let videos: Vec<serde_json::Value> = outcomes
+    .iter()
+    .map(|o| match &o.result {
+      Ok(count) => serde_json::json!({ "video_id": o.video_id, "status": "ok", "segments": count }),
+      Err(e) => serde_json::json!({ "video_id": o.video_id, "status": "error", "error": error::error_value(e) }),
+    })
+    .collect();
+  let failed = outcomes.iter().filter(|o| o.result.is_err()).count();
+  serde_json::json!({
+    "schema_version": schema::SCHEMA_VERSION,
+    "total": outcomes.len(),
+    "succeeded": outcomes.len() - failed,
+    "failed": failed,
+    "finished_at": chrono::Local::now().to_rfc3339(),
+    "videos": videos,
+  })
+}
+
+// ---------------------------------------------------------------------------
+// Subcommand: boilerplate
+// ---------------------------------------------------------------------------
+
+/// Learn a channel's recurring intro/outro from stored transcripts and print it as JSON.
+///
+/// Uses the transcripts already in the store for the channel's latest `limit`
+/// videos; nothing is transcribed. The model is saved so later `yp summarize`
+/// runs on the channel's videos suppress the matching utterances.
+pub async fn cmd_boilerplate(channel: &str, limit: usize, classifier: &summarize::ClassifierConfig) -> Result<()> {
+  let channel_url = channel_url(channel)?;
+
+  eprintln!("Listing latest {limit} video(s) from: {channel_url}");
+  let entries =
+    youtube::list_channel_videos(&channel_url, 1, Some(limit)).await.context("Failed to list channel videos")?;
+  remember_channel(channel);
+  if entries.is_empty() {
+    return Err(error::fail(ErrorKind::NotFound, "No videos found in the channel"));
+  }
+
+  let videos: Vec<Vec<String>> = entries
+    .iter()
+    .filter_map(|e| store::load(&e.video_id))
+    .filter(|r| !r.utterances.is_empty())
+    .map(|r| {
+      let triples: Vec<(i64, i64, String)> = r.utterances.into_iter().map(|u| (u.start, u.stop, u.text)).collect();
+      segment::sentences(&triples).into_iter().map(|(_, _, text)| text).collect()
+    })
+    .collect();
+  if videos.len() < classifier.boilerplate_min_videos {
+    return Err(error::fail(
+      ErrorKind::NotFound,
+      format!(
+        "Found stored transcripts for {} of {} videos; need at least {}. \
+         Transcribe more with `yp channel {channel} --all | yp transcribe-batch`.",
+        videos.len(),
+        entries.len(),
+        classifier.boilerplate_min_videos
+      ),
+    ));
+  }
+
+  let uploader = match entries.iter().find_map(|e| e.uploader.clone()) {
+    Some(uploader) => Some(uploader),
+    None => youtube::get_video_info(&entries[0].video_id).await.ok().and_then(|d| d.uploader),
+  };
+  if uploader.is_none() {
+    eprintln!("Warning: could not determine the uploader name; the model will not be applied automatically");
+  }
+
+  eprintln!("Learning boilerplate from {} transcripts...", videos.len());
+  let model = boilerplate::BoilerplateModel::build(channel, uploader, &videos, classifier);
+  let path = boilerplate::save(&model)?;
+  eprintln!("Saved {}", path.display());
+
+  let json = serde_json::to_string_pretty(&schema::versioned(&serde_json::json!({
+    "channel": model.channel,
+    "uploader": model.uploader,
+    "videos": model.videos,
+    "shingles": model.shingles.len(),
+    "phrases": model.phrases,
+    "built_at": model.built_at,
+  })))
+  .context("Failed to serialize boilerplate model")?;
+  println!("{json}");
+  Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Subcommand: jobs
+// ---------------------------------------------------------------------------
+
+/// List interrupted transcription jobs as JSONL, or discard their checkpoints.
+///
+/// Resuming needs no extra command: `yp transcript <id>` (or replaying the
+/// video in the TUI) picks up from the stored offset automatically.
+pub fn cmd_jobs(discard: Option<&str>, discard_all: bool) -> Result<()> {
+  if discard_all {
+    let jobs = store::incomplete();
+    for job in &jobs {
+      store::discard(&job.video_id)?;
     }
+    eprintln!("Discarded {} incomplete job(s)", jobs.len());
+    return Ok(());
+  }
+
+  if let Some(video) = discard {
+    let video_id = extract_video_id(video);
+    if store::discard(&video_id)? {
+      eprintln!("Discarded checkpoint for {video_id}");
+      return Ok(());
+    }
+    return Err(error::fail(ErrorKind::NotFound, format!("No checkpoint stored for {video_id}")));
+  }
+
+  let titles: std::collections::HashMap<String, String> = cache::read_videos().into_iter().collect();
+  let jobs = store::incomplete();
+  if jobs.is_empty() {
+    eprintln!("No incomplete transcription jobs.");
   }
+  for job in &jobs {
+    write_jsonl(&schema::versioned(&serde_json::json!({
+      "video_id": job.video_id,
+      "title": titles.get(&job.video_id),
+      "url": job.url,
+      "offset_secs": job.offset_secs,
+      "utterances": job.utterances.len(),
+      "updated_at": job.updated_at,
+    })))?;
+  }
+  Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Subcommand: schema
+// ---------------------------------------------------------------------------
+
+/// Print the JSON Schema of a command's output, or the commands that have one
+/// and the exit status of each error code.
+pub fn cmd_schema(command: Option<schema::SchemaCommand>) -> Result<()> {
+  let value = match command {
+    Some(command) => schema::command_schema(command),
+    None => {
+      use clap::ValueEnum;
+      let commands: Vec<String> = schema::SchemaCommand::value_variants().iter().map(|c| c.name()).collect();
+      let exit_codes: serde_json::Map<String, serde_json::Value> =
+        ErrorKind::ALL.iter().map(|k| (k.code().to_string(), k.exit_code().into())).collect();
+      serde_json::json!({ "schema_version": schema::SCHEMA_VERSION, "commands": commands, "exit_codes": exit_codes })
+    }
+  };
+  let json = serde_json::to_string_pretty(&value).context("Failed to serialize schema")?;
+  println!("{json}");
+  Ok(())
+}
 
+// ---------------------------------------------------------------------------
+// Subcommand: _complete (hidden, for shell completions)
+// ---------------------------------------------------------------------------
+
+/// Print completion values for the argument after `words`, one per line in
+/// `shell`'s format: cached video IDs for video arguments, recent and
+/// configured channels for channel arguments, nothing anywhere else.
+pub fn cmd_complete(shell: clap_complete::Shell, cmd: clap::Command, words: &[String]) -> Result<()> {
+  use std::io::Write;
+
+  let Some(kind) = completions::dynamic_at(cmd, words) else { return Ok(()) };
   let stdout = std::io::stdout();
   let mut lock = stdout.lock();
-  for (id, title) in entries.iter().rev() {
-    // Escape colons in title (zsh _describe uses colon as delimiter).
-    let escaped = title.replace('\\', "\\\\").replace(':', "\\:");
-    writeln!(lock, "{id}:{escaped}").context("Failed to write completion entry")?;
+  for (value, description) in completions::candidates(kind) {
+    writeln!(lock, "{}", completions::candidate_line(shell, &value, &description))
+      .context("Failed to write completion entry")?;
   }
   lock.flush().context("Failed to flush completion output")?;
-
   Ok(())
 }
 
@@ -698,4 +1655,86 @@ mod tests {
   fn parse_duration_invalid() {
     assert_eq!(parse_duration_secs("abc"), None);
   }
+
+  #[test]
+  fn parse_batch_line_formats() {
+    assert_eq!(parse_batch_line("dQw4w9WgXcQ").as_deref(), Some("dQw4w9WgXcQ"));
+    assert_eq!(parse_batch_line("https://youtu.be/dQw4w9WgXcQ").as_deref(), Some("dQw4w9WgXcQ"));
+    assert_eq!(parse_batch_line(r#"{"video_id":"dQw4w9WgXcQ","title":"x"}"#).as_deref(), Some("dQw4w9WgXcQ"));
+  }
+
+  #[test]
+  fn parse_batch_line_skips_blank_and_comments() {
+    assert_eq!(parse_batch_line("   "), None);
+    assert_eq!(parse_batch_line("# backlog"), None);
+  }
+
+  #[test]
+  fn batch_report_counts() {
+    let outcomes = vec![
+      BatchOutcome { video_id: "a".to_string(), result: Ok(12) },
+      BatchOutcome { video_id: "b".to_string(), result: Err(anyhow::anyhow!("boom")) },
+    ];
+    let report = batch_report(&outcomes);
+    assert_eq!(report["schema_version"], schema::SCHEMA_VERSION);
+    assert_eq!(report["total"], 2);
+    assert_eq!(report["succeeded"], 1);
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["videos"][1]["error"], serde_json::json!({ "code": "internal", "message": "boom" }));
+  }
+
+  // --- Golden files ---
+
+  /// Summarize `testdata/misty-mountains.srt` with default settings in `format`
+  /// and compare against `testdata/misty-mountains.summary.<ext>`. Run with
+  /// `YP_UPDATE_GOLDEN=1` to rewrite the expected output after an intended
+  /// change to the summarizer.
+  async fn assert_summary_golden(format: summarize::SummaryFormat, ext: &str) {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata");
+    let file = transcript_file::load(&dir.join("misty-mountains.srt")).unwrap();
+    let details = crate::player::VideoDetails {
+      url: "https://www.youtube.com/watch?v=golden00001".to_string(),
+      title: "Misty Mountains in Watercolor".to_string(),
+      uploader: Some("Pastel Sketchbook".to_string()),
+      duration: Some("2:08".to_string()),
+      upload_date: Some("20240301".to_string()),
+      view_count: None,
+      tags: vec!["watercolor".to_string()],
+    };
+    let classifier = summarize::ClassifierConfig::default();
+    let opts = SummarizeOptions {
+      raw: false,
+      fresh: false,
+      classifier: &classifier,
+      llm: None,
+      max_tokens: None,
+      format,
+      with_transcript: true,
+      boilerplate: false,
+    };
+    let rendered = render_summary(&details, &file.utterances, &opts).await.unwrap();
+    let actual = format!("{}\n", rendered.trim_end_matches('\n'));
+
+    let golden = dir.join(format!("misty-mountains.summary.{ext}"));
+    if std::env::var_os("YP_UPDATE_GOLDEN").is_some() {
+      std::fs::write(&golden, &actual).unwrap();
+    }
+    let expected = std::fs::read_to_string(&golden).unwrap();
+    assert_eq!(
+      actual,
+      expected,
+      "summary drifted from {}; rerun with YP_UPDATE_GOLDEN=1 if intended",
+      golden.display()
+    );
+  }
+
+  #[tokio::test]
+  async fn summary_golden_json() {
+    assert_summary_golden(summarize::SummaryFormat::Json, "json").await;
+  }
+
+  #[tokio::test]
+  async fn summary_golden_markdown() {
+    assert_summary_golden(summarize::SummaryFormat::Md, "md").await;
+  }
 }
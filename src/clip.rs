@@ -0,0 +1,172 @@
+//! Audio clip export: cut a time range of a video's audio with ffmpeg.
+//!
+//! Used by `yp clip` (explicit ranges or every key segment of a summary) and
+//! the TUI's mark in / mark out / export keys. The audio comes straight from
+//! the resolved stream URL; ffmpeg picks the codec from the output extension.
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::error::{self, ErrorKind};
+use crate::summarize::{self, SummaryOutput, UtteranceClass};
+
+/// Seconds of lead-in and tail added around a key segment.
+const KEY_SEGMENT_PAD_SECS: f64 = 1.0;
+/// Longest clip cut for a key segment (whisper sentences can run long).
+const MAX_KEY_SEGMENT_SECS: f64 = 90.0;
+
+/// One exported clip.
+#[derive(Debug, Clone, Serialize)]
+pub struct Clip {
+  pub file: PathBuf,
+  pub from_secs: f64,
+  pub to_secs: f64,
+  /// What is said in the clip (key segments only).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub text: Option<String>,
+}
+
+/// Parse `83`, `83.5`, `1:23`, `1:23.5` or `1:02:03` into seconds.
+pub fn parse_time(s: &str) -> Option<f64> {
+  let mut secs = 0.0;
+  let parts: Vec<&str> = s.trim().split(':').collect();
+  if parts.len() > 3 {
+    return None;
+  }
+  for (i, part) in parts.iter().enumerate() {
+    let value: f64 = part.parse().ok()?;
+    // Only the last part may have a fraction; minutes and seconds stay below 60.
+    if !value.is_finite() || value < 0.0 || (i + 1 < parts.len() && value.fract() != 0.0) {
+      return None;
+    }
+    if i > 0 && value >= 60.0 {
+      return None;
+    }
+    secs = secs * 60.0 + value;
+  }
+  Some(secs)
+}
+
+/// `parse_time` for clap arguments.
+pub fn parse_time_arg(s: &str) -> Result<f64, String> {
+  parse_time(s).ok_or_else(|| format!("invalid time '{s}' (expected seconds, m:ss or h:mm:ss)"))
+}
+
+/// `1m23s`-style label for file names (`1h02m03s` past an hour).
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn file_label(secs: f64) -> String {
+  let total = secs.max(0.0) as u64;
+  let (h, m, s) = (total / 3600, (total % 3600) / 60, total % 60);
+  if h > 0 { format!("{h}h{m:02}m{s:02}s") } else { format!("{m}m{s:02}s") }
+}
+
+/// Default file name for a clip: `<video_id>-1m23s-1m45s.<ext>`.
+pub fn default_name(video_id: &str, from: f64, to: f64, ext: &str) -> PathBuf {
+  PathBuf::from(format!("{video_id}-{}-{}.{ext}", file_label(from), file_label(to)))
+}
+
+/// Ranges (with text) covering each key segment of a summary, padded and capped.
+pub fn key_segment_ranges(output: &SummaryOutput) -> Vec<(f64, f64, String)> {
+  output
+    .summary
+    .key_segments
+    .iter()
+    .map(|k| {
+      let end = output
+        .utterances
+        .iter()
+        .find(|u| u.class == UtteranceClass::KeySegment && (u.start - k.at_secs).abs() < 0.01)
+        .map_or(k.at_secs + MAX_KEY_SEGMENT_SECS, |u| u.end);
+      let from = (k.at_secs - KEY_SEGMENT_PAD_SECS).max(0.0);
+      let to = (end + KEY_SEGMENT_PAD_SECS).min(from + MAX_KEY_SEGMENT_SECS);
+      (from, to, k.text.clone())
+    })
+    .collect()
+}
+
+/// Cut `[from, to)` of the audio at `stream_url` into `out`.
+pub async fn export(stream_url: &str, from: f64, to: f64, out: &Path) -> Result<()> {
+  if to <= from {
+    return Err(error::fail(
+      ErrorKind::InvalidInput,
+      format!("Clip end ({}) must be after its start ({})", summarize::timestamp(to), summarize::timestamp(from)),
+    ));
+  }
+  if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+    std::fs::create_dir_all(parent).context("Failed to create clip directory")?;
+  }
+  let output = tokio::process::Command::new("ffmpeg")
+    .args(["-y", "-loglevel", "error", "-ss", &format!("{from:.2}"), "-t", &format!("{:.2}", to - from), "-i"])
+    .arg(stream_url)
+    .arg("-vn")
+    .arg(out)
+    .stdin(std::process::Stdio::null())
+    .output()
+    .await
+    .map_err(|e| {
+      if e.kind() == std::io::ErrorKind::NotFound {
+        error::fail(
+          ErrorKind::DependencyMissing,
+          "ffmpeg not found. Install it with: brew install ffmpeg (macOS) or apt install ffmpeg (Linux)",
+        )
+      } else {
+        anyhow!(e).context("Failed to run ffmpeg")
+      }
+    })?;
+  if !output.status.success() {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    bail!("ffmpeg failed to cut the clip: {}", stderr.trim());
+  }
+  Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::summarize::{ClassifiedUtterance, KeySegment};
+
+  #[test]
+  fn parses_times() {
+    assert_eq!(parse_time("83"), Some(83.0));
+    assert_eq!(parse_time("83.5"), Some(83.5));
+    assert_eq!(parse_time("1:23"), Some(83.0));
+    assert_eq!(parse_time("1:23.5"), Some(83.5));
+    assert_eq!(parse_time("1:02:03"), Some(3723.0));
+    assert_eq!(parse_time("1:75"), None);
+    assert_eq!(parse_time("1.5:00"), None);
+    assert_eq!(parse_time("-3"), None);
+    assert_eq!(parse_time("1:2:3:4"), None);
+    assert_eq!(parse_time("abc"), None);
+  }
+
+  #[test]
+  fn default_names_are_readable() {
+    assert_eq!(default_name("abc", 83.0, 105.4, "m4a"), PathBuf::from("abc-1m23s-1m45s.m4a"));
+    assert_eq!(default_name("abc", 3723.0, 3730.0, "mp3"), PathBuf::from("abc-1h02m03s-1h02m10s.mp3"));
+  }
+
+  #[test]
+  fn key_segments_are_padded_and_capped() {
+    let utterance = |start: f64, end: f64| ClassifiedUtterance {
+      start,
+      end,
+      text: format!("key at {start}"),
+      class: UtteranceClass::KeySegment,
+    };
+    let mut output = SummaryOutput::fixture("Test", 400.0);
+    output.summary.key_segments = vec![
+      KeySegment { at_secs: 0.5, text: "key at 0.5".to_string() },
+      KeySegment { at_secs: 100.0, text: "key at 100".to_string() },
+    ];
+    output.utterances = vec![utterance(0.5, 12.0), utterance(100.0, 300.0)];
+    let ranges = key_segment_ranges(&output);
+    assert_eq!(ranges[0], (0.0, 13.0, "key at 0.5".to_string()));
+    assert_eq!(ranges[1].0, 99.0);
+    assert_eq!(ranges[1].1, 99.0 + MAX_KEY_SEGMENT_SECS);
+  }
+}
@@ -0,0 +1,271 @@
+//! Shell completions generated from the clap definition.
+//!
+//! `yp completions <shell>` prints `clap_complete`'s script, so options and
+//! subcommands never drift from `Command`. For bash and zsh the generated
+//! `_yp` function is renamed `_yp_static` and wrapped; fish gets one extra
+//! rule. The wrapper first asks the hidden `yp _complete` for values: it walks
+//! the same clap definition to find the argument under the cursor and, for a
+//! video argument (value name `VIDEO`), prints the cached video IDs, or for a
+//! channel argument (`CHANNEL`), recently used and configured channels. Any
+//! other position falls through to the static completion.
+
+use clap::{Arg, Command};
+use clap_complete::Shell;
+
+/// Arguments whose values are completed at completion time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dynamic {
+  Video,
+  Channel,
+}
+
+/// The dynamic kind of an argument, from its value name (or its id).
+fn dynamic_kind(arg: &Arg) -> Option<Dynamic> {
+  let name = arg
+    .get_value_names()
+    .and_then(|names| names.first())
+    .map_or_else(|| arg.get_id().as_str().to_string(), ToString::to_string)
+    .to_lowercase();
+  match name.as_str() {
+    "video" => Some(Dynamic::Video),
+    "channel" | "channels" => Some(Dynamic::Channel),
+    _ => None,
+  }
+}
+
+/// The option a word names, if it expects its value in the next word.
+fn option_awaiting_value<'a>(cmd: &'a Command, word: &str) -> Option<&'a Arg> {
+  let arg = if let Some(long) = word.strip_prefix("--") {
+    if long.contains('=') {
+      return None;
+    }
+    cmd.get_arguments().find(|a| a.get_long() == Some(long))?
+  } else {
+    let mut chars = word.strip_prefix('-')?.chars();
+    let (Some(short), None) = (chars.next(), chars.next()) else { return None };
+    cmd.get_arguments().find(|a| a.get_short() == Some(short))?
+  };
+  let takes_value = arg.get_action().takes_values() && arg.get_num_args().is_some_and(|n| n.min_values() > 0);
+  takes_value.then_some(arg)
+}
+
+/// Which dynamic argument the next word fills, given the words before it
+/// (without the binary name). `None` when it is anything else.
+pub fn dynamic_at(mut cmd: Command, words: &[String]) -> Option<Dynamic> {
+  cmd.build();
+  let mut cmd = &cmd;
+  let mut pending: Option<&Arg> = None;
+  let mut positionals = 0;
+  let mut only_positionals = false;
+
+  for word in words {
+    if pending.take().is_some() {
+      continue;
+    }
+    if !only_positionals && word == "--" {
+      only_positionals = true;
+    } else if !only_positionals && word.len() > 1 && word.starts_with('-') {
+      pending = option_awaiting_value(cmd, word);
+    } else if let Some(sub) = cmd.find_subcommand(word).filter(|_| positionals == 0) {
+      cmd = sub;
+    } else {
+      positionals += 1;
+    }
+  }
+
+  if let Some(arg) = pending {
+    return dynamic_kind(arg);
+  }
+  if cmd.has_subcommands() {
+    return None;
+  }
+  let args: Vec<&Arg> = cmd.get_positionals().collect();
+  let arg = match args.get(positionals) {
+    Some(arg) => *arg,
+    // A trailing list keeps taking values.
+    None => args.last().filter(|a| a.get_num_args().is_some_and(|n| n.max_values() > 1))?,
+  };
+  dynamic_kind(arg)
+}
+
+/// Candidate values for a dynamic argument, as `(value, description)`, most recent first.
+pub fn candidates(kind: Dynamic) -> Vec<(String, String)> {
+  match kind {
+    Dynamic::Video => crate::cache::read_videos().into_iter().rev().collect(),
+    Dynamic::Channel => {
+      let recent = crate::cache::read_channels().into_iter().map(|c| (c, "recently used"));
+      let configured = crate::watch::WatchConfig::load()
+        .map(|config| config.channels)
+        .unwrap_or_default()
+        .into_iter()
+        .chain(std::iter::once(crate::constants::constants().pastel_sketchbook_channel.clone()))
+        .map(|c| (c, "configured"));
+      let mut seen = std::collections::HashSet::new();
+      recent
+        .chain(configured)
+        .filter(|(c, _)| seen.insert(c.clone()))
+        .map(|(c, description)| (c, description.to_string()))
+        .collect()
+    }
+  }
+}
+
+/// One candidate line in the format the shell's wrapper reads.
+pub fn candidate_line(shell: Shell, value: &str, description: &str) -> String {
+  match shell {
+    // `_describe` splits on the first unescaped colon.
+    Shell::Zsh => {
+      let escape = |s: &str| s.replace('\\', "\\\\").replace(':', "\\:");
+      format!("{}:{}", escape(value), escape(description))
+    }
+    Shell::Fish => format!("{value}\t{}", description.replace(['\t', '\n'], " ")),
+    _ => value.to_string(),
+  }
+}
+
+const BASH_WRAPPER: &str = r#"
+_yp() {
+    # COMP_WORDS splits URLs and `--flag=value` at `:` and `=`; keep words whole.
+    local cur words cword
+    if declare -F _get_comp_words_by_ref >/dev/null; then
+        _get_comp_words_by_ref -n := cur words cword
+    else
+        local line="${COMP_LINE:0:COMP_POINT}"
+        read -ra words <<< "${line}"
+        cword=${#words[@]}
+        cur=""
+        if [[ ${line} != *[[:space:]] ]]; then
+            cword=$((cword - 1))
+            cur="${words[cword]}"
+        fi
+    fi
+    if [[ ${cur} != -* ]]; then
+        local values
+        values="$(yp _complete bash -- "${words[@]:1:cword-1}" 2>/dev/null)"
+        if [[ -n ${values} ]]; then
+            local IFS=$'\n'
+            COMPREPLY=($(compgen -W "${values}" -- "${cur}"))
+            # Bash replaces only the text after the last `:` or `=` of the word.
+            local prefix="${cur%"${cur##*[:=]}"}"
+            COMPREPLY=("${COMPREPLY[@]#"${prefix}"}")
+            return 0
+        fi
+    fi
+    _yp_static "$@"
+}
+"#;
+
+const ZSH_WRAPPER: &str = r#"
+_yp() {
+    if [[ $PREFIX != -* ]]; then
+        local -a values
+        values=(${(f)"$(yp _complete zsh -- "${(@)words[2,CURRENT-1]}" 2>/dev/null)"})
+        if (( $#values )); then
+            _describe 'value' values
+            return
+        fi
+    fi
+    _yp_static "$@"
+}
+
+"#;
+
+const FISH_WRAPPER: &str = r"
+function __fish_yp_dynamic
+    set -l words (commandline -opc)
+    set -e words[1]
+    yp _complete fish -- $words 2>/dev/null
+end
+complete -c yp -a '(__fish_yp_dynamic)'
+";
+
+/// The completion script for `shell`, with dynamic values for bash, fish and zsh.
+pub fn script(shell: Shell, mut cmd: Command) -> String {
+  let bin_name = cmd.get_name().to_string();
+  let mut out = Vec::new();
+  clap_complete::generate(shell, &mut cmd, bin_name, &mut out);
+  let script = String::from_utf8_lossy(&out).into_owned();
+
+  match shell {
+    Shell::Bash => format!("{}{BASH_WRAPPER}", rename_entry_point(&script)),
+    Shell::Zsh => {
+      // The trailing block calls `_yp` (or registers it) and must see the wrapper.
+      let script = rename_entry_point(&script);
+      match script.rfind("if [ \"$funcstack[1]\" = \"_yp\" ]") {
+        Some(i) => format!("{}{}{}", &script[..i], ZSH_WRAPPER.trim_start(), &script[i..]),
+        None => format!("{script}{ZSH_WRAPPER}compdef _yp yp\n"),
+      }
+    }
+    Shell::Fish => format!("{script}{FISH_WRAPPER}"),
+    _ => script,
+  }
+}
+
+/// Rename the generated `_yp` function so the wrapper can take its name.
+fn rename_entry_point(script: &str) -> String {
+  let mut renamed = false;
+  let lines: Vec<&str> = script
+    .lines()
+    .map(|line| {
+      if !renamed && line == "_yp() {" {
+        renamed = true;
+        "_yp_static() {"
+      } else {
+        line
+      }
+    })
+    .collect();
+  lines.join("\n") + "\n"
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use clap::CommandFactory;
+
+  fn at(words: &[&str]) -> Option<Dynamic> {
+    let words: Vec<String> = words.iter().map(|w| (*w).to_string()).collect();
+    dynamic_at(crate::Args::command(), &words)
+  }
+
+  #[test]
+  fn finds_the_argument_under_the_cursor() {
+    assert_eq!(at(&[]), None);
+    assert_eq!(at(&["info"]), Some(Dynamic::Video));
+    assert_eq!(at(&["-d", "kitty", "transcript", "--raw"]), Some(Dynamic::Video));
+    assert_eq!(at(&["info", "abc"]), None);
+    assert_eq!(at(&["info", "--format"]), None);
+    assert_eq!(at(&["info", "--format", "csv"]), Some(Dynamic::Video));
+    assert_eq!(at(&["channel"]), Some(Dynamic::Channel));
+    assert_eq!(at(&["channel", "--limit"]), None);
+    assert_eq!(at(&["channel", "--limit", "5"]), Some(Dynamic::Channel));
+    assert_eq!(at(&["grep", "glaze"]), Some(Dynamic::Channel));
+    assert_eq!(at(&["jobs", "--discard"]), Some(Dynamic::Video));
+    assert_eq!(at(&["watch", "@a", "-i", "30m", "@b"]), Some(Dynamic::Channel));
+    assert_eq!(at(&["search"]), None);
+  }
+
+  #[test]
+  fn scripts_wrap_the_generated_completion() {
+    for shell in [Shell::Bash, Shell::Zsh] {
+      let script = script(shell, crate::Args::command());
+      assert_eq!(script.matches("_yp() {").count(), 1, "{shell}");
+      assert!(script.contains("_yp_static() {"), "{shell}");
+      assert!(script.contains("yp _complete"), "{shell}");
+    }
+    let zsh = script(Shell::Zsh, crate::Args::command());
+    assert!(zsh.find("\n_yp() {").unwrap() < zsh.rfind("funcstack").unwrap());
+    assert!(script(Shell::Fish, crate::Args::command()).contains("(__fish_yp_dynamic)"));
+  }
+
+  #[test]
+  fn candidate_lines_per_shell() {
+    assert_eq!(candidate_line(Shell::Zsh, "abc", "Glaze: part 1"), "abc:Glaze\\: part 1");
+    assert_eq!(candidate_line(Shell::Fish, "abc", "Glaze"), "abc\tGlaze");
+    assert_eq!(candidate_line(Shell::Bash, "abc", "Glaze"), "abc");
+  }
+}
@@ -1,34 +1,85 @@
+use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::{self, ErrorKind};
+use crate::llm::LlmConfig;
+use crate::summarize::ClassifierConfig;
+use crate::watch::WatchConfig;
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Config {
   pub theme_name: Option<String>,
   pub frame_mode: Option<String>,
+  /// `[classifier]` table: transcript classifier thresholds (see `ClassifierConfig`).
+  pub classifier: Option<ClassifierConfig>,
+  /// `[llm]` table: OpenAI-compatible endpoint for `yp summarize --llm`.
+  pub llm: Option<LlmConfig>,
+  /// `[watch]` table: channels and hook command for `yp watch`.
+  pub watch: Option<WatchConfig>,
+}
+
+/// `<config_dir>/yp/prefs.toml`.
+fn config_path() -> Option<PathBuf> {
+  ProjectDirs::from("", "", "yp").map(|d| d.config_dir().join("prefs.toml"))
 }
 
 impl Config {
-  pub fn load() -> Self {
-    if let Some(proj_dirs) = ProjectDirs::from("", "", "yp") {
-      let config_file = proj_dirs.config_dir().join("prefs.toml");
-      if let Ok(content) = std::fs::read_to_string(config_file)
-        && let Ok(config) = toml::from_str(&content)
-      {
-        return config;
-      }
-    }
-    Self::default()
+  /// Read the config file. A missing file is the default config; one that can't
+  /// be read or parsed is an error, so a typo is never mistaken for an empty file.
+  pub fn read() -> Result<Self> {
+    config_path().map_or_else(|| Ok(Self::default()), |path| Self::read_from(&path))
+  }
+
+  fn read_from(path: &Path) -> Result<Self> {
+    let content = match std::fs::read_to_string(path) {
+      Ok(content) => content,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+      Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    toml::from_str(&content)
+      .map_err(|e| error::fail(ErrorKind::InvalidInput, format!("Failed to parse {}: {e}", path.display())))
+  }
+
+  /// Write the config. Refuses to replace a file that doesn't parse: this
+  /// config could not see its tables, so writing it would delete them.
+  pub fn save(&self) -> Result<()> {
+    config_path().map_or(Ok(()), |path| self.save_to(&path))
   }
 
-  pub fn save(&self) {
-    if let Some(proj_dirs) = ProjectDirs::from("", "", "yp") {
-      let config_dir = proj_dirs.config_dir();
-      if std::fs::create_dir_all(config_dir).is_ok() {
-        let config_file = config_dir.join("prefs.toml");
-        if let Ok(content) = toml::to_string(self) {
-          let _ = std::fs::write(config_file, content);
-        }
-      }
+  fn save_to(&self, path: &Path) -> Result<()> {
+    Self::read_from(path).context("Not saving preferences over a config file that has errors")?;
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent).context("Failed to create config directory")?;
     }
+    let content = toml::to_string(self).context("Failed to serialize preferences")?;
+    std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn a_broken_file_is_an_error_and_never_overwritten() {
+    let path = std::env::temp_dir().join(format!("yp-prefs-test-{}.toml", std::process::id()));
+    let broken = "theme_name = \"Dusk\"\n\n[classifier]\nfiller_ratio = 0.5\nnot_a_key = 1\n";
+    std::fs::write(&path, broken).unwrap();
+
+    assert!(Config::read_from(&path).is_err());
+    let config = Config { theme_name: Some("Dawn".to_string()), ..Config::default() };
+    assert!(config.save_to(&path).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), broken);
+
+    std::fs::write(&path, "theme_name = \"Dusk\"\n").unwrap();
+    config.save_to(&path).unwrap();
+    assert_eq!(Config::read_from(&path).unwrap().theme_name.as_deref(), Some("Dawn"));
+    let _ = std::fs::remove_file(&path);
   }
 }
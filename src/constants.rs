@@ -30,6 +30,7 @@ pub struct Constants {
   // Transcription
   pub chunk_secs: u32,
   pub min_chunk_bytes: u64,
+  pub whisper_instance_mb: u64,
 
   // YouTube / yt-dlp
   pub frame_extract_fps: f64,
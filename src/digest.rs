@@ -0,0 +1,482 @@
+//! Channel digest: one report over the summaries of a channel's recent videos.
+//!
+//! Every video is summarized on its own (classify + reduce); the digest then
+//! looks across them. A theme is a keyphrase that is a keyword of at least one
+//! video and shows up in at least `MIN_THEME_VIDEOS` videos, either as a
+//! keyword or mentioned in their kept speech. Themes rank by summed relevance,
+//! and each lists the videos most about it with a link to its first mention.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+use crate::summarize::{self, SummaryFormat, SummaryOutput};
+use crate::textrank;
+
+/// Most themes in a digest.
+const MAX_THEMES: usize = 12;
+/// Related videos listed per theme.
+const RELATED_PER_THEME: usize = 3;
+/// Keywords listed per video.
+const KEYWORDS_PER_VIDEO: usize = 5;
+/// A phrase must appear in this many videos to be a theme.
+const MIN_THEME_VIDEOS: usize = 2;
+/// Relevance of one plain mention (not a keyword) of a phrase in a video.
+const MENTION_WEIGHT: f64 = 0.1;
+/// Cap on mention-based relevance, so a keyword always outranks chatter.
+const MAX_MENTION_RELEVANCE: f64 = 0.5;
+
+/// A video that is about a theme.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedVideo {
+  pub video_id: String,
+  pub title: String,
+  /// Keyword score (0–1), or a smaller weight for plain mentions.
+  pub relevance: f64,
+  /// First mention of the theme in the video.
+  pub at_secs: f64,
+  /// Link to that moment.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub url: Option<String>,
+}
+
+/// A keyphrase that recurs across videos.
+#[derive(Debug, Clone, Serialize)]
+pub struct Theme {
+  pub phrase: String,
+  /// Number of videos that mention it.
+  pub videos: usize,
+  /// Summed relevance over those videos.
+  pub score: f64,
+  /// The videos most about it, best first.
+  pub related: Vec<RelatedVideo>,
+}
+
+/// One video on the timeline, with the themes it touches.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+  pub date: Option<String>,
+  pub video_id: String,
+  pub title: String,
+  pub themes: Vec<String>,
+}
+
+/// Per-video line of the digest.
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestVideo {
+  pub video_id: String,
+  pub title: String,
+  pub url: String,
+  pub upload_date: Option<String>,
+  pub duration: Option<String>,
+  /// First sentence of the video's abstract.
+  pub one_liner: String,
+  pub keywords: Vec<String>,
+  pub themes: Vec<String>,
+}
+
+/// A channel-level report built from per-video summaries.
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+  pub _hint: String,
+  pub channel: String,
+  /// Build time (RFC 3339).
+  pub generated_at: String,
+  pub video_count: usize,
+  /// Oldest and newest upload date, when known.
+  pub date_range: Option<(String, String)>,
+  pub themes: Vec<Theme>,
+  /// Videos oldest first.
+  pub timeline: Vec<TimelineEntry>,
+  /// Videos in channel order (latest first).
+  pub videos: Vec<DigestVideo>,
+}
+
+/// Kept sentences of one video as (start, words), for phrase matching.
+fn kept_words(output: &SummaryOutput) -> Vec<(f64, Vec<String>)> {
+  output
+    .utterances
+    .iter()
+    .filter(|u| summarize::is_kept(&u.class))
+    .map(|u| (u.start, textrank::words(&u.text)))
+    .collect()
+}
+
+/// Count whole-word mentions of `phrase` and the time of the first one.
+fn mentions(sentences: &[(f64, Vec<String>)], phrase: &[String]) -> (usize, Option<f64>) {
+  let mut count = 0;
+  let mut first = None;
+  for (start, words) in sentences {
+    let n = words.windows(phrase.len()).filter(|w| *w == phrase).count();
+    if n > 0 {
+      count += n;
+      first.get_or_insert(*start);
+    }
+  }
+  (count, first)
+}
+
+/// Whether one phrase's words appear as a run inside the other's.
+fn overlaps(a: &[String], b: &[String]) -> bool {
+  let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+  long.windows(short.len()).any(|w| w == short)
+}
+
+/// Build a digest from `(video_id, summary)` pairs in channel order.
+#[allow(clippy::cast_precision_loss)]
+pub fn build(channel: &str, summaries: &[(String, SummaryOutput)]) -> Digest {
+  let sentences: Vec<Vec<(f64, Vec<String>)>> = summaries.iter().map(|(_, s)| kept_words(s)).collect();
+
+  // Candidates: every video keyword, normalized to words.
+  let candidates: BTreeSet<Vec<String>> = summaries
+    .iter()
+    .flat_map(|(_, s)| &s.summary.keywords)
+    .map(|k| textrank::words(&k.phrase))
+    .filter(|w| !w.is_empty())
+    .collect();
+
+  let mut ranked: Vec<(Vec<String>, Theme)> = Vec::new();
+  for words in candidates {
+    let phrase = words.join(" ");
+    let mut related: Vec<RelatedVideo> = Vec::new();
+    for ((video_id, output), sentences) in summaries.iter().zip(&sentences) {
+      let keyword = output.summary.keywords.iter().find(|k| textrank::words(&k.phrase) == words);
+      let (relevance, at_secs) = match keyword {
+        Some(k) => (k.score, k.first_at_secs),
+        None => match mentions(sentences, &words) {
+          (n, Some(at)) => ((n as f64 * MENTION_WEIGHT).min(MAX_MENTION_RELEVANCE), at),
+          _ => continue,
+        },
+      };
+      related.push(RelatedVideo {
+        video_id: video_id.clone(),
+        title: output.video.title.clone(),
+        relevance,
+        at_secs,
+        url: summarize::moment_url(&output.video.url, at_secs),
+      });
+    }
+    if related.len() < MIN_THEME_VIDEOS {
+      continue;
+    }
+    related.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
+    let score = related.iter().map(|r| r.relevance).sum();
+    let videos = related.len();
+    related.truncate(RELATED_PER_THEME);
+    ranked.push((words, Theme { phrase, videos, score, related }));
+  }
+  ranked.sort_by(|a, b| b.1.score.total_cmp(&a.1.score).then_with(|| a.1.phrase.cmp(&b.1.phrase)));
+
+  // Keep the best of overlapping phrases ("glaze" vs "glaze layers").
+  let mut chosen: Vec<Vec<String>> = Vec::new();
+  let mut themes: Vec<Theme> = Vec::new();
+  for (words, theme) in ranked {
+    if themes.len() == MAX_THEMES {
+      break;
+    }
+    if chosen.iter().any(|c| overlaps(c, &words)) {
+      continue;
+    }
+    chosen.push(words);
+    themes.push(theme);
+  }
+
+  // Themes per video, in theme rank order; matched against every mention, not
+  // just the top related videos.
+  let video_themes: Vec<Vec<String>> = summaries
+    .iter()
+    .zip(&sentences)
+    .map(|((_, output), sentences)| {
+      themes
+        .iter()
+        .zip(&chosen)
+        .filter(|(_, words)| {
+          output.summary.keywords.iter().any(|k| textrank::words(&k.phrase) == **words)
+            || mentions(sentences, words).0 > 0
+        })
+        .map(|(theme, _)| theme.phrase.clone())
+        .collect()
+    })
+    .collect();
+
+  let videos: Vec<DigestVideo> = summaries
+    .iter()
+    .zip(&video_themes)
+    .map(|((video_id, output), themes)| {
+      let one_liner = summarize::first_sentence(&output.summary.abstract_text);
+      DigestVideo {
+        video_id: video_id.clone(),
+        title: output.video.title.clone(),
+        url: output.video.url.clone(),
+        upload_date: output.video.upload_date.as_deref().map(summarize::display_date),
+        duration: output.video.duration.clone(),
+        one_liner,
+        keywords: output.summary.keywords.iter().take(KEYWORDS_PER_VIDEO).map(|k| k.phrase.clone()).collect(),
+        themes: themes.clone(),
+      }
+    })
+    .collect();
+
+  // Timeline: oldest first; undated videos keep their channel order at the end.
+  let mut timeline: Vec<TimelineEntry> = videos
+    .iter()
+    .rev()
+    .map(|v| TimelineEntry {
+      date: v.upload_date.clone(),
+      video_id: v.video_id.clone(),
+      title: v.title.clone(),
+      themes: v.themes.clone(),
+    })
+    .collect();
+  timeline.sort_by(|a, b| match (&a.date, &b.date) {
+    (Some(a), Some(b)) => a.cmp(b),
+    (Some(_), None) => std::cmp::Ordering::Less,
+    (None, Some(_)) => std::cmp::Ordering::Greater,
+    (None, None) => std::cmp::Ordering::Equal,
+  });
+  let dates: Vec<&String> = timeline.iter().filter_map(|t| t.date.as_ref()).collect();
+  let date_range = dates.first().zip(dates.last()).map(|(a, b)| ((*a).clone(), (*b).clone()));
+
+  Digest {
+    _hint: format!(
+      "Digest of {} videos from {channel}. Themes are keyphrases found in at least {MIN_THEME_VIDEOS} videos, \
+       ranked by summed relevance (keyword score, or {MENTION_WEIGHT} per plain mention up to \
+       {MAX_MENTION_RELEVANCE}); related videos link to the first mention. Run `yp summarize` on a video for \
+       its full summary.",
+      summaries.len()
+    ),
+    channel: channel.to_string(),
+    generated_at: chrono::Local::now().to_rfc3339(),
+    video_count: summaries.len(),
+    date_range,
+    themes,
+    timeline,
+    videos,
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Rendering
+// ---------------------------------------------------------------------------
+
+/// Render a digest in the requested format.
+pub fn render(digest: &Digest, format: SummaryFormat) -> Result<String> {
+  match format {
+    SummaryFormat::Json => {
+      serde_json::to_string_pretty(&crate::schema::versioned(digest)).context("Failed to serialize digest")
+    }
+    SummaryFormat::Yaml => summarize::to_yaml(&crate::schema::versioned(digest)),
+    SummaryFormat::Md => Ok(to_markdown(digest)),
+    SummaryFormat::Txt => Ok(to_text(digest)),
+  }
+}
+
+/// "12 videos · 2024-01-05 – 2024-03-01"
+fn overview(digest: &Digest) -> String {
+  match &digest.date_range {
+    Some((from, to)) if from != to => format!("{} videos · {from} – {to}", digest.video_count),
+    Some((date, _)) => format!("{} videos · {date}", digest.video_count),
+    None => format!("{} videos", digest.video_count),
+  }
+}
+
+/// Markdown report: themes with linked videos, timeline, per-video one-liners.
+pub fn to_markdown(digest: &Digest) -> String {
+  let mut md = format!("# Digest: {}\n\n{}\n", digest.channel, overview(digest));
+
+  if !digest.themes.is_empty() {
+    md.push_str("\n## Themes\n\n");
+    for (i, theme) in digest.themes.iter().enumerate() {
+      let related: Vec<String> = theme
+        .related
+        .iter()
+        .map(|r| match &r.url {
+          Some(url) => format!("[{}]({url})", summarize::md_cell(&r.title)),
+          None => r.title.clone(),
+        })
+        .collect();
+      md.push_str(&format!("{}. **{}** — {} videos: {}\n", i + 1, theme.phrase, theme.videos, related.join(", ")));
+    }
+  }
+
+  if !digest.timeline.is_empty() {
+    md.push_str("\n## Timeline\n\n| Date | Video | Themes |\n|---|---|---|\n");
+    for entry in &digest.timeline {
+      md.push_str(&format!(
+        "| {} | {} | {} |\n",
+        entry.date.as_deref().unwrap_or("—"),
+        summarize::md_cell(&entry.title),
+        summarize::md_cell(&entry.themes.join(", "))
+      ));
+    }
+  }
+
+  if !digest.videos.is_empty() {
+    md.push_str("\n## Videos\n");
+    for video in &digest.videos {
+      if video.url.is_empty() {
+        md.push_str(&format!("\n### {}\n\n", video.title.trim()));
+      } else {
+        md.push_str(&format!("\n### [{}]({})\n\n", video.title.trim(), video.url));
+      }
+      let meta: Vec<&str> = [video.upload_date.as_deref(), video.duration.as_deref()].into_iter().flatten().collect();
+      if !meta.is_empty() {
+        md.push_str(&format!("*{}*\n\n", meta.join(" · ")));
+      }
+      if !video.one_liner.is_empty() {
+        md.push_str(&format!("{}\n\n", video.one_liner));
+      }
+      if !video.keywords.is_empty() {
+        md.push_str(&format!("Keywords: {}\n", video.keywords.join(", ")));
+      }
+    }
+  }
+  md
+}
+
+/// Plain-text report with the same layout as the Markdown one.
+pub fn to_text(digest: &Digest) -> String {
+  let title = format!("Digest: {}", digest.channel);
+  let mut txt = format!("{title}\n{}\n{}\n", "=".repeat(title.chars().count()), overview(digest));
+
+  if !digest.themes.is_empty() {
+    txt.push_str("\nTHEMES\n");
+    for (i, theme) in digest.themes.iter().enumerate() {
+      txt.push_str(&format!("{:>2}. {} ({} videos)\n", i + 1, theme.phrase, theme.videos));
+      for r in &theme.related {
+        txt.push_str(&format!("    - {} @ {}\n", r.title, summarize::timestamp(r.at_secs)));
+      }
+    }
+  }
+
+  if !digest.timeline.is_empty() {
+    txt.push_str("\nTIMELINE\n");
+    for entry in &digest.timeline {
+      txt.push_str(&format!("{:<10}  {}", entry.date.as_deref().unwrap_or("?"), entry.title));
+      if !entry.themes.is_empty() {
+        txt.push_str(&format!(" [{}]", entry.themes.join(", ")));
+      }
+      txt.push('\n');
+    }
+  }
+
+  if !digest.videos.is_empty() {
+    txt.push_str("\nVIDEOS\n");
+    for video in &digest.videos {
+      txt.push_str(&format!("\n{}\n", video.title.trim()));
+      if !video.url.is_empty() {
+        txt.push_str(&format!("  {}\n", video.url));
+      }
+      if !video.one_liner.is_empty() {
+        txt.push_str(&format!("  {}\n", video.one_liner));
+      }
+      if !video.keywords.is_empty() {
+        txt.push_str(&format!("  Keywords: {}\n", video.keywords.join(", ")));
+      }
+    }
+  }
+  txt
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::keywords::Keyword;
+  use crate::summarize::{ClassifiedUtterance, UtteranceClass};
+
+  fn keyword(phrase: &str, score: f64, first_at_secs: f64) -> Keyword {
+    Keyword { phrase: phrase.to_string(), score, first_at_secs }
+  }
+
+  fn summary(id: &str, date: &str, keywords: Vec<Keyword>, speech: &[&str]) -> (String, SummaryOutput) {
+    let utterances = speech
+      .iter()
+      .enumerate()
+      .map(|(i, text)| ClassifiedUtterance {
+        start: i as f64 * 10.0,
+        end: i as f64 * 10.0 + 5.0,
+        text: (*text).to_string(),
+        class: UtteranceClass::Normal,
+      })
+      .collect();
+    let mut output = SummaryOutput::fixture(&format!("Video {id}"), 600.0);
+    output.video.url = format!("https://youtube.com/watch?v={id}");
+    output.video.uploader = Some("Painter".to_string());
+    output.video.duration = Some("10:00".to_string());
+    output.video.upload_date = Some(date.to_string());
+    output.summary.total_utterances = speech.len() as u64;
+    output.summary.abstract_text = format!("We paint {id} today. Then we varnish it.");
+    output.summary.keywords = keywords;
+    output.utterances = utterances;
+    (id.to_string(), output)
+  }
+
+  fn sample() -> Vec<(String, SummaryOutput)> {
+    vec![
+      summary(
+        "c",
+        "20240301",
+        vec![keyword("wet glaze", 1.0, 30.0), keyword("sunset", 0.6, 5.0)],
+        &["Start with the sky.", "A wet glaze deepens it."],
+      ),
+      summary(
+        "b",
+        "20240215",
+        vec![keyword("mountains", 1.0, 12.0)],
+        &["Sketch the mountains first.", "Then a wet glaze over the ridge."],
+      ),
+      summary("a", "20240101", vec![keyword("wet glaze", 0.8, 40.0), keyword("glaze", 0.5, 2.0)], &["Hello."]),
+    ]
+  }
+
+  #[test]
+  fn themes_need_several_videos() {
+    let digest = build("@Painter", &sample());
+    let phrases: Vec<&str> = digest.themes.iter().map(|t| t.phrase.as_str()).collect();
+    // "wet glaze": keyword in c and a, mentioned in b. "glaze" overlaps it; the rest are single-video.
+    assert_eq!(phrases, vec!["wet glaze"]);
+    let theme = &digest.themes[0];
+    assert_eq!(theme.videos, 3);
+    let related: Vec<&str> = theme.related.iter().map(|r| r.video_id.as_str()).collect();
+    assert_eq!(related, vec!["c", "a", "b"]);
+    assert!((theme.related[2].relevance - MENTION_WEIGHT).abs() < 1e-9);
+    assert_eq!(theme.related[2].at_secs, 10.0);
+    assert_eq!(theme.related[0].url.as_deref(), Some("https://youtube.com/watch?v=c&t=30s"));
+  }
+
+  #[test]
+  fn timeline_runs_oldest_first() {
+    let digest = build("@Painter", &sample());
+    let order: Vec<&str> = digest.timeline.iter().map(|t| t.video_id.as_str()).collect();
+    assert_eq!(order, vec!["a", "b", "c"]);
+    assert_eq!(digest.date_range, Some(("2024-01-01".to_string(), "2024-03-01".to_string())));
+    assert_eq!(digest.videos[0].video_id, "c");
+    assert_eq!(digest.videos[0].one_liner, "We paint c today.");
+    assert_eq!(digest.videos[1].themes, vec!["wet glaze"]);
+  }
+
+  #[test]
+  fn mentions_match_whole_words() {
+    let sentences = vec![(0.0, textrank::words("Glazes and a glaze.")), (4.0, textrank::words("glaze again"))];
+    assert_eq!(mentions(&sentences, &["glaze".to_string()]), (2, Some(0.0)));
+    assert_eq!(mentions(&sentences, &["wet".to_string(), "glaze".to_string()]), (0, None));
+  }
+
+  #[test]
+  fn renders_markdown_and_text() {
+    let digest = build("@Painter", &sample());
+    let md = to_markdown(&digest);
+    assert!(md.starts_with("# Digest: @Painter\n\n3 videos · 2024-01-01 – 2024-03-01\n"), "{md}");
+    assert!(md.contains("1. **wet glaze** — 3 videos: [Video c](https://youtube.com/watch?v=c&t=30s)"), "{md}");
+    assert!(md.contains("| 2024-01-01 | Video a | wet glaze |"), "{md}");
+    assert!(md.contains("### [Video b](https://youtube.com/watch?v=b)"), "{md}");
+
+    let txt = to_text(&digest);
+    assert!(txt.contains(" 1. wet glaze (3 videos)\n    - Video c @ 0:30\n"), "{txt}");
+    assert!(txt.contains("2024-02-15  Video b [wet glaze]"), "{txt}");
+  }
+}
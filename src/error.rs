@@ -0,0 +1,239 @@
+//! Error taxonomy shared by the CLI, the HTTP API and the MCP server.
+//!
+//! Failures are tagged with an [`ErrorKind`] where they are detected: a
+//! missing binary, bad user input, a failed pipeline stage, or a yt-dlp run
+//! classified from its stderr. Untagged errors are `internal` (or `network`
+//! for connection failures). Each kind has a stable `code` for JSON and
+//! a distinct process exit status, so scripts can branch on either.
+
+use serde_json::{Value, json};
+use std::fmt;
+use std::process::ExitCode;
+
+/// What went wrong, independent of which command hit it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+  /// Bad arguments or input lines.
+  InvalidInput,
+  /// No such video, channel, checkpoint or result.
+  NotFound,
+  /// The video exists but cannot be watched: private, removed, members-only, region-locked.
+  Unavailable,
+  /// The video needs a signed-in, age-verified account.
+  AgeRestricted,
+  /// yt-dlp, ffmpeg or mpv is not installed.
+  DependencyMissing,
+  /// YouTube, the model download or the LLM endpoint could not be reached.
+  Network,
+  /// Audio was fetched but whisper did not produce a transcript.
+  TranscriptionFailed,
+  /// Anything else.
+  Internal,
+}
+
+impl ErrorKind {
+  pub const ALL: [Self; 8] = [
+    Self::InvalidInput,
+    Self::NotFound,
+    Self::Unavailable,
+    Self::AgeRestricted,
+    Self::DependencyMissing,
+    Self::Network,
+    Self::TranscriptionFailed,
+    Self::Internal,
+  ];
+
+  /// The `code` field of JSON errors.
+  pub fn code(self) -> &'static str {
+    match self {
+      Self::InvalidInput => "invalid_input",
+      Self::NotFound => "not_found",
+      Self::Unavailable => "unavailable",
+      Self::AgeRestricted => "age_restricted",
+      Self::DependencyMissing => "dependency_missing",
+      Self::Network => "network",
+      Self::TranscriptionFailed => "transcription_failed",
+      Self::Internal => "internal",
+    }
+  }
+
+  /// Process exit status. 2 matches clap's usage errors.
+  pub fn exit_code(self) -> u8 {
+    match self {
+      Self::Internal => 1,
+      Self::InvalidInput => 2,
+      Self::NotFound => 3,
+      Self::Unavailable => 4,
+      Self::AgeRestricted => 5,
+      Self::DependencyMissing => 6,
+      Self::Network => 7,
+      Self::TranscriptionFailed => 8,
+    }
+  }
+
+  /// Recognize a kind from yt-dlp's stderr.
+  pub fn from_message(text: &str) -> Option<Self> {
+    let text = text.to_lowercase();
+    let any = |needles: &[&str]| needles.iter().any(|n| text.contains(n));
+    if any(&["confirm your age", "age-restricted", "age restricted", "inappropriate for some users"]) {
+      Some(Self::AgeRestricted)
+    } else if any(&[
+      "private video",
+      "video unavailable",
+      "this video is unavailable",
+      "members-only",
+      "has been removed",
+      "not available in your country",
+      "premieres in",
+    ]) {
+      Some(Self::Unavailable)
+    } else if any(&[
+      "does not exist",
+      "http error 404",
+      "incomplete youtube id",
+      "is not a valid url",
+      "unsupported url",
+    ]) {
+      Some(Self::NotFound)
+    } else if any(&[
+      "unable to download",
+      "timed out",
+      "name resolution",
+      "name or service not known",
+      "connection refused",
+      "connection reset",
+      "network is unreachable",
+    ]) {
+      Some(Self::Network)
+    } else {
+      None
+    }
+  }
+}
+
+/// An error tagged with its kind. Wrapping it in `anyhow` context keeps the tag.
+#[derive(Debug)]
+pub struct YpError {
+  pub kind: ErrorKind,
+  message: String,
+}
+
+impl fmt::Display for YpError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+impl std::error::Error for YpError {}
+
+/// Create a tagged error.
+pub fn fail(kind: ErrorKind, message: impl Into<String>) -> anyhow::Error {
+  anyhow::Error::new(YpError { kind, message: message.into() })
+}
+
+/// A failed yt-dlp run, classified from its stderr.
+pub fn yt_dlp_failed(what: &str, stderr: &[u8]) -> anyhow::Error {
+  let stderr = String::from_utf8_lossy(stderr);
+  let kind = ErrorKind::from_message(&stderr).unwrap_or(ErrorKind::Internal);
+  fail(kind, format!("yt-dlp {what} failed: {}", stderr.trim()))
+}
+
+/// The kind of an error: its tag, else `Network` for a `reqwest` connection failure, else `Internal`.
+pub fn kind_of(err: &anyhow::Error) -> ErrorKind {
+  if let Some(tagged) = err.chain().find_map(|e| e.downcast_ref::<YpError>()) {
+    return tagged.kind;
+  }
+  if err.chain().filter_map(|e| e.downcast_ref::<reqwest::Error>()).any(|e| e.is_connect() || e.is_timeout()) {
+    return ErrorKind::Network;
+  }
+  ErrorKind::Internal
+}
+
+/// Tag an error that has no recognizable kind with `fallback`.
+pub fn or_kind(err: anyhow::Error, fallback: ErrorKind) -> anyhow::Error {
+  match kind_of(&err) {
+    ErrorKind::Internal => fail(fallback, format!("{err:#}")),
+    _ => err,
+  }
+}
+
+/// `{code, message}`: the `error` field of error outputs and per-video records.
+pub fn error_value(err: &anyhow::Error) -> Value {
+  json!({ "code": kind_of(err).code(), "message": format!("{err:#}") })
+}
+
+/// Report a failed command: the message on stderr and the kind's exit status.
+/// Commands with JSON or JSONL output (`json_line`) also end stdout with the
+/// JSON error, so parsers of that output see it; others keep stdout clean.
+pub fn report(err: &anyhow::Error, json_line: bool) -> ExitCode {
+  use std::io::Write;
+  if json_line {
+    let line = json!({ "error": error_value(err) });
+    let mut stdout = std::io::stdout().lock();
+    let _ = serde_json::to_writer(&mut stdout, &crate::schema::versioned(&line))
+      .map_err(std::io::Error::from)
+      .and_then(|()| writeln!(stdout))
+      .and_then(|()| stdout.flush());
+  }
+  eprintln!("Error: {err:#}");
+  ExitCode::from(kind_of(err).exit_code())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Context;
+
+  #[test]
+  fn exit_codes_are_distinct() {
+    let mut codes: Vec<u8> = ErrorKind::ALL.iter().map(|k| k.exit_code()).collect();
+    codes.sort_unstable();
+    codes.dedup();
+    assert_eq!(codes.len(), ErrorKind::ALL.len());
+    assert!(!codes.contains(&0));
+  }
+
+  #[test]
+  fn classifies_yt_dlp_messages() {
+    let cases = [
+      ("ERROR: [youtube] abc: Private video. Sign in if you've been granted access", ErrorKind::Unavailable),
+      ("ERROR: [youtube] abc: Sign in to confirm your age. This video may be inappropriate", ErrorKind::AgeRestricted),
+      ("ERROR: [youtube] abc: Video unavailable", ErrorKind::Unavailable),
+      ("ERROR: [youtube:tab] @nobody: This channel does not exist.", ErrorKind::NotFound),
+      (
+        "ERROR: Unable to download webpage: <urlopen error [Errno -3] Temporary failure in name resolution>",
+        ErrorKind::Network,
+      ),
+    ];
+    for (message, kind) in cases {
+      assert_eq!(ErrorKind::from_message(message), Some(kind), "{message}");
+    }
+    assert_eq!(ErrorKind::from_message("whisper returned nothing"), None);
+  }
+
+  #[test]
+  fn untagged_errors_are_internal_whatever_they_say() {
+    assert_eq!(kind_of(&anyhow::anyhow!("Video unavailable")), ErrorKind::Internal);
+    assert_eq!(
+      kind_of(&yt_dlp_failed("video info", b"ERROR: [youtube] abc: Video unavailable")),
+      ErrorKind::Unavailable
+    );
+  }
+
+  #[test]
+  fn tags_survive_context() {
+    let err = Err::<(), _>(fail(ErrorKind::NotFound, "No such video")).context("Failed to get video info").unwrap_err();
+    assert_eq!(kind_of(&err), ErrorKind::NotFound);
+    assert_eq!(error_value(&err)["code"], "not_found");
+    assert_eq!(error_value(&err)["message"], "Failed to get video info: No such video");
+    assert_eq!(kind_of(&or_kind(err, ErrorKind::TranscriptionFailed)), ErrorKind::NotFound);
+    assert_eq!(
+      kind_of(&or_kind(anyhow::anyhow!("boom"), ErrorKind::TranscriptionFailed)),
+      ErrorKind::TranscriptionFailed
+    );
+  }
+}
@@ -0,0 +1,335 @@
+//! Inverted index over the transcript store, for `yp grep` and the TUI's `?` search.
+//!
+//! Maps every word to the videos whose stored transcript contains it and the
+//! utterances it occurs in. The index lives in `<cache_dir>/yp/index.json` and
+//! is updated by `store::mark_complete` / `store::discard`, so it follows
+//! every finished transcript; updates from concurrent processes serialize on
+//! `index.lock`. A query narrows the candidate videos to those containing all of
+//! its words, then checks the phrase against the stored utterances (a phrase
+//! may run across an utterance boundary) and returns each hit with context.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::store::{self, TranscriptRecord};
+use crate::summarize;
+use crate::textrank;
+
+/// Utterances of context on either side of a hit.
+const CONTEXT_UTTERANCES: usize = 1;
+/// Longest snippet, in characters.
+const MAX_SNIPPET_CHARS: usize = 240;
+
+/// Word → video → utterance positions.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TranscriptIndex {
+  /// Indexed videos and the `updated_at` of the record they were indexed from.
+  videos: BTreeMap<String, String>,
+  postings: BTreeMap<String, BTreeMap<String, Vec<u32>>>,
+}
+
+/// One search hit.
+#[derive(Debug, Clone, Serialize)]
+pub struct Hit {
+  pub video_id: String,
+  pub title: Option<String>,
+  pub at_secs: f64,
+  pub timestamp: String,
+  /// The matching utterance with its neighbours.
+  pub snippet: String,
+  /// Link to the moment (`&t=`).
+  pub url: String,
+}
+
+impl TranscriptIndex {
+  /// Number of indexed videos.
+  pub fn len(&self) -> usize {
+    self.videos.len()
+  }
+
+  /// Whether nothing is indexed.
+  pub fn is_empty(&self) -> bool {
+    self.videos.is_empty()
+  }
+
+  /// Index a record, replacing any earlier postings for the video.
+  #[allow(clippy::cast_possible_truncation)]
+  pub fn add(&mut self, record: &TranscriptRecord) {
+    self.remove(&record.video_id);
+    for (i, u) in record.utterances.iter().enumerate() {
+      let words: HashSet<String> = textrank::words(&u.text).into_iter().collect();
+      for word in words {
+        self.postings.entry(word).or_default().entry(record.video_id.clone()).or_default().push(i as u32);
+      }
+    }
+    self.videos.insert(record.video_id.clone(), record.updated_at.clone());
+  }
+
+  /// Drop a video's postings.
+  pub fn remove(&mut self, video_id: &str) {
+    if self.videos.remove(video_id).is_none() {
+      return;
+    }
+    self.postings.retain(|_, videos| {
+      videos.remove(video_id);
+      !videos.is_empty()
+    });
+  }
+
+  /// Videos containing every term, with the utterances holding the first one.
+  fn candidates(&self, terms: &[String]) -> Vec<(&str, &[u32])> {
+    let Some((first, rest)) = terms.split_first() else { return Vec::new() };
+    let Some(videos) = self.postings.get(first) else { return Vec::new() };
+    videos
+      .iter()
+      .filter(|(id, _)| rest.iter().all(|t| self.postings.get(t).is_some_and(|v| v.contains_key(*id))))
+      .map(|(id, positions)| (id.as_str(), positions.as_slice()))
+      .collect()
+  }
+}
+
+/// Utterances of `record` where `terms` start, as consecutive words across utterances.
+fn find_phrase(record: &TranscriptRecord, terms: &[String], positions: &[u32]) -> Vec<usize> {
+  // Flatten the transcript into words tagged with their utterance.
+  let mut words: Vec<(usize, String)> = Vec::new();
+  let mut offsets: Vec<usize> = Vec::with_capacity(record.utterances.len());
+  for (i, u) in record.utterances.iter().enumerate() {
+    offsets.push(words.len());
+    words.extend(textrank::words(&u.text).into_iter().map(|w| (i, w)));
+  }
+
+  let mut hits = Vec::new();
+  for &pos in positions {
+    let utterance = pos as usize;
+    let Some(&start) = offsets.get(utterance) else { continue };
+    let matched = words[start..].iter().take_while(|(u, _)| *u == utterance).enumerate().any(|(k, _)| {
+      let at = start + k;
+      words.len() - at >= terms.len() && words[at..at + terms.len()].iter().map(|(_, w)| w).eq(terms.iter())
+    });
+    if matched {
+      hits.push(utterance);
+    }
+  }
+  hits
+}
+
+/// The utterance at `i` with `CONTEXT_UTTERANCES` on either side, trimmed to `MAX_SNIPPET_CHARS`.
+fn snippet(record: &TranscriptRecord, i: usize) -> String {
+  let from = i.saturating_sub(CONTEXT_UTTERANCES);
+  let to = (i + CONTEXT_UTTERANCES + 1).min(record.utterances.len());
+  let text: String =
+    record.utterances[from..to].iter().flat_map(|u| u.text.split_whitespace()).collect::<Vec<_>>().join(" ");
+  if text.chars().count() <= MAX_SNIPPET_CHARS {
+    return text;
+  }
+  let cut: String = text.chars().take(MAX_SNIPPET_CHARS - 1).collect();
+  format!("{}…", cut.trim_end())
+}
+
+/// Search the index for `query`, most recently transcribed videos first.
+///
+/// `only` restricts the search to a set of video IDs (e.g. one channel's).
+/// `titles` maps video IDs to titles for display.
+#[allow(clippy::cast_precision_loss)]
+pub fn search(
+  index: &TranscriptIndex,
+  query: &str,
+  only: Option<&HashSet<String>>,
+  titles: &HashMap<String, String>,
+  limit: usize,
+) -> Vec<Hit> {
+  let terms = textrank::words(query);
+  let mut records: Vec<(TranscriptRecord, &[u32])> = index
+    .candidates(&terms)
+    .into_iter()
+    .filter(|(id, _)| only.is_none_or(|ids| ids.contains(*id)))
+    .filter_map(|(id, positions)| Some((store::load(id)?, positions)))
+    .collect();
+  records.sort_by(|a, b| b.0.updated_at.cmp(&a.0.updated_at));
+
+  let mut hits = Vec::new();
+  for (record, positions) in &records {
+    for i in find_phrase(record, &terms, positions) {
+      let at_secs = record.utterances[i].start as f64 / 100.0;
+      let base = format!("https://youtube.com/watch?v={}", record.video_id);
+      hits.push(Hit {
+        video_id: record.video_id.clone(),
+        title: titles.get(&record.video_id).cloned(),
+        at_secs,
+        timestamp: summarize::timestamp(at_secs),
+        snippet: snippet(record, i),
+        url: summarize::moment_url(&base, at_secs).unwrap_or(base),
+      });
+      if hits.len() == limit {
+        return hits;
+      }
+    }
+  }
+  hits
+}
+
+// ---------------------------------------------------------------------------
+// Persistence
+// ---------------------------------------------------------------------------
+
+/// Return the index path: `<cache_dir>/yp/index.json`.
+fn index_path() -> Option<PathBuf> {
+  directories::ProjectDirs::from("", "", "yp").map(|d| d.cache_dir().join("index.json"))
+}
+
+/// Load the index, if one exists and parses.
+pub fn load() -> Option<TranscriptIndex> {
+  let content = fs::read_to_string(index_path()?).ok()?;
+  serde_json::from_str(&content).ok()
+}
+
+/// Write the index atomically via temp file + rename.
+fn save(index: &TranscriptIndex) -> Result<()> {
+  let Some(path) = index_path() else { return Ok(()) }; // silently skip if no cache dir
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).context("Failed to create cache directory")?;
+  }
+  let tmp = path.with_extension("json.tmp");
+  let json = serde_json::to_string(index).context("Failed to serialize transcript index")?;
+  fs::write(&tmp, json).context("Failed to write transcript index")?;
+  fs::rename(&tmp, &path).context("Failed to rename transcript index")?;
+  Ok(())
+}
+
+/// Take the update lock, shared by every process, until the returned file is dropped.
+fn lock() -> Result<Option<fs::File>> {
+  let Some(path) = index_path() else { return Ok(None) };
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).context("Failed to create cache directory")?;
+  }
+  let file = fs::OpenOptions::new()
+    .create(true)
+    .write(true)
+    .truncate(false)
+    .open(path.with_extension("lock"))
+    .context("Failed to open transcript index lock")?;
+  file.lock().context("Failed to lock transcript index")?;
+  Ok(Some(file))
+}
+
+/// Re-index one stored record.
+pub fn update(record: &TranscriptRecord) -> Result<()> {
+  let _lock = lock()?;
+  let mut index = load().unwrap_or_default();
+  index.add(record);
+  save(&index)
+}
+
+/// Drop a discarded video from the index.
+pub fn remove(video_id: &str) -> Result<()> {
+  let _lock = lock()?;
+  let Some(mut index) = load() else { return Ok(()) };
+  index.remove(video_id);
+  save(&index)
+}
+
+/// Rebuild the index from every complete stored record.
+pub fn rebuild() -> Result<TranscriptIndex> {
+  let _lock = lock()?;
+  let mut index = TranscriptIndex::default();
+  for record in store::list().into_iter().filter(|r| r.complete) {
+    index.add(&record);
+  }
+  save(&index)?;
+  Ok(index)
+}
+
+/// Load the index, rebuilding it when missing or out of step with the store's
+/// complete records: a video added, removed or re-transcribed (a different
+/// `updated_at`) since it was indexed.
+pub fn load_or_rebuild() -> Result<TranscriptIndex> {
+  let stored = store::completed();
+  match load() {
+    Some(index)
+      if index.videos.len() == stored.len() && index.videos.iter().all(|(id, at)| stored.get(id) == Some(at)) =>
+    {
+      Ok(index)
+    }
+    _ => rebuild(),
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::store::StoredUtterance;
+
+  fn record(video_id: &str, lines: &[&str]) -> TranscriptRecord {
+    TranscriptRecord {
+      video_id: video_id.to_string(),
+      url: format!("https://youtube.com/watch?v={video_id}"),
+      offset_secs: 0,
+      complete: true,
+      updated_at: String::new(),
+      utterances: lines
+        .iter()
+        .enumerate()
+        .map(|(i, text)| StoredUtterance {
+          start: i as i64 * 500,
+          stop: i as i64 * 500 + 400,
+          text: (*text).to_string(),
+        })
+        .collect(),
+    }
+  }
+
+  fn terms(query: &str) -> Vec<String> {
+    textrank::words(query)
+  }
+
+  #[test]
+  fn candidates_need_every_term() {
+    let mut index = TranscriptIndex::default();
+    index.add(&record("a", &["The borrow checker is strict.", "Lifetimes help."]));
+    index.add(&record("b", &["Borrow a brush.", "Check the paper."]));
+    let found: Vec<&str> = index.candidates(&terms("borrow checker")).into_iter().map(|(id, _)| id).collect();
+    assert_eq!(found, vec!["a"]);
+    let found: Vec<&str> = index.candidates(&terms("Borrow")).into_iter().map(|(id, _)| id).collect();
+    assert_eq!(found, vec!["a", "b"]);
+    assert!(index.candidates(&terms("watercolor")).is_empty());
+  }
+
+  #[test]
+  fn re_adding_replaces_postings() {
+    let mut index = TranscriptIndex::default();
+    index.add(&record("a", &["old words"]));
+    index.add(&record("a", &["new words"]));
+    assert_eq!(index.len(), 1);
+    assert!(index.candidates(&terms("old")).is_empty());
+    assert_eq!(index.candidates(&terms("new")).len(), 1);
+    index.remove("a");
+    assert!(index.is_empty());
+    assert!(index.postings.is_empty());
+  }
+
+  #[test]
+  fn phrases_match_across_utterances() {
+    let rec = record("a", &["we fight the borrow", "checker every day", "borrow checker again", "checker borrow"]);
+    let mut index = TranscriptIndex::default();
+    index.add(&rec);
+    let (_, positions) = index.candidates(&terms("borrow checker"))[0];
+    assert_eq!(find_phrase(&rec, &terms("borrow checker"), positions), vec![0, 2]);
+  }
+
+  #[test]
+  fn snippets_include_neighbours() {
+    let rec = record("a", &["one", "two  three", "four", "five"]);
+    assert_eq!(snippet(&rec, 0), "one two three");
+    assert_eq!(snippet(&rec, 2), "two three four five");
+    let long = record("b", &[&"word ".repeat(100)]);
+    assert!(snippet(&long, 0).ends_with('…'));
+    assert_eq!(snippet(&long, 0).chars().count(), MAX_SNIPPET_CHARS);
+  }
+}
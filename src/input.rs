@@ -37,7 +37,9 @@ pub async fn handle_key_event(app: &mut App, key: event::KeyEvent) -> Result<()>
     if app.player.is_playing() {
       app.player.stop().await.context("Failed to stop playback")?;
       app.cancel_transcription();
+      app.transcript_resumable = false;
       app.utterances.clear();
+      app.paragraph_cache = None;
       app.clear_frame_state();
       app.gfx.last_sent = None;
       app.gfx.resized_thumb = None;
@@ -80,6 +82,34 @@ pub async fn handle_key_event(app: &mut App, key: event::KeyEvent) -> Result<()>
     return Ok(());
   }
 
+  // Ctrl+B / Ctrl+E / Ctrl+X — mark clip in / mark clip out / export the clip
+  if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('b') {
+    if app.player.is_playing() {
+      app.clip_mark_in();
+    }
+    return Ok(());
+  }
+  if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('e') {
+    if app.player.is_playing() {
+      app.clip_mark_out();
+    }
+    return Ok(());
+  }
+  if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('x') {
+    if app.player.is_playing() {
+      app.trigger_clip_export();
+    }
+    return Ok(());
+  }
+
+  // Ctrl+P — switch the transcript pane between timed lines and paragraphs
+  if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('p') {
+    if app.transcript_visible && !app.utterances.is_empty() {
+      app.transcript_paragraphs = !app.transcript_paragraphs;
+    }
+    return Ok(());
+  }
+
   // Ctrl+W — toggle wiki pane (only when playing)
   if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('w') {
     if app.player.is_playing() {
@@ -179,13 +209,13 @@ fn handle_input_key(app: &mut App, key: event::KeyEvent) {
         app.input.clear();
         app.cursor_position = 0;
         app.input_scroll = 0;
-      } else if !app.search_results.is_empty() {
+      } else if app.result_count() > 0 {
         app.mode = AppMode::Results;
       } else {
         app.should_quit = true;
       }
     }
-    KeyCode::Down if !app.search_results.is_empty() => {
+    KeyCode::Down if app.result_count() > 0 => {
       app.mode = AppMode::Results;
     }
     _ => {}
@@ -0,0 +1,211 @@
+//! Keyphrase extraction with RAKE weighted by TF-IDF.
+//!
+//! Candidate phrases are runs of up to three content words between stopwords
+//! and punctuation (RAKE). Each word scores degree / frequency over all
+//! candidates, and a phrase scores the sum of its words times how often the
+//! phrase is mentioned. For per-topic keywords the score is further weighted by
+//! the words' IDF across topics, so terms that run through the whole video
+//! don't crowd out what makes each topic distinct. Stopwords are shared with
+//! `textrank`.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::textrank;
+
+/// Longest candidate phrase, in words; longer runs are split into chunks.
+const MAX_PHRASE_WORDS: usize = 3;
+
+/// A ranked keyphrase with the time it is first mentioned.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Keyword {
+  pub phrase: String,
+  /// Relative score in 0–1 (the top keyword of the set scores 1).
+  pub score: f64,
+  pub first_at_secs: f64,
+}
+
+/// A candidate phrase occurrence.
+struct Candidate {
+  words: Vec<String>,
+  at_secs: f64,
+}
+
+/// Whether a word can be part of a keyphrase.
+fn is_content_word(word: &str) -> bool {
+  word.chars().count() > 1 && !textrank::is_stopword(word) && !word.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Extract RAKE candidate phrases from one sentence.
+fn candidates(at_secs: f64, text: &str) -> Vec<Candidate> {
+  let mut out = Vec::new();
+  // Punctuation ends a phrase just like a stopword does.
+  for clause in text.split([',', '.', '!', '?', ';', ':', '—', '–', '(', ')', '"', '…']) {
+    let mut run: Vec<String> = Vec::new();
+    for word in textrank::words(clause).into_iter().chain(std::iter::once(String::new())) {
+      if is_content_word(&word) {
+        run.push(word);
+        continue;
+      }
+      for chunk in run.chunks(MAX_PHRASE_WORDS) {
+        out.push(Candidate { words: chunk.to_vec(), at_secs });
+      }
+      run.clear();
+    }
+  }
+  out
+}
+
+/// Smoothed inverse document frequency of every word across `docs`.
+#[allow(clippy::cast_precision_loss)]
+fn idf(docs: &[Vec<Candidate>]) -> HashMap<String, f64> {
+  let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+  for doc in docs {
+    let mut seen: Vec<&str> = doc.iter().flat_map(|c| c.words.iter().map(String::as_str)).collect();
+    seen.sort_unstable();
+    seen.dedup();
+    for word in seen {
+      *doc_freq.entry(word).or_default() += 1;
+    }
+  }
+  let n = docs.len() as f64;
+  doc_freq.into_iter().map(|(w, df)| (w.to_string(), ((1.0 + n) / (1.0 + df as f64)).ln() + 1.0)).collect()
+}
+
+/// Rank the candidates of one document, optionally weighting by `idf`.
+#[allow(clippy::cast_precision_loss)]
+fn rank(cands: &[Candidate], idf: Option<&HashMap<String, f64>>, limit: usize) -> Vec<Keyword> {
+  // RAKE word scores: degree (co-occurring words, including itself) over frequency.
+  let mut freq: HashMap<&str, f64> = HashMap::new();
+  let mut degree: HashMap<&str, f64> = HashMap::new();
+  for c in cands {
+    for w in &c.words {
+      *freq.entry(w).or_default() += 1.0;
+      *degree.entry(w).or_default() += c.words.len() as f64;
+    }
+  }
+
+  // Distinct phrases.
+  let mut phrases: HashMap<String, &[String]> = HashMap::new();
+  for c in cands {
+    phrases.entry(c.words.join(" ")).or_insert(&c.words);
+  }
+
+  let mut scored: Vec<(String, f64, f64)> = phrases
+    .into_iter()
+    .map(|(phrase, words)| {
+      // Mentions inside longer runs count too, so "masking fluid" isn't
+      // outscored by a one-off "masking fluid keeps".
+      let mentions: Vec<&Candidate> =
+        cands.iter().filter(|c| c.words.windows(words.len()).any(|w| w == words)).collect();
+      let first_at = mentions.iter().map(|c| c.at_secs).fold(f64::INFINITY, f64::min);
+      let rake: f64 = words.iter().map(|w| degree[w.as_str()] / freq[w.as_str()]).sum();
+      let weight = idf
+        .map_or(1.0, |idf| words.iter().map(|w| idf.get(w).copied().unwrap_or(1.0)).sum::<f64>() / words.len() as f64);
+      (phrase, rake * mentions.len() as f64 * weight, first_at)
+    })
+    .collect();
+  scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+  // Skip phrases already covered by a higher-ranked one ("paper" after "watercolor paper").
+  let mut picked: Vec<(String, f64, f64)> = Vec::new();
+  for cand in scored {
+    if picked.len() >= limit {
+      break;
+    }
+    let covered = picked.iter().any(|(p, _, _)| {
+      let words: Vec<&str> = p.split(' ').collect();
+      cand.0.split(' ').all(|w| words.contains(&w))
+    });
+    if !covered {
+      picked.push(cand);
+    }
+  }
+
+  let top = picked.first().map_or(1.0, |k| k.1).max(f64::MIN_POSITIVE);
+  picked
+    .into_iter()
+    .map(|(phrase, score, first_at_secs)| Keyword {
+      phrase,
+      score: (score / top * 1000.0).round() / 1000.0,
+      first_at_secs,
+    })
+    .collect()
+}
+
+/// Top keyphrases for a whole video from its `(at_secs, text)` sentences.
+pub fn video_keywords(sentences: &[(f64, &str)], limit: usize) -> Vec<Keyword> {
+  let cands: Vec<Candidate> = sentences.iter().flat_map(|(at, text)| candidates(*at, text)).collect();
+  rank(&cands, None, limit)
+}
+
+/// Top keyphrases for each topic, weighted by how distinctive their words are across topics.
+pub fn topic_keywords(topics: &[Vec<(f64, &str)>], limit: usize) -> Vec<Vec<Keyword>> {
+  let docs: Vec<Vec<Candidate>> =
+    topics.iter().map(|t| t.iter().flat_map(|(at, text)| candidates(*at, text)).collect()).collect();
+  let idf = idf(&docs);
+  docs.iter().map(|cands| rank(cands, Some(&idf), limit)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn candidates_split_at_stopwords_and_punctuation() {
+    let phrases: Vec<String> =
+      candidates(0.0, "The watercolor paper, and the masking fluid.").into_iter().map(|c| c.words.join(" ")).collect();
+    assert_eq!(phrases, vec!["watercolor paper", "masking fluid"]);
+  }
+
+  #[test]
+  fn candidates_chunk_long_runs() {
+    let cands = candidates(0.0, "cold pressed cotton rag watercolor paper");
+    assert!(cands.iter().all(|c| c.words.len() <= MAX_PHRASE_WORDS));
+    assert_eq!(cands.len(), 2);
+  }
+
+  #[test]
+  fn candidates_skip_numbers() {
+    let cands = candidates(0.0, "step 2 brushes");
+    let phrases: Vec<String> = cands.into_iter().map(|c| c.words.join(" ")).collect();
+    assert_eq!(phrases, vec!["step", "brushes"]);
+  }
+
+  #[test]
+  fn video_keywords_rank_recurring_phrase_first() {
+    let sentences = [
+      (1.0, "Today we use masking fluid on watercolor paper."),
+      (5.0, "Masking fluid is great for highlights."),
+      (9.0, "Remove the masking fluid once the paint is dry."),
+    ];
+    let keywords = video_keywords(&sentences, 3);
+    assert_eq!(keywords[0].phrase, "masking fluid");
+    assert!((keywords[0].score - 1.0).abs() < f64::EPSILON);
+    assert!((keywords[0].first_at_secs - 1.0).abs() < f64::EPSILON);
+    assert!(keywords.iter().all(|k| k.phrase != "masking" && k.phrase != "fluid"));
+  }
+
+  #[test]
+  fn topic_keywords_prefer_distinctive_terms() {
+    let topics = vec![
+      vec![(0.0, "Watercolor glazing needs patience."), (3.0, "Glazing with watercolor builds depth.")],
+      vec![(60.0, "Watercolor splatter adds texture."), (63.0, "Splatter with a toothbrush.")],
+    ];
+    let per_topic = topic_keywords(&topics, 3);
+    assert_eq!(per_topic.len(), 2);
+    let mentions = |i: usize, word: &str| per_topic[i].iter().any(|k| k.phrase.contains(word));
+    assert!(mentions(0, "glazing") && !mentions(0, "splatter"));
+    assert!(mentions(1, "splatter") && !mentions(1, "glazing"));
+  }
+
+  #[test]
+  fn empty_input() {
+    assert!(video_keywords(&[], 5).is_empty());
+    assert!(topic_keywords(&[], 5).is_empty());
+  }
+}
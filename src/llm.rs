@@ -0,0 +1,422 @@
+//! Optional LLM summarization over an OpenAI-compatible chat endpoint.
+//!
+//! Opt-in via `yp summarize --llm`. The reduced `SummaryOutput` (topics,
+//! keyphrases, kept utterances) is rendered as plain text and sent to a chat
+//! completions endpoint — typically a local llama.cpp server, Ollama, or vLLM.
+//! Transcripts that don't fit one request are summarized map-reduce style:
+//! each chunk of utterances is condensed to notes first, notes that are still
+//! too long are condensed again in further levels, and then the notes and the
+//! topic list go into the final request. The model answers with JSON holding
+//! an abstract, bullet takeaways, and one title per topic.
+//!
+//! Settings come from the `[llm]` table in `prefs.toml`, overridable with
+//! `--llm-endpoint` / `--llm-model`.
+
+use anyhow::{Context, Result, anyhow};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, info};
+
+use crate::summarize::{SummaryOutput, timestamp};
+
+/// Default prompt for the final request. Placeholders: `{title}`, `{topic_count}`, `{content}`.
+const DEFAULT_PROMPT: &str = "\
+You are summarizing the YouTube video \"{title}\" from a pre-filtered transcript.
+
+{content}
+
+Reply with only a JSON object, no prose, in this shape:
+{\"abstract\": \"3-5 sentence overview\", \"takeaways\": [\"short bullet\", ...], \
+\"topic_titles\": [\"title for topic 1\", ...]}
+Give exactly {topic_count} topic titles, in topic order, each under 8 words.";
+
+/// Prompt for the map step over one chunk of a long transcript.
+const CHUNK_PROMPT: &str = "\
+Condense this part ({part} of {parts}) of a YouTube video transcript into 3-6 \
+plain-text sentences of notes. Keep names, numbers and concrete steps.
+
+{content}";
+
+/// Prompt for condensing notes again when they still don't fit one request.
+const NOTES_PROMPT: &str = "\
+Condense these notes on a YouTube video (group {part} of {parts}) into 3-6 \
+plain-text sentences. Keep names, numbers and concrete steps.
+
+{content}";
+
+/// Connection and prompting settings for the LLM backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LlmConfig {
+  /// Base URL of the OpenAI-compatible API (`/chat/completions` is appended).
+  pub endpoint: String,
+  pub model: String,
+  /// Sent as a bearer token when set (most local servers don't need one).
+  pub api_key: Option<String>,
+  pub temperature: f64,
+  /// Transcript characters per request before map-reduce chunking kicks in.
+  pub max_chunk_chars: usize,
+  pub timeout_secs: u64,
+  /// Replacement for the built-in final prompt (same placeholders).
+  pub prompt: Option<String>,
+}
+
+impl Default for LlmConfig {
+  fn default() -> Self {
+    Self {
+      endpoint: "http://127.0.0.1:8080/v1".to_string(),
+      model: "local".to_string(),
+      api_key: None,
+      temperature: 0.2,
+      max_chunk_chars: 12_000,
+      timeout_secs: 300,
+      prompt: None,
+    }
+  }
+}
+
+impl LlmConfig {
+  /// Load the `[llm]` table from the user config, or defaults (an error if the config is broken).
+  pub fn load() -> Result<Self> {
+    Ok(crate::config::Config::read()?.llm.unwrap_or_default())
+  }
+}
+
+/// A generated title for one topic segment.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopicTitle {
+  pub start_secs: f64,
+  pub title: String,
+}
+
+/// LLM-generated summary attached to `SummaryOutput.llm`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LlmSummary {
+  pub model: String,
+  #[serde(rename = "abstract")]
+  pub abstract_text: String,
+  pub takeaways: Vec<String>,
+  pub topic_titles: Vec<TopicTitle>,
+  /// Number of chat requests made (1 unless the transcript was chunked).
+  pub requests: usize,
+}
+
+/// The JSON shape the model is asked to reply with.
+#[derive(Debug, Deserialize)]
+struct ModelReply {
+  #[serde(default, rename = "abstract")]
+  abstract_text: String,
+  #[serde(default)]
+  takeaways: Vec<String>,
+  #[serde(default)]
+  topic_titles: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+  choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+  message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+  content: String,
+}
+
+/// Render the topic list and keyphrases that frame every final request.
+fn render_outline(output: &SummaryOutput) -> String {
+  let mut text = String::new();
+  let keywords: Vec<&str> = output.summary.keywords.iter().map(|k| k.phrase.as_str()).collect();
+  if !keywords.is_empty() {
+    text.push_str(&format!("Keyphrases: {}\n\n", keywords.join(", ")));
+  }
+  text.push_str("Topics:\n");
+  for (i, topic) in output.summary.topics.iter().enumerate() {
+    text.push_str(&format!("{}. [{}] {}\n", i + 1, timestamp(topic.start_secs), topic.summary));
+  }
+  text
+}
+
+/// Split the kept utterances into `[m:ss] text` chunks of at most `max_chars` each.
+fn transcript_chunks(output: &SummaryOutput, max_chars: usize) -> Vec<String> {
+  pack(output.utterances.iter().map(|u| format!("[{}] {}\n", timestamp(u.start), u.text.trim())), max_chars)
+}
+
+/// Join pieces into chunks of at most `max_chars` (a single longer piece gets its own chunk).
+fn pack(pieces: impl IntoIterator<Item = String>, max_chars: usize) -> Vec<String> {
+  let mut chunks = Vec::new();
+  let mut current = String::new();
+  for piece in pieces {
+    if !current.is_empty() && current.len() + piece.len() > max_chars.max(1) {
+      chunks.push(std::mem::take(&mut current));
+    }
+    current.push_str(&piece);
+  }
+  if !current.is_empty() {
+    chunks.push(current);
+  }
+  chunks
+}
+
+/// Pull the first JSON object out of a model reply (tolerates code fences and chatter).
+fn extract_json(reply: &str) -> Option<&str> {
+  let start = reply.find('{')?;
+  let end = reply.rfind('}')?;
+  (end > start).then(|| &reply[start..=end])
+}
+
+/// A chat client bound to one endpoint.
+struct ChatClient<'a> {
+  client: Client,
+  cfg: &'a LlmConfig,
+  requests: usize,
+}
+
+impl ChatClient<'_> {
+  /// Send one user message and return the assistant's reply text.
+  async fn complete(&mut self, prompt: &str) -> Result<String> {
+    let url = format!("{}/chat/completions", self.cfg.endpoint.trim_end_matches('/'));
+    let body = serde_json::json!({
+      "model": self.cfg.model,
+      "temperature": self.cfg.temperature,
+      "messages": [
+        { "role": "system", "content": "You write concise, factual summaries of video transcripts." },
+        { "role": "user", "content": prompt },
+      ],
+    });
+    debug!(url = %url, chars = prompt.len(), "llm: chat request");
+    let mut request = self.client.post(&url).json(&body);
+    if let Some(key) = &self.cfg.api_key {
+      request = request.bearer_auth(key);
+    }
+    self.requests += 1;
+    let response = request.send().await.with_context(|| format!("Failed to reach LLM endpoint {url}"))?;
+    let status = response.status();
+    if !status.is_success() {
+      let text = response.text().await.unwrap_or_default();
+      return Err(anyhow!("LLM endpoint returned {status}: {}", text.trim()));
+    }
+    let chat: ChatResponse = response.json().await.context("Failed to parse LLM chat response")?;
+    chat.choices.into_iter().next().map(|c| c.message.content).ok_or_else(|| anyhow!("LLM response had no choices"))
+  }
+
+  /// Condense each of `parts` with one request: transcript chunks at level 1,
+  /// groups of earlier notes above that. Returns one `Part n: …` note per part.
+  async fn condense(&mut self, parts: &[String], level: usize) -> Result<Vec<String>> {
+    let prompt = if level == 1 { CHUNK_PROMPT } else { NOTES_PROMPT };
+    let mut notes = Vec::with_capacity(parts.len());
+    for (i, part) in parts.iter().enumerate() {
+      if level == 1 {
+        eprintln!("LLM: condensing part {}/{}...", i + 1, parts.len());
+      } else {
+        eprintln!("LLM: condensing notes (level {level}), group {}/{}...", i + 1, parts.len());
+      }
+      let prompt = prompt
+        .replace("{part}", &(i + 1).to_string())
+        .replace("{parts}", &parts.len().to_string())
+        .replace("{content}", part);
+      let reply = self.complete(&prompt).await.with_context(|| format!("LLM failed on part {}", i + 1))?;
+      notes.push(format!("Part {}: {}\n\n", i + 1, reply.trim()));
+    }
+    Ok(notes)
+  }
+}
+
+/// Generate an abstract, takeaways and topic titles for a reduced summary.
+pub async fn summarize(output: &SummaryOutput, cfg: &LlmConfig) -> Result<LlmSummary> {
+  let client =
+    Client::builder().timeout(Duration::from_secs(cfg.timeout_secs)).build().context("Failed to build HTTP client")?;
+  let mut chat = ChatClient { client, cfg, requests: 0 };
+
+  // Map: condense each chunk when the transcript is too long for one request.
+  let chunks = transcript_chunks(output, cfg.max_chunk_chars);
+  let transcript = if chunks.len() <= 1 {
+    chunks.into_iter().next().unwrap_or_default()
+  } else {
+    info!(chunks = chunks.len(), "llm: map-reduce over transcript chunks");
+    let mut notes = chat.condense(&chunks, 1).await?;
+    // Reduce in levels until the notes fit one request, as long as each level shrinks them.
+    let mut level = 1;
+    while notes.concat().len() > cfg.max_chunk_chars {
+      let groups = pack(notes.iter().cloned(), cfg.max_chunk_chars);
+      if groups.len() <= 1 {
+        break;
+      }
+      level += 1;
+      info!(level, groups = groups.len(), "llm: notes too long, condensing again");
+      let condensed = chat.condense(&groups, level).await?;
+      if condensed.concat().len() >= notes.concat().len() {
+        break;
+      }
+      notes = condensed;
+    }
+    notes.concat()
+  };
+
+  // Reduce: one final request over the outline plus transcript (or notes).
+  eprintln!("LLM: generating summary with {}...", cfg.model);
+  let content = format!("{}\nTranscript:\n{transcript}", render_outline(output));
+  let topic_count = output.summary.topics.len();
+  let prompt = cfg
+    .prompt
+    .as_deref()
+    .unwrap_or(DEFAULT_PROMPT)
+    .replace("{title}", &output.video.title)
+    .replace("{topic_count}", &topic_count.to_string())
+    .replace("{content}", &content);
+  let reply = chat.complete(&prompt).await?;
+  let json = extract_json(&reply).ok_or_else(|| anyhow!("LLM reply contained no JSON object"))?;
+  let parsed: ModelReply = serde_json::from_str(json).context("Failed to parse LLM summary JSON")?;
+
+  let topic_titles = output
+    .summary
+    .topics
+    .iter()
+    .zip(parsed.topic_titles)
+    .map(|(topic, title)| TopicTitle { start_secs: topic.start_secs, title: title.trim().to_string() })
+    .collect();
+
+  Ok(LlmSummary {
+    model: cfg.model.clone(),
+    abstract_text: parsed.abstract_text.trim().to_string(),
+    takeaways: parsed.takeaways.into_iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect(),
+    topic_titles,
+    requests: chat.requests,
+  })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::summarize::{self, ClassifierConfig};
+  use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+  fn sample_output() -> SummaryOutput {
+    let utterances: Vec<(i64, i64, String)> = (0..8)
+      .map(|i| (i * 1000, i * 1000 + 900, format!("Sentence number {i} explains watercolor glazing in detail.")))
+      .collect();
+    let classified = summarize::classify(&utterances, &ClassifierConfig::default());
+    let video = SummaryOutput::fixture("Glazing 101", 0.0).video;
+    summarize::reduce(&video, &classified, &ClassifierConfig::default())
+  }
+
+  /// Serve canned chat completions on a local port; returns the base URL and
+  /// a handle yielding every request body received.
+  async fn stand_in_server(reply: &'static str) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handle = tokio::spawn(async move {
+      let mut bodies = Vec::new();
+      while let Ok(Ok((mut socket, _))) = tokio::time::timeout(Duration::from_millis(500), listener.accept()).await {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let body = loop {
+          let n = socket.read(&mut chunk).await.unwrap();
+          buf.extend_from_slice(&chunk[..n]);
+          let text = String::from_utf8_lossy(&buf).to_string();
+          if let Some(split) = text.find("\r\n\r\n") {
+            let len = text[..split]
+              .lines()
+              .find_map(|l| {
+                l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap())
+              })
+              .unwrap_or(0);
+            if buf.len() >= split + 4 + len || n == 0 {
+              break text[split + 4..].to_string();
+            }
+          }
+        };
+        bodies.push(body);
+        let payload = serde_json::json!({ "choices": [{ "message": { "role": "assistant", "content": reply } }] });
+        let payload = payload.to_string();
+        let response = format!(
+          "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+          payload.len()
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+      }
+      bodies
+    });
+    (format!("http://{addr}/v1"), handle)
+  }
+
+  #[test]
+  fn extract_json_strips_fences() {
+    assert_eq!(extract_json("```json\n{\"a\": 1}\n```"), Some("{\"a\": 1}"));
+    assert_eq!(extract_json("no json here"), None);
+  }
+
+  #[test]
+  fn transcript_chunks_respect_limit() {
+    let output = sample_output();
+    let chunks = transcript_chunks(&output, 150);
+    assert!(chunks.len() > 1);
+    assert!(chunks.iter().all(|c| c.len() <= 150));
+  }
+
+  #[tokio::test]
+  async fn summarize_against_stand_in_server() {
+    let reply = "```json\n{\"abstract\": \"A glazing lesson.\", \"takeaways\": [\"Thin layers\", \" \"], \
+                 \"topic_titles\": [\"Glazing basics\", \"extra\"]}\n```";
+    let (endpoint, server) = stand_in_server(reply).await;
+    let cfg = LlmConfig { endpoint, ..LlmConfig::default() };
+    let output = sample_output();
+    let summary = summarize(&output, &cfg).await.unwrap();
+    assert_eq!(summary.abstract_text, "A glazing lesson.");
+    assert_eq!(summary.takeaways, vec!["Thin layers".to_string()]);
+    assert_eq!(summary.topic_titles.len(), output.summary.topics.len().min(2));
+    assert_eq!(summary.requests, 1);
+    let bodies = server.await.unwrap();
+    assert!(bodies[0].contains("Glazing 101"));
+  }
+
+  #[tokio::test]
+  async fn summarize_map_reduces_long_transcripts() {
+    let reply = "{\"abstract\": \"Notes.\", \"takeaways\": [], \"topic_titles\": []}";
+    let (endpoint, server) = stand_in_server(reply).await;
+    let cfg = LlmConfig { endpoint, max_chunk_chars: 300, ..LlmConfig::default() };
+    let output = sample_output();
+    let expected_chunks = transcript_chunks(&output, 300).len();
+    assert!(expected_chunks > 1);
+    let summary = summarize(&output, &cfg).await.unwrap();
+    assert_eq!(summary.requests, expected_chunks + 1);
+    let bodies = server.await.unwrap();
+    assert_eq!(bodies.len(), expected_chunks + 1);
+    assert!(bodies.last().unwrap().contains("Part 1:"));
+  }
+
+  #[tokio::test]
+  async fn summarize_reduces_notes_in_levels() {
+    // Every reply is longer than a chunk may be, so two notes never share a group.
+    let reply = "{\"abstract\": \"Notes on glazing layers and washes, in some detail.\", \"takeaways\": [], \"topic_titles\": []}";
+    let (endpoint, server) = stand_in_server(reply).await;
+    let cfg = LlmConfig { endpoint, max_chunk_chars: 100, ..LlmConfig::default() };
+    let output = sample_output();
+    let chunks = transcript_chunks(&output, 100).len();
+    assert!(chunks > 2);
+    let summary = summarize(&output, &cfg).await.unwrap();
+    let bodies = server.await.unwrap();
+    assert_eq!(bodies.len(), summary.requests);
+    assert!(bodies[chunks].contains("group 1 of"));
+  }
+
+  #[tokio::test]
+  async fn summarize_reports_unreachable_endpoint() {
+    // A port that was just free: nothing listens there any more.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let cfg = LlmConfig { endpoint: format!("http://{addr}/v1"), timeout_secs: 2, ..LlmConfig::default() };
+    let err = summarize(&sample_output(), &cfg).await.unwrap_err();
+    assert!(format!("{err:#}").contains("Failed to reach LLM endpoint"));
+  }
+}
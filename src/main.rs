@@ -1,23 +1,42 @@
 mod app;
+mod boilerplate;
 mod cache;
+mod chapters;
 mod cli;
+mod clip;
+mod completions;
 mod config;
 mod constants;
+mod digest;
 mod display;
+mod error;
 mod graphics;
+mod index;
 mod input;
+mod keywords;
+mod llm;
+mod mcp;
+mod output;
+mod play;
 mod player;
+mod schema;
+mod segment;
+mod serve;
+mod store;
 mod summarize;
+mod textrank;
 mod theme;
 mod transcript;
+mod transcript_file;
 mod ui;
+mod watch;
 mod wiki;
 mod window;
 mod youtube;
 
 use anyhow::{Context, Result};
 use clap::{CommandFactory, Parser, Subcommand};
-use clap_complete::{Shell, generate};
+use clap_complete::Shell;
 use ratatui::{
   DefaultTerminal,
   crossterm::{
@@ -25,6 +44,7 @@ use ratatui::{
     execute,
   },
 };
+use std::process::ExitCode;
 use std::time::Duration;
 use tracing::info;
 
@@ -41,6 +61,15 @@ struct Args {
   #[arg(short, long, default_value = "auto")]
   display_mode: CliDisplayMode,
 
+  /// Run as a Model Context Protocol server on stdin/stdout instead of the TUI
+  #[arg(long)]
+  mcp: bool,
+
+  /// Output format: json, jsonl, tsv, csv or table for search, channel and info;
+  /// json, md (Markdown), txt or yaml for summarize and digest
+  #[arg(long, global = true, value_enum)]
+  format: Option<output::Format>,
+
   #[command(subcommand)]
   command: Option<Command>,
 }
@@ -60,6 +89,8 @@ enum Command {
     /// Max results (default: 20)
     #[arg(short, long, default_value_t = 20)]
     limit: usize,
+    #[command(flatten)]
+    list: output::ListOptions,
   },
 
   /// List videos from a `YouTube` channel (output as JSONL)
@@ -78,21 +109,31 @@ enum Command {
     /// Number of concurrent enrichment processes (default: 8)
     #[arg(short, long, default_value_t = 8)]
     jobs: usize,
+    #[command(flatten)]
+    list: output::ListOptions,
   },
 
   /// Fetch metadata for a specific video (output as JSON)
   Info {
     /// Video ID or `YouTube` URL
     video: String,
+    #[command(flatten)]
+    list: output::ListOptions,
   },
 
   /// Transcribe a video and return utterances (output as JSONL)
   Transcript {
-    /// Video ID or `YouTube` URL (reads from stdin if omitted)
+    /// Video ID or `YouTube` URL (reads one per line from stdin if omitted)
     video: Option<String>,
     /// Disable classification, output raw utterances
     #[arg(short, long)]
     raw: bool,
+    /// Ignore any stored checkpoint and transcribe from the start
+    #[arg(long)]
+    fresh: bool,
+    /// Videos transcribed at once when several are piped on stdin
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
   },
 
   /// Transcribe + classify + reduce a video to a summary (output as JSON)
@@ -106,17 +147,282 @@ enum Command {
     /// Output full unprocessed transcript
     #[arg(short, long)]
     raw: bool,
+    /// Ignore any stored checkpoint and transcribe from the start
+    #[arg(long)]
+    fresh: bool,
+    /// Override a classifier setting for this run, e.g. `topic_gap_secs=2` (repeatable)
+    #[arg(long, value_name = "KEY=VALUE")]
+    classifier: Vec<String>,
+    /// Add an LLM-generated abstract, takeaways and topic titles (OpenAI-compatible endpoint)
+    #[arg(long, conflicts_with = "raw")]
+    llm: bool,
+    /// Chat endpoint base URL for --llm (default: `[llm] endpoint` or http://127.0.0.1:8080/v1)
+    #[arg(long, value_name = "URL", requires = "llm")]
+    llm_endpoint: Option<String>,
+    /// Model name for --llm (default: `[llm] model`)
+    #[arg(long, value_name = "MODEL", requires = "llm")]
+    llm_model: Option<String>,
+    /// Trim the summary to roughly N tokens (drops least central utterances first)
+    #[arg(long, value_name = "N", conflicts_with = "raw")]
+    max_tokens: Option<usize>,
+    /// Summarize an existing transcript file (JSONL, JSON, SRT or VTT) instead of transcribing
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["video", "latest", "fresh"])]
+    from_transcript: Option<std::path::PathBuf>,
+    /// Video metadata for --from-transcript (`yp info` or yt-dlp JSON)
+    #[arg(long, value_name = "FILE", requires = "from_transcript")]
+    info: Option<std::path::PathBuf>,
+    /// Document format, from the global `--format`
+    #[arg(skip)]
+    format: summarize::SummaryFormat,
+    /// Include the filtered transcript in md/txt output
+    #[arg(long)]
+    with_transcript: bool,
+    /// Keep the channel's intro/outro even if `yp boilerplate` learned it
+    #[arg(long)]
+    keep_boilerplate: bool,
+    /// Videos summarized at once when several are piped on stdin
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
+  },
+
+  /// Draft YouTube chapters (`0:00 Title`) from a video's topic segments (output as JSON)
+  Chapters {
+    /// Video ID or `YouTube` URL
+    #[arg(required_unless_present = "from_transcript")]
+    video: Option<String>,
+    /// Use an existing transcript file (JSONL, JSON, SRT or VTT) instead of transcribing
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["video", "fresh"])]
+    from_transcript: Option<std::path::PathBuf>,
+    /// Video metadata for --from-transcript (`yp info` or yt-dlp JSON)
+    #[arg(long, value_name = "FILE", requires = "from_transcript")]
+    info: Option<std::path::PathBuf>,
+    /// Print only the chapter lines, ready to paste into a description
+    #[arg(long)]
+    text: bool,
+    /// Override a classifier setting for this run, e.g. `topic_gap_secs=8` (repeatable)
+    #[arg(long, value_name = "KEY=VALUE")]
+    classifier: Vec<String>,
+    /// Keep the channel's intro/outro even if `yp boilerplate` learned it
+    #[arg(long)]
+    keep_boilerplate: bool,
+    /// Ignore any stored checkpoint and transcribe from the start
+    #[arg(long)]
+    fresh: bool,
+  },
+
+  /// Cut audio clips: a time range, or every key segment of the summary (output as JSONL)
+  Clip {
+    /// Video ID or `YouTube` URL
+    video: String,
+    /// Clip start: seconds, m:ss or h:mm:ss
+    #[arg(long, value_parser = clip::parse_time_arg, required_unless_present = "key_segments", requires = "to")]
+    from: Option<f64>,
+    /// Clip end: seconds, m:ss or h:mm:ss
+    #[arg(long, value_parser = clip::parse_time_arg, requires = "from")]
+    to: Option<f64>,
+    /// Cut every key segment of the summary into numbered files
+    #[arg(long, conflicts_with_all = ["from", "to"])]
+    key_segments: bool,
+    /// Output file, or directory with --key-segments (default: generated names in the current directory)
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<std::path::PathBuf>,
+    /// Audio format (file extension) for generated names
+    #[arg(long, default_value = "m4a")]
+    ext: String,
+    /// Override a classifier setting for --key-segments, e.g. `key_min_words=20` (repeatable)
+    #[arg(long, value_name = "KEY=VALUE", requires = "key_segments")]
+    classifier: Vec<String>,
+    /// Ignore any stored checkpoint and transcribe from the start (--key-segments)
+    #[arg(long, requires = "key_segments")]
+    fresh: bool,
+  },
+
+  /// Play videos in the terminal without the TUI: a status line, single-key controls, optional live transcript
+  Play {
+    /// Video ID or `YouTube` URL, a channel handle with --latest, or `-` to read IDs from stdin
+    video: Option<String>,
+    /// Play the latest N videos from a channel, newest first (`--latest=N`; default: 1)
+    #[arg(long, value_name = "N", num_args = 0..=1, require_equals = true, default_missing_value = "1")]
+    latest: Option<usize>,
+    /// Print the transcript to stdout as playback reaches each line
+    #[arg(short, long)]
+    transcript: bool,
+  },
+
+  /// Summarize a channel's latest videos into one digest: themes, timeline, one-liners (output as JSON)
+  Digest {
+    /// Channel handle or URL (defaults to the configured channel)
+    channel: Option<String>,
+    /// Cover the latest N videos
+    #[arg(short, long, default_value_t = 10)]
+    latest: usize,
+    /// Document format, from the global `--format`
+    #[arg(skip)]
+    format: summarize::SummaryFormat,
+    /// Override a classifier setting for this run, e.g. `topic_gap_secs=2` (repeatable)
+    #[arg(long, value_name = "KEY=VALUE")]
+    classifier: Vec<String>,
+    /// Keep the channel's intro/outro even if `yp boilerplate` learned it
+    #[arg(long)]
+    keep_boilerplate: bool,
+    /// Ignore stored transcripts and transcribe every video from the start
+    #[arg(long)]
+    fresh: bool,
   },
 
-  /// Output cached video IDs for shell completion (hidden)
-  #[command(name = "_complete-ids", hide = true)]
-  CompleteIds {
-    /// Fetch from default channel if cache is empty
+  /// Watch channels for new uploads, optionally transcribing or summarizing each (output as JSONL events)
+  Watch {
+    /// Channel handles or URLs (default: `[watch] channels`, else the configured channel)
+    channels: Vec<String>,
+    /// Time between polls, e.g. `90s`, `30m`, `2h` or `1h30m`
+    #[arg(short, long, default_value = "30m", value_parser = watch::parse_interval)]
+    interval: std::time::Duration,
+    /// Newest videos to check per channel and poll
+    #[arg(short, long, default_value_t = 5)]
+    limit: usize,
+    /// Transcribe each new upload (the transcript is stored for later commands)
+    #[arg(long)]
+    transcribe: bool,
+    /// Summarize each new upload
+    #[arg(long, conflicts_with = "transcribe")]
+    summarize: bool,
+    /// Shell command run for each new upload with its last event on stdin (default: `[watch] exec`)
+    #[arg(long, value_name = "COMMAND")]
+    exec: Option<String>,
+    /// Write `<video_id>.jsonl` transcripts or `<video_id>.json` summaries here instead of inlining them
+    #[arg(short, long, value_name = "DIR")]
+    out_dir: Option<std::path::PathBuf>,
+    /// Poll once and exit (e.g. from cron)
     #[arg(long)]
-    live: bool,
+    once: bool,
+  },
+
+  /// Search stored transcripts for a phrase (output as JSONL hits with `&t=` links)
+  Grep {
+    /// Words or phrase to find (case-insensitive, whole words)
+    query: String,
+    /// Only search videos from this channel handle or URL
+    channel: Option<String>,
+    /// Max hits
+    #[arg(short, long, default_value_t = 50)]
+    limit: usize,
+    /// Rebuild the search index from the transcript store first
+    #[arg(long)]
+    reindex: bool,
+  },
+
+  /// Transcribe many videos with bounded parallelism (output as JSONL or per-video files)
+  TranscribeBatch {
+    /// File with one video ID, URL, or JSONL object per line (default: stdin)
+    input: Option<std::path::PathBuf>,
+    /// Max videos in flight (whisper instances are further capped by available memory)
+    #[arg(short, long, default_value_t = 2)]
+    jobs: usize,
+    /// Write `<video_id>.jsonl` per video and `report.json` here instead of stdout
+    #[arg(short, long, value_name = "DIR")]
+    out_dir: Option<std::path::PathBuf>,
+    /// Output raw utterances without classification
+    #[arg(short, long)]
+    raw: bool,
+    /// Ignore stored checkpoints and start every video from scratch
+    #[arg(long)]
+    fresh: bool,
+  },
+
+  /// List interrupted transcription jobs (output as JSONL)
+  Jobs {
+    /// Discard the checkpoint for a video ID or URL
+    #[arg(long, value_name = "VIDEO")]
+    discard: Option<String>,
+    /// Discard all incomplete checkpoints
+    #[arg(long, conflicts_with = "discard")]
+    discard_all: bool,
+  },
+
+  /// Learn a channel's recurring intro/outro from stored transcripts (output as JSON)
+  Boilerplate {
+    /// Channel handle or URL (defaults to the configured channel)
+    channel: Option<String>,
+    /// Consider the latest N videos (only those with stored transcripts are used)
+    #[arg(short, long, default_value_t = 50)]
+    limit: usize,
+    /// Override a classifier setting for this run, e.g. `boilerplate_min_videos=5` (repeatable)
+    #[arg(long, value_name = "KEY=VALUE")]
+    classifier: Vec<String>,
+  },
+
+  /// Serve search, channel, info, transcript and summarize as a local HTTP API
+  Serve {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8484")]
+    bind: String,
+  },
+
+  /// Print the JSON Schema of a command's output (lists the commands without one)
+  Schema {
+    /// Command whose output to describe
+    #[arg(value_enum)]
+    command: Option<schema::SchemaCommand>,
+  },
+
+  /// Print completion values for the argument after `words` (hidden, called by the completion scripts)
+  #[command(name = "_complete", hide = true)]
+  Complete {
+    /// Format of the printed values
+    shell: Shell,
+    /// Words typed so far, without the binary name and the word being completed
+    #[arg(last = true)]
+    words: Vec<String>,
   },
 }
 
+impl Command {
+  /// Check the global `--format` against what the command prints and hand it to the command.
+  fn apply_format(&mut self, format: Option<output::Format>) -> Result<()> {
+    let Some(format) = format else { return Ok(()) };
+    match self {
+      Command::Search { list, .. } => list.format = Some(format.records("search")?),
+      Command::Channel { list, .. } => list.format = Some(format.records("channel")?),
+      Command::Info { list, .. } => list.format = Some(format.records("info")?),
+      Command::Summarize { raw, format: document, .. } => {
+        *document = format.document("summarize")?;
+        if *raw && *document != summarize::SummaryFormat::Json {
+          return Err(error::fail(error::ErrorKind::InvalidInput, "--raw prints JSON; drop --format or --raw"));
+        }
+      }
+      Command::Digest { format: document, .. } => *document = format.document("digest")?,
+      _ => {
+        return Err(error::fail(
+          error::ErrorKind::InvalidInput,
+          "--format applies to search, channel, info, summarize and digest",
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  /// Whether the command prints JSON or JSONL, so a failure also ends stdout with a JSON error line.
+  fn prints_json(&self) -> bool {
+    match self {
+      Command::Search { list, .. } | Command::Channel { list, .. } | Command::Info { list, .. } => {
+        list.format.is_none_or(|f| matches!(f, output::OutputFormat::Json | output::OutputFormat::Jsonl))
+      }
+      Command::Summarize { raw, format, .. } => *raw || *format == summarize::SummaryFormat::Json,
+      Command::Digest { format, .. } => *format == summarize::SummaryFormat::Json,
+      Command::Chapters { text, .. } => !*text,
+      Command::Transcript { .. }
+      | Command::Clip { .. }
+      | Command::Grep { .. }
+      | Command::TranscribeBatch { .. }
+      | Command::Jobs { .. }
+      | Command::Boilerplate { .. }
+      | Command::Watch { .. }
+      | Command::Schema { .. } => true,
+      Command::Completions { .. } | Command::Play { .. } | Command::Serve { .. } | Command::Complete { .. } => false,
+    }
+  }
+}
+
 // --- Helpers ---
 
 /// Parse the time position (in seconds) from an mpv status string.
@@ -145,7 +451,7 @@ pub fn parse_mpv_time_secs(status: &str) -> Option<f64> {
 // --- Main ---
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
   // --- Daily file logging ---
   let log_dir = directories::BaseDirs::new()
     .map_or_else(|| std::path::PathBuf::from("/tmp/yp/logs"), |d| d.data_dir().join("yp/logs"));
@@ -178,48 +484,189 @@ async fn main() -> Result<()> {
 
   let args = Args::parse();
 
-  // Handle non-TUI subcommands before entering the terminal.
-  if let Some(command) = args.command {
-    return match command {
-      Command::Completions { shell } => {
-        if shell == Shell::Zsh {
-          // Custom zsh completion with dynamic video ID support.
-          cli::generate_zsh_completions();
-        } else {
-          let mut cmd = Args::command();
-          let bin_name = cmd.get_name().to_string();
-          generate(shell, &mut cmd, bin_name, &mut std::io::stdout());
+  if args.mcp {
+    if args.command.is_some() {
+      Args::command()
+        .error(clap::error::ErrorKind::ArgumentConflict, "--mcp cannot be combined with a subcommand")
+        .exit();
+    }
+    return mcp::serve().await.map(|()| ExitCode::SUCCESS);
+  }
+
+  // Handle non-TUI subcommands before entering the terminal. Failures exit
+  // with the status of their kind, ending JSON output with a JSON error.
+  if let Some(mut command) = args.command {
+    if let Err(e) = command.apply_format(args.format) {
+      return Ok(error::report(&e, false));
+    }
+    let json_errors = command.prints_json();
+    let result: Result<()> = async move {
+      match command {
+        Command::Completions { shell } => {
+          print!("{}", completions::script(shell, Args::command()));
+          Ok(())
         }
-        Ok(())
-      }
-      Command::Search { query, limit } => cli::cmd_search(&query, limit).await,
-      Command::Channel { channel, limit, all, enrich, jobs } => {
-        let channel = channel.unwrap_or_else(|| constants::constants().pastel_sketchbook_channel.clone());
-        let count = if all { None } else { Some(limit) };
-        cli::cmd_channel(&channel, count, enrich, jobs).await
-      }
-      Command::Info { video } => cli::cmd_info(&video).await,
-      Command::Transcript { video, raw } => {
-        if let Some(video) = video {
-          cli::cmd_transcript(&video, raw).await
-        } else {
-          cli::cmd_transcript_stdin(raw).await
+        Command::Search { query, limit, list } => cli::cmd_search(&query, limit, &list).await,
+        Command::Channel { channel, limit, all, enrich, jobs, list } => {
+          let channel = channel.unwrap_or_else(|| constants::constants().pastel_sketchbook_channel.clone());
+          let count = if all { None } else { Some(limit) };
+          cli::cmd_channel(&channel, count, enrich, jobs, &list).await
         }
-      }
-      Command::Summarize { video, latest, raw } => {
-        if let Some(count) = latest {
-          // --latest: treat `video` as a channel handle, default to configured channel
-          let channel = video.unwrap_or_else(|| constants::constants().pastel_sketchbook_channel.clone());
-          cli::cmd_summarize_latest(&channel, count, raw).await
-        } else if let Some(video) = video {
-          cli::cmd_summarize(&video, raw).await
-        } else {
-          // No video arg and no --latest: read from stdin (pipe mode)
-          cli::cmd_summarize_stdin(raw).await
+        Command::Info { video, list } => cli::cmd_info(&video, &list).await,
+        Command::Transcript { video, raw, fresh, jobs } => {
+          if let Some(video) = video {
+            cli::cmd_transcript(&video, raw, fresh).await
+          } else {
+            cli::cmd_transcript_stdin(raw, fresh, jobs).await
+          }
         }
+        Command::Summarize {
+          video,
+          latest,
+          raw,
+          fresh,
+          classifier,
+          llm,
+          llm_endpoint,
+          llm_model,
+          max_tokens,
+          from_transcript,
+          info,
+          format,
+          with_transcript,
+          keep_boilerplate,
+          jobs,
+        } => {
+          let mut classifier_cfg = summarize::ClassifierConfig::load()?;
+          classifier_cfg.apply_overrides(&classifier)?;
+          let llm_cfg = llm
+            .then(|| {
+              let mut llm_cfg = llm::LlmConfig::load()?;
+              llm_cfg.endpoint = llm_endpoint.unwrap_or(llm_cfg.endpoint);
+              llm_cfg.model = llm_model.unwrap_or(llm_cfg.model);
+              anyhow::Ok(llm_cfg)
+            })
+            .transpose()?;
+          let opts = cli::SummarizeOptions {
+            raw,
+            fresh,
+            classifier: &classifier_cfg,
+            llm: llm_cfg.as_ref(),
+            max_tokens,
+            format,
+            with_transcript,
+            boilerplate: !keep_boilerplate,
+          };
+          if let Some(path) = from_transcript {
+            cli::cmd_summarize_file(&path, info.as_deref(), &opts).await
+          } else if let Some(count) = latest {
+            // --latest: treat `video` as a channel handle, default to configured channel
+            let channel = video.unwrap_or_else(|| constants::constants().pastel_sketchbook_channel.clone());
+            cli::cmd_summarize_latest(&channel, count, &opts, jobs).await
+          } else if let Some(video) = video {
+            cli::cmd_summarize(&video, &opts).await
+          } else {
+            // No video arg and no --latest: read from stdin (pipe mode)
+            cli::cmd_summarize_stdin(&opts, jobs).await
+          }
+        }
+        Command::Chapters { video, from_transcript, info, text, classifier, keep_boilerplate, fresh } => {
+          // Every topic is a chapter candidate, unless `max_topics` is overridden.
+          let mut classifier_cfg = summarize::ClassifierConfig::load()?;
+          classifier_cfg.max_topics = usize::MAX;
+          classifier_cfg.apply_overrides(&classifier)?;
+          let opts = cli::SummarizeOptions {
+            raw: false,
+            fresh,
+            classifier: &classifier_cfg,
+            llm: None,
+            max_tokens: None,
+            format: summarize::SummaryFormat::Json,
+            with_transcript: false,
+            boilerplate: !keep_boilerplate,
+          };
+          cli::cmd_chapters(video.as_deref(), from_transcript.as_deref(), info.as_deref(), text, &opts).await
+        }
+        Command::Clip { video, from, to, key_segments, output, ext, classifier, fresh } => {
+          let mut classifier_cfg = summarize::ClassifierConfig::load()?;
+          classifier_cfg.apply_overrides(&classifier)?;
+          let opts = cli::SummarizeOptions {
+            raw: false,
+            fresh,
+            classifier: &classifier_cfg,
+            llm: None,
+            max_tokens: None,
+            format: summarize::SummaryFormat::Json,
+            with_transcript: false,
+            boilerplate: true,
+          };
+          let range = match (from, to) {
+            (Some(from), Some(to)) if !key_segments => cli::ClipRange::Range { from, to },
+            _ => cli::ClipRange::KeySegments,
+          };
+          cli::cmd_clip(&video, range, output.as_deref(), &ext, &opts).await
+        }
+        Command::Digest { channel, latest, format, classifier, keep_boilerplate, fresh } => {
+          let mut classifier_cfg = summarize::ClassifierConfig::load()?;
+          classifier_cfg.apply_overrides(&classifier)?;
+          let opts = cli::SummarizeOptions {
+            raw: false,
+            fresh,
+            classifier: &classifier_cfg,
+            llm: None,
+            max_tokens: None,
+            format,
+            with_transcript: false,
+            boilerplate: !keep_boilerplate,
+          };
+          let channel = channel.unwrap_or_else(|| constants::constants().pastel_sketchbook_channel.clone());
+          cli::cmd_digest(&channel, latest, &opts).await
+        }
+        Command::Grep { query, channel, limit, reindex } => {
+          cli::cmd_grep(&query, channel.as_deref(), limit, reindex).await
+        }
+        Command::TranscribeBatch { input, jobs, out_dir, raw, fresh } => {
+          cli::cmd_transcribe_batch(input.as_deref(), jobs, out_dir.as_deref(), raw, fresh).await
+        }
+        Command::Jobs { discard, discard_all } => cli::cmd_jobs(discard.as_deref(), discard_all),
+        Command::Boilerplate { channel, limit, classifier } => {
+          let mut classifier_cfg = summarize::ClassifierConfig::load()?;
+          classifier_cfg.apply_overrides(&classifier)?;
+          let channel = channel.unwrap_or_else(|| constants::constants().pastel_sketchbook_channel.clone());
+          cli::cmd_boilerplate(&channel, limit, &classifier_cfg).await
+        }
+        Command::Play { video, latest, transcript } => cli::cmd_play(video.as_deref(), latest, transcript).await,
+        Command::Watch { channels, interval, limit, transcribe, summarize: summarize_new, exec, out_dir, once } => {
+          let config = watch::WatchConfig::load()?;
+          let channels = match (channels.is_empty(), config.channels.is_empty()) {
+            (false, _) => channels,
+            (true, false) => config.channels,
+            (true, true) => vec![constants::constants().pastel_sketchbook_channel.clone()],
+          };
+          let action = if transcribe {
+            Some(watch::WatchAction::Transcribe)
+          } else {
+            summarize_new.then_some(watch::WatchAction::Summarize)
+          };
+          let classifier = summarize::ClassifierConfig::load()?;
+          let opts = cli::WatchOptions {
+            interval,
+            limit,
+            action,
+            exec: exec.as_deref().or(config.exec.as_deref()),
+            out_dir: out_dir.as_deref(),
+            once,
+            classifier: &classifier,
+          };
+          cli::cmd_watch(&channels, &opts).await
+        }
+        Command::Serve { bind } => serve::serve(&bind).await,
+        Command::Schema { command } => cli::cmd_schema(command),
+        Command::Complete { shell, words } => cli::cmd_complete(shell, Args::command(), &words),
       }
-      Command::CompleteIds { live } => cli::cmd_complete_ids(live).await,
-    };
+    }
+    .await;
+    return Ok(result.map_or_else(|e| error::report(&e, json_errors), |()| ExitCode::SUCCESS));
   }
 
   let default_hook = std::panic::take_hook();
@@ -233,7 +680,7 @@ async fn main() -> Result<()> {
   let result = run(&mut terminal, args).await;
   execute!(std::io::stdout(), DisableMouseCapture)?;
   ratatui::restore();
-  result
+  result.map(|()| ExitCode::SUCCESS)
 }
 
 async fn run(terminal: &mut DefaultTerminal, args: Args) -> Result<()> {
@@ -358,4 +805,49 @@ mod tests {
   fn parse_mpv_time_garbage() {
     assert_eq!(parse_mpv_time_secs("Time: abc / def"), None);
   }
+
+  // --- output formats ---
+
+  fn command(args: &[&str]) -> Command {
+    let args = Args::try_parse_from(std::iter::once("yp").chain(args.iter().copied())).unwrap();
+    let mut command = args.command.unwrap();
+    command.apply_format(args.format).unwrap();
+    command
+  }
+
+  fn format_error(args: &[&str]) -> error::ErrorKind {
+    let args = Args::try_parse_from(std::iter::once("yp").chain(args.iter().copied())).unwrap();
+    error::kind_of(&args.command.unwrap().apply_format(args.format).unwrap_err())
+  }
+
+  #[test]
+  fn one_global_format_covers_records_and_documents() {
+    assert!(command(&["channel", "@a"]).prints_json());
+    assert!(command(&["channel", "@a", "--format", "jsonl"]).prints_json());
+    assert!(!command(&["channel", "@a", "--format", "csv"]).prints_json());
+    assert!(!command(&["--format", "table", "search", "glaze"]).prints_json());
+    assert!(command(&["summarize", "abc"]).prints_json());
+    assert!(!command(&["summarize", "abc", "--format", "md"]).prints_json());
+    assert!(!command(&["digest", "@a", "--format", "yaml"]).prints_json());
+    let Command::Info { list, .. } = command(&["info", "abc", "--format", "tsv"]) else { panic!() };
+    assert_eq!(list.format, Some(output::OutputFormat::Tsv));
+  }
+
+  #[test]
+  fn mismatched_formats_are_invalid_input() {
+    assert_eq!(format_error(&["channel", "@a", "--format", "md"]), error::ErrorKind::InvalidInput);
+    assert_eq!(format_error(&["summarize", "abc", "--format", "csv"]), error::ErrorKind::InvalidInput);
+    assert_eq!(format_error(&["summarize", "abc", "--raw", "--format", "md"]), error::ErrorKind::InvalidInput);
+    assert_eq!(format_error(&["transcript", "abc", "--format", "json"]), error::ErrorKind::InvalidInput);
+  }
+
+  #[test]
+  fn play_latest_takes_its_count_after_an_equals_sign() {
+    let Command::Play { video, latest, .. } = command(&["play", "--latest", "@ChrisH-v4e"]) else { panic!() };
+    assert_eq!((video.as_deref(), latest), (Some("@ChrisH-v4e"), Some(1)));
+    let Command::Play { video, latest, .. } = command(&["play", "@ChrisH-v4e", "--latest=3"]) else { panic!() };
+    assert_eq!((video.as_deref(), latest), (Some("@ChrisH-v4e"), Some(3)));
+    let Command::Play { latest, .. } = command(&["play", "abc123"]) else { panic!() };
+    assert_eq!(latest, None);
+  }
 }
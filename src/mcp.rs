@@ -0,0 +1,486 @@
+//! Model Context Protocol server: yp's commands as typed tools over stdio.
+//!
+//! `yp --mcp` speaks newline-delimited JSON-RPC 2.0 on stdin/stdout, so an
+//! MCP client (an editor, an agent) can search, list channels, fetch metadata,
+//! transcribe and summarize without parsing CLI output. Each tool declares an
+//! input schema and an output schema (from `schema`), and returns its result
+//! both as `structuredContent` and as JSON text.
+//!
+//! Tool calls run concurrently as their own tasks and share one whisper
+//! instance. Transcriptions report `notifications/progress` when the request
+//! carries a `progressToken`, and `notifications/cancelled` aborts the call
+//! (the transcript checkpoint is kept, so a retry resumes). Logs go to the log
+//! file and progress lines to stderr; stdout carries protocol messages only.
+
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+
+use crate::cache;
+use crate::cli;
+use crate::error;
+use crate::schema::{self, VideoEntry};
+use crate::summarize;
+use crate::transcript::WhisperCache;
+use crate::youtube;
+
+/// Protocol revision this server implements.
+const PROTOCOL_VERSION: &str = "2025-06-18";
+/// Revisions a client may ask for; anything else gets `PROTOCOL_VERSION`.
+const SUPPORTED_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// What the server loop should do with one incoming message.
+#[derive(Debug, PartialEq)]
+enum Action {
+  /// Send this response now.
+  Reply(Value),
+  /// Run a tool in the background and reply when it finishes.
+  CallTool { id: Value, name: String, arguments: Value, progress_token: Option<Value> },
+  /// Abort the running call with this request ID.
+  Cancel(Value),
+  /// Notification or response needing no reply.
+  Ignore,
+}
+
+fn response(id: &Value, result: Value) -> Value {
+  json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+  json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Tool definitions for `tools/list`.
+fn tools() -> Value {
+  let video = json!({ "type": "string", "description": "Video ID or YouTube URL" });
+  let fresh = json!({ "type": "boolean", "description": "Ignore any stored checkpoint and transcribe from the start" });
+  json!([
+    {
+      "name": "search",
+      "title": "Search YouTube",
+      "description": "Search YouTube for videos matching a query.",
+      "inputSchema": {
+        "type": "object",
+        "properties": {
+          "query": { "type": "string" },
+          "limit": { "type": "integer", "minimum": 1, "default": 20 },
+        },
+        "required": ["query"],
+      },
+      "outputSchema": {
+        "type": "object",
+        "properties": { "results": { "type": "array", "items": schema::video_entry() } },
+        "required": ["results"],
+      },
+    },
+    {
+      "name": "channel_videos",
+      "title": "List channel videos",
+      "description": "List the latest videos of a YouTube channel, newest first.",
+      "inputSchema": {
+        "type": "object",
+        "properties": {
+          "channel": { "type": "string", "description": "Channel handle (@name), URL or name; defaults to the configured channel" },
+          "limit": { "type": "integer", "minimum": 1, "default": 30 },
+        },
+      },
+      "outputSchema": {
+        "type": "object",
+        "properties": { "videos": { "type": "array", "items": schema::video_entry() } },
+        "required": ["videos"],
+      },
+    },
+    {
+      "name": "video_info",
+      "title": "Video metadata",
+      "description": "Fetch title, uploader, duration, upload date, views and tags of a video.",
+      "inputSchema": { "type": "object", "properties": { "video": video }, "required": ["video"] },
+      "outputSchema": schema::video_details(),
+    },
+    {
+      "name": "transcript",
+      "title": "Transcribe a video",
+      "description": "Transcribe a video with whisper (stored transcripts are reused). Utterances are classified unless raw is set. Long videos take minutes; pass a progressToken to follow along.",
+      "inputSchema": {
+        "type": "object",
+        "properties": {
+          "video": video,
+          "raw": { "type": "boolean", "description": "Skip classification", "default": false },
+          "fresh": fresh,
+        },
+        "required": ["video"],
+      },
+      "outputSchema": {
+        "type": "object",
+        "properties": {
+          "video_id": { "type": "string" },
+          "utterances": {
+            "type": "array",
+            "items": { "oneOf": [schema::classified_utterance(), schema::raw_utterance()] },
+          },
+        },
+        "required": ["video_id", "utterances"],
+      },
+    },
+    {
+      "name": "summarize",
+      "title": "Summarize a video",
+      "description": "Transcribe, classify and reduce a video to abstract, keywords, topics, key segments and Q&A pairs with timestamps.",
+      "inputSchema": {
+        "type": "object",
+        "properties": {
+          "video": video,
+          "llm": { "type": "boolean", "description": "Add an LLM abstract, takeaways and topic titles (configured endpoint)", "default": false },
+          "max_tokens": { "type": "integer", "minimum": 1, "description": "Trim the summary to roughly this many tokens" },
+          "keep_boilerplate": { "type": "boolean", "description": "Keep the channel's learned intro/outro", "default": false },
+          "fresh": fresh,
+        },
+        "required": ["video"],
+      },
+      "outputSchema": schema::summary_output(),
+    },
+  ])
+}
+
+/// Decide what to do with one parsed message. Tool calls are validated here;
+/// running them is left to the server loop.
+fn handle(msg: &Value) -> Action {
+  let Some(method) = msg.get("method").and_then(Value::as_str) else {
+    // Responses to requests we never send, or garbage.
+    return match msg.get("id") {
+      Some(id) if msg.get("result").is_none() && msg.get("error").is_none() => {
+        Action::Reply(error_response(id, INVALID_REQUEST, "Missing method"))
+      }
+      _ => Action::Ignore,
+    };
+  };
+  let params = msg.get("params").cloned().unwrap_or_else(|| json!({}));
+  let Some(id) = msg.get("id").cloned() else {
+    return match method {
+      "notifications/cancelled" => params.get("requestId").cloned().map_or(Action::Ignore, Action::Cancel),
+      _ => Action::Ignore,
+    };
+  };
+
+  match method {
+    "initialize" => {
+      let requested = params.get("protocolVersion").and_then(Value::as_str);
+      let version = requested.filter(|v| SUPPORTED_VERSIONS.contains(v)).unwrap_or(PROTOCOL_VERSION);
+      Action::Reply(response(
+        &id,
+        json!({
+          "protocolVersion": version,
+          "capabilities": { "tools": { "listChanged": false } },
+          "serverInfo": { "name": "yp", "title": "yp — YouTube search, transcripts and summaries", "version": env!("CARGO_PKG_VERSION") },
+          "instructions": "Use search or channel_videos to find videos, then summarize for a compact timestamped digest. transcript returns every utterance and is much larger.",
+        }),
+      ))
+    }
+    "ping" => Action::Reply(response(&id, json!({}))),
+    "tools/list" => Action::Reply(response(&id, json!({ "tools": tools() }))),
+    "tools/call" => {
+      let Some(name) = params.get("name").and_then(Value::as_str) else {
+        return Action::Reply(error_response(&id, INVALID_PARAMS, "Missing tool name"));
+      };
+      if !tools().as_array().is_some_and(|t| t.iter().any(|t| t["name"] == name)) {
+        return Action::Reply(error_response(&id, INVALID_PARAMS, &format!("Unknown tool: {name}")));
+      }
+      Action::CallTool {
+        id,
+        name: name.to_string(),
+        arguments: params.get("arguments").cloned().unwrap_or_else(|| json!({})),
+        progress_token: params.pointer("/_meta/progressToken").cloned(),
+      }
+    }
+    _ => Action::Reply(error_response(&id, METHOD_NOT_FOUND, &format!("Method not found: {method}"))),
+  }
+}
+
+/// A `tools/call` result: the value as structured content and JSON text, or
+/// the error as text led by its code (`not_found: ...`).
+fn tool_result(result: Result<Value>) -> Value {
+  match result {
+    Ok(value) => json!({
+      "content": [{ "type": "text", "text": serde_json::to_string_pretty(&value).unwrap_or_default() }],
+      "structuredContent": value,
+      "isError": false,
+    }),
+    Err(e) => json!({
+      "content": [{ "type": "text", "text": format!("{}: {e:#}", error::kind_of(&e).code()) }],
+      "isError": true,
+    }),
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Tools
+// ---------------------------------------------------------------------------
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Result<&'a str> {
+  args.get(key).and_then(Value::as_str).ok_or_else(|| anyhow!("Missing string argument: {key}"))
+}
+
+fn bool_arg(args: &Value, key: &str) -> bool {
+  args.get(key).and_then(Value::as_bool).unwrap_or(false)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn usize_arg(args: &Value, key: &str) -> Option<usize> {
+  args.get(key).and_then(Value::as_u64).map(|n| n as usize)
+}
+
+/// Run one tool. `progress` receives seconds transcribed and the video length when known.
+async fn call_tool(
+  name: &str,
+  args: &Value,
+  whisper: &WhisperCache,
+  progress: &(dyn Fn(f64, Option<f64>) + Sync),
+) -> Result<Value> {
+  match name {
+    "search" => {
+      let mut results = youtube::search_youtube(str_arg(args, "query")?).await.context("YouTube search failed")?;
+      results.truncate(usize_arg(args, "limit").unwrap_or(20));
+      cli::remember_videos(&results);
+      let results: Vec<VideoEntry> = results.iter().map(|e| VideoEntry::new(e, None)).collect();
+      Ok(json!({ "results": results }))
+    }
+    "channel_videos" => {
+      let channel = args
+        .get("channel")
+        .and_then(Value::as_str)
+        .map_or_else(|| crate::constants::constants().pastel_sketchbook_channel.clone(), str::to_string);
+      let url = cli::channel_url(&channel)?;
+      let videos = youtube::list_channel_videos(&url, 1, Some(usize_arg(args, "limit").unwrap_or(30)))
+        .await
+        .context("Failed to list channel videos")?;
+      cli::remember_videos(&videos);
+      let videos: Vec<VideoEntry> = videos.iter().map(|e| VideoEntry::new(e, None)).collect();
+      Ok(json!({ "videos": videos }))
+    }
+    "video_info" => {
+      let video_id = cli::extract_video_id(str_arg(args, "video")?);
+      let details = youtube::get_video_info(&video_id).await.context("Failed to get video info")?;
+      if let Err(e) = cache::append_videos(&[(&video_id, &details.title)]) {
+        tracing::warn!("Failed to update video cache: {}", e);
+      }
+      serde_json::to_value(details).context("Failed to serialize video details")
+    }
+    "transcript" => {
+      let video_id = cli::extract_video_id(str_arg(args, "video")?);
+      // Metadata only provides the progress total; transcribe without it if the lookup fails.
+      let duration = youtube::get_video_info(&video_id)
+        .await
+        .ok()
+        .and_then(|d| d.duration.as_deref().and_then(cli::parse_duration_secs));
+      let utterances = transcribe(&video_id, duration, bool_arg(args, "fresh"), whisper, progress).await?;
+      Ok(json!({ "video_id": video_id, "utterances": cli::transcript_values(&utterances, bool_arg(args, "raw"))? }))
+    }
+    "summarize" => {
+      let video_id = cli::extract_video_id(str_arg(args, "video")?);
+      let flags = cli::SummaryFlags {
+        llm: bool_arg(args, "llm"),
+        max_tokens: usize_arg(args, "max_tokens"),
+        keep_boilerplate: bool_arg(args, "keep_boilerplate"),
+      };
+      let fresh = bool_arg(args, "fresh");
+      let output = cli::summarize_video(&video_id, &flags, async |duration| {
+        transcribe(&video_id, duration, fresh, whisper, progress).await
+      })
+      .await?;
+      serde_json::to_value(output).context("Failed to serialize summary")
+    }
+    _ => Err(anyhow!("Unknown tool: {name}")),
+  }
+}
+
+/// Transcribe with the shared whisper instance, forwarding chunk progress.
+async fn transcribe(
+  video_id: &str,
+  duration: Option<u32>,
+  fresh: bool,
+  whisper: &WhisperCache,
+  progress: &(dyn Fn(f64, Option<f64>) + Sync),
+) -> Result<Vec<whisper_cli::Utternace>> {
+  let total = duration.map(f64::from);
+  #[allow(clippy::cast_precision_loss)]
+  let on_chunk = |batch: &[whisper_cli::Utternace]| {
+    if let Some(last) = batch.last() {
+      progress(last.stop as f64 / 100.0, total);
+    }
+  };
+  cli::run_transcription_reporting(video_id, duration, fresh, Arc::clone(whisper), "", &on_chunk).await
+}
+
+// ---------------------------------------------------------------------------
+// Server loop
+// ---------------------------------------------------------------------------
+
+/// Serve MCP over stdin/stdout until stdin closes.
+pub async fn serve() -> Result<()> {
+  let (out_tx, mut out_rx) = mpsc::unbounded_channel::<Value>();
+  let writer = tokio::spawn(async move {
+    let mut stdout = tokio::io::stdout();
+    while let Some(msg) = out_rx.recv().await {
+      let mut line = serde_json::to_vec(&msg).context("Failed to serialize MCP message")?;
+      line.push(b'\n');
+      stdout.write_all(&line).await.context("Failed to write to stdout")?;
+      stdout.flush().await.context("Failed to flush stdout")?;
+    }
+    Ok::<(), anyhow::Error>(())
+  });
+
+  let whisper: WhisperCache = Arc::new(StdMutex::new(None));
+  // Running tool calls, keyed by the JSON text of their request ID.
+  let running: Arc<StdMutex<HashMap<String, AbortHandle>>> = Arc::new(StdMutex::new(HashMap::new()));
+  eprintln!("yp MCP server v{} ready on stdio", env!("CARGO_PKG_VERSION"));
+
+  let mut lines = BufReader::new(tokio::io::stdin()).lines();
+  while let Some(line) = lines.next_line().await.context("Failed to read from stdin")? {
+    if line.trim().is_empty() {
+      continue;
+    }
+    let action = match serde_json::from_str::<Value>(&line) {
+      Ok(msg) => handle(&msg),
+      Err(e) => Action::Reply(error_response(&Value::Null, PARSE_ERROR, &format!("Parse error: {e}"))),
+    };
+    match action {
+      Action::Reply(msg) => {
+        let _ = out_tx.send(msg);
+      }
+      Action::Cancel(id) => {
+        if let Some(task) = running.lock().unwrap_or_else(std::sync::PoisonError::into_inner).remove(&id.to_string()) {
+          tracing::info!(request = %id, "mcp: cancelling tool call");
+          task.abort();
+        }
+      }
+      Action::CallTool { id, name, arguments, progress_token } => {
+        let key = id.to_string();
+        let (out, whisper, done) = (out_tx.clone(), Arc::clone(&whisper), Arc::clone(&running));
+        // Hold the lock across spawn so the task cannot finish (and deregister) before it is registered.
+        let mut tasks = running.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let task = tokio::spawn(async move {
+          let progress = |secs: f64, total: Option<f64>| {
+            if let Some(token) = &progress_token {
+              let mut params = json!({
+                "progressToken": token,
+                "progress": secs,
+                "message": format!("Transcribed {}", summarize::timestamp(secs)),
+              });
+              if let Some(total) = total {
+                params["total"] = json!(total);
+              }
+              let _ = out.send(json!({ "jsonrpc": "2.0", "method": "notifications/progress", "params": params }));
+            }
+          };
+          let result = call_tool(&name, &arguments, &whisper, &progress).await;
+          if let Err(e) = &result {
+            tracing::warn!(tool = %name, err = %e, "mcp: tool call failed");
+          }
+          done.lock().unwrap_or_else(std::sync::PoisonError::into_inner).remove(&id.to_string());
+          let _ = out.send(response(&id, tool_result(result)));
+        });
+        tasks.insert(key, task.abort_handle());
+      }
+      Action::Ignore => {}
+    }
+  }
+
+  // Client hung up: stop outstanding calls, then drain pending output.
+  for (_, task) in running.lock().unwrap_or_else(std::sync::PoisonError::into_inner).drain() {
+    task.abort();
+  }
+  drop(out_tx);
+  writer.await.context("MCP writer task failed")?
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn reply(action: Action) -> Value {
+    match action {
+      Action::Reply(v) => v,
+      other => panic!("expected a reply, got {other:?}"),
+    }
+  }
+
+  #[test]
+  fn initialize_negotiates_version() {
+    let init = |version: &str| {
+      reply(handle(&json!({
+        "jsonrpc": "2.0", "id": 1, "method": "initialize",
+        "params": { "protocolVersion": version, "capabilities": {}, "clientInfo": { "name": "t", "version": "0" } },
+      })))
+    };
+    let res = init("2025-03-26");
+    assert_eq!(res["id"], 1);
+    assert_eq!(res["result"]["protocolVersion"], "2025-03-26");
+    assert_eq!(res["result"]["serverInfo"]["name"], "yp");
+    assert!(res["result"]["capabilities"]["tools"].is_object());
+    assert_eq!(init("1999-01-01")["result"]["protocolVersion"], PROTOCOL_VERSION);
+  }
+
+  #[test]
+  fn tools_have_object_schemas() {
+    let res = reply(handle(&json!({ "jsonrpc": "2.0", "id": "a", "method": "tools/list" })));
+    let tools = res["result"]["tools"].as_array().unwrap();
+    let names: Vec<&str> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["search", "channel_videos", "video_info", "transcript", "summarize"]);
+    for tool in tools {
+      assert_eq!(tool["inputSchema"]["type"], "object", "{}", tool["name"]);
+      assert_eq!(tool["outputSchema"]["type"], "object", "{}", tool["name"]);
+    }
+  }
+
+  #[test]
+  fn tool_calls_and_cancellation_are_routed() {
+    let call = handle(&json!({
+      "jsonrpc": "2.0", "id": 7, "method": "tools/call",
+      "params": { "name": "summarize", "arguments": { "video": "abc" }, "_meta": { "progressToken": "p1" } },
+    }));
+    assert_eq!(
+      call,
+      Action::CallTool {
+        id: json!(7),
+        name: "summarize".to_string(),
+        arguments: json!({ "video": "abc" }),
+        progress_token: Some(json!("p1")),
+      }
+    );
+    let unknown =
+      reply(handle(&json!({ "jsonrpc": "2.0", "id": 8, "method": "tools/call", "params": { "name": "rm" } })));
+    assert_eq!(unknown["error"]["code"], INVALID_PARAMS);
+
+    let cancel =
+      handle(&json!({ "jsonrpc": "2.0", "method": "notifications/cancelled", "params": { "requestId": 7 } }));
+    assert_eq!(cancel, Action::Cancel(json!(7)));
+    assert_eq!(handle(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })), Action::Ignore);
+  }
+
+  #[test]
+  fn unknown_methods_and_failures_are_reported() {
+    let res = reply(handle(&json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/list" })));
+    assert_eq!(res["error"]["code"], METHOD_NOT_FOUND);
+
+    let ok = tool_result(Ok(json!({ "results": [] })));
+    assert_eq!(ok["isError"], false);
+    assert_eq!(ok["structuredContent"], json!({ "results": [] }));
+    let err = tool_result(Err(anyhow!("yt-dlp not found").context("YouTube search failed")));
+    assert_eq!(err["isError"], true);
+    assert_eq!(err["content"][0]["text"], "internal: YouTube search failed: yt-dlp not found");
+  }
+}
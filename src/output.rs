@@ -0,0 +1,359 @@
+//! Output formats: the global `--format` flag, and record output for the
+//! listing commands (`--format` and `--fields`).
+//!
+//! `--format` is one flag for every command that prints something formattable.
+//! `search`, `channel` and `info` take the record formats (json, jsonl, tsv,
+//! csv, table); `summarize` and `digest` take the document formats (json, md,
+//! txt, yaml). A value the command cannot print is an `invalid_input` error.
+//!
+//! Records are flat JSON objects. This module renders them as a JSON array,
+//! JSONL, TSV, CSV or an aligned table, optionally projected onto a subset of
+//! fields. Line formats stream as records arrive; the JSON array and the table
+//! wait for the last record (the table needs every row to size its columns).
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{IsTerminal, Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::error::{self, ErrorKind};
+use crate::schema;
+use crate::summarize::SummaryFormat;
+
+/// `--format`: every record and document format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+  /// JSON (records: one array; documents: pretty-printed)
+  Json,
+  /// Records: one JSON object per line
+  Jsonl,
+  /// Records: tab-separated values
+  Tsv,
+  /// Records: comma-separated values
+  Csv,
+  /// Records: aligned columns
+  Table,
+  /// Documents: Markdown
+  #[value(alias = "markdown")]
+  Md,
+  /// Documents: plain text
+  #[value(alias = "text")]
+  Txt,
+  /// Documents: YAML
+  #[value(alias = "yml")]
+  Yaml,
+}
+
+impl Format {
+  /// The record format for `search`, `channel` or `info`.
+  pub fn records(self, command: &str) -> Result<OutputFormat> {
+    match self {
+      Format::Json => Ok(OutputFormat::Json),
+      Format::Jsonl => Ok(OutputFormat::Jsonl),
+      Format::Tsv => Ok(OutputFormat::Tsv),
+      Format::Csv => Ok(OutputFormat::Csv),
+      Format::Table => Ok(OutputFormat::Table),
+      Format::Md | Format::Txt | Format::Yaml => Err(self.mismatch(command, "json, jsonl, tsv, csv or table")),
+    }
+  }
+
+  /// The document format for `summarize` or `digest`.
+  pub fn document(self, command: &str) -> Result<SummaryFormat> {
+    match self {
+      Format::Json => Ok(SummaryFormat::Json),
+      Format::Md => Ok(SummaryFormat::Md),
+      Format::Txt => Ok(SummaryFormat::Txt),
+      Format::Yaml => Ok(SummaryFormat::Yaml),
+      Format::Jsonl | Format::Tsv | Format::Csv | Format::Table => Err(self.mismatch(command, "json, md, txt or yaml")),
+    }
+  }
+
+  fn mismatch(self, command: &str, accepted: &str) -> anyhow::Error {
+    use clap::ValueEnum;
+    let name = self.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default();
+    error::fail(ErrorKind::InvalidInput, format!("`yp {command}` cannot print --format {name} (use {accepted})"))
+  }
+}
+
+/// How records are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+  /// One JSON array
+  Json,
+  /// One JSON object per line
+  Jsonl,
+  /// Tab-separated values with a header row
+  Tsv,
+  /// Comma-separated values (RFC 4180) with a header row
+  Csv,
+  /// Aligned columns fitted to the terminal width
+  Table,
+}
+
+/// `--fields` and the record format, shared by the listing commands.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct ListOptions {
+  /// Record format from the global `--format` (default: the command's usual JSON or JSONL)
+  #[arg(skip)]
+  pub format: Option<OutputFormat>,
+  /// Only print these fields, in this order, e.g. `video_id,title,duration`
+  #[arg(long, value_delimiter = ',')]
+  pub fields: Vec<String>,
+}
+
+/// Narrowest a table column is shrunk to before the table overflows instead.
+const MIN_COLUMN_WIDTH: usize = 6;
+
+/// Prints records in one format, projected onto the selected fields.
+pub struct RecordWriter {
+  format: OutputFormat,
+  /// Selected fields, in output order.
+  fields: Vec<String>,
+  /// Whether the user picked the fields (JSON output is projected only then).
+  projected: bool,
+  /// Records held back for the JSON array or the table.
+  buffered: Vec<Value>,
+  wrote_header: bool,
+}
+
+impl RecordWriter {
+  /// `known` lists the record's fields in their natural order; it is the
+  /// default selection and the set `--fields` must come from.
+  pub fn new(opts: &ListOptions, default: OutputFormat, known: &[&str]) -> Result<Self> {
+    if let Some(unknown) = opts.fields.iter().find(|f| !known.contains(&f.as_str())) {
+      return Err(error::fail(
+        ErrorKind::InvalidInput,
+        format!("Unknown field `{unknown}` (known: {})", known.join(", ")),
+      ));
+    }
+    let projected = !opts.fields.is_empty();
+    let fields = if projected { opts.fields.clone() } else { known.iter().map(|f| (*f).to_string()).collect() };
+    Ok(Self { format: opts.format.unwrap_or(default), fields, projected, buffered: Vec::new(), wrote_header: false })
+  }
+
+  /// Print (or hold back) one record.
+  pub fn write(&mut self, record: &impl Serialize) -> Result<()> {
+    let value = serde_json::to_value(record).context("Failed to serialize record")?;
+    match self.format {
+      OutputFormat::Json | OutputFormat::Table => {
+        self.buffered.push(value);
+        Ok(())
+      }
+      OutputFormat::Jsonl => {
+        let line =
+          serde_json::to_string(&schema::versioned(&self.project(&value))).context("Failed to serialize record")?;
+        print_line(&line)
+      }
+      OutputFormat::Tsv | OutputFormat::Csv => {
+        let sep = if self.format == OutputFormat::Tsv { "\t" } else { "," };
+        if !self.wrote_header {
+          self.wrote_header = true;
+          print_line(&self.fields.iter().map(|f| self.escape(f)).collect::<Vec<_>>().join(sep))?;
+        }
+        let row: Vec<String> = self.fields.iter().map(|f| self.escape(&cell(&value[f.as_str()]))).collect();
+        print_line(&row.join(sep))
+      }
+    }
+  }
+
+  /// Print whatever was held back.
+  pub fn finish(self) -> Result<()> {
+    match self.format {
+      OutputFormat::Json => {
+        let records: Vec<Value> = self
+          .buffered
+          .iter()
+          .map(|v| serde_json::to_value(schema::versioned(&self.project(v))))
+          .collect::<Result<_, _>>()?;
+        let json = serde_json::to_string_pretty(&records).context("Failed to serialize records")?;
+        print_line(&json)
+      }
+      OutputFormat::Table => {
+        let rows: Vec<Vec<String>> =
+          self.buffered.iter().map(|v| self.fields.iter().map(|f| cell(&v[f.as_str()])).collect()).collect();
+        for line in table(&self.fields, &rows, terminal_width()) {
+          print_line(&line)?;
+        }
+        Ok(())
+      }
+      OutputFormat::Jsonl | OutputFormat::Tsv | OutputFormat::Csv => Ok(()),
+    }
+  }
+
+  /// Print a single record as a document: pretty JSON instead of a one-element array.
+  pub fn write_one(mut self, record: &impl Serialize) -> Result<()> {
+    if self.format != OutputFormat::Json {
+      self.write(record)?;
+      return self.finish();
+    }
+    let value = serde_json::to_value(record).context("Failed to serialize record")?;
+    let json =
+      serde_json::to_string_pretty(&schema::versioned(&self.project(&value))).context("Failed to serialize record")?;
+    print_line(&json)
+  }
+
+  /// Keep only the selected fields (all of them when none were selected).
+  fn project(&self, value: &Value) -> Value {
+    if !self.projected {
+      return value.clone();
+    }
+    let map: serde_json::Map<String, Value> =
+      self.fields.iter().map(|f| (f.clone(), value.get(f).cloned().unwrap_or(Value::Null))).collect();
+    Value::Object(map)
+  }
+
+  fn escape(&self, text: &str) -> String {
+    match self.format {
+      OutputFormat::Tsv => text.replace(['\t', '\n', '\r'], " "),
+      _ if text.contains([',', '"', '\n', '\r']) => format!("\"{}\"", text.replace('"', "\"\"")),
+      _ => text.to_string(),
+    }
+  }
+}
+
+/// One field as text: strings as-is, lists comma-joined, null empty.
+fn cell(value: &Value) -> String {
+  match value {
+    Value::Null => String::new(),
+    Value::String(s) => s.clone(),
+    Value::Array(items) => items.iter().map(cell).collect::<Vec<_>>().join(", "),
+    other => other.to_string(),
+  }
+}
+
+/// Write one line to stdout and flush, so pipes see records as they arrive.
+fn print_line(line: &str) -> Result<()> {
+  let mut stdout = std::io::stdout().lock();
+  writeln!(stdout, "{line}").context("Failed to write output")?;
+  stdout.flush().context("Failed to flush stdout")
+}
+
+/// Columns available for the table: the terminal's, else `$COLUMNS`, else unlimited.
+fn terminal_width() -> Option<usize> {
+  if std::io::stdout().is_terminal()
+    && let Ok((width, _)) = ratatui::crossterm::terminal::size()
+  {
+    return Some(usize::from(width));
+  }
+  std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok())
+}
+
+/// Lay out an aligned table with upper-case headers. When it is wider than
+/// `width`, the widest columns give up space first and long cells end in `…`.
+fn table(fields: &[String], rows: &[Vec<String>], width: Option<usize>) -> Vec<String> {
+  let headers: Vec<String> = fields.iter().map(|f| f.to_uppercase()).collect();
+  let mut widths: Vec<usize> = headers.iter().map(|h| h.width()).collect();
+  for row in rows {
+    for (w, cell) in widths.iter_mut().zip(row) {
+      *w = (*w).max(cell.width());
+    }
+  }
+
+  if let Some(width) = width {
+    let gaps = 2 * widths.len().saturating_sub(1);
+    while widths.iter().sum::<usize>() + gaps > width {
+      let Some(widest) = widths.iter_mut().filter(|w| **w > MIN_COLUMN_WIDTH).max_by_key(|w| **w) else {
+        break;
+      };
+      *widest -= 1;
+    }
+  }
+
+  std::iter::once(&headers)
+    .chain(rows)
+    .map(|row| {
+      let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, &w)| pad(&truncate(cell, w), w)).collect();
+      cells.join("  ").trim_end().to_string()
+    })
+    .collect()
+}
+
+/// Cut `text` to `width` display columns, ending in `…` when cut.
+pub(crate) fn truncate(text: &str, width: usize) -> String {
+  let text = text.replace(['\n', '\r', '\t'], " ");
+  if text.width() <= width {
+    return text;
+  }
+  let mut out = String::new();
+  let mut used = 0;
+  for c in text.chars() {
+    let w = c.width().unwrap_or(0);
+    if used + w + 1 > width {
+      break;
+    }
+    out.push(c);
+    used += w;
+  }
+  out.push('…');
+  out
+}
+
+fn pad(text: &str, width: usize) -> String {
+  format!("{text}{}", " ".repeat(width.saturating_sub(text.width())))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn writer(format: OutputFormat, fields: &[&str]) -> RecordWriter {
+    let opts = ListOptions { format: Some(format), fields: fields.iter().map(|f| (*f).to_string()).collect() };
+    RecordWriter::new(&opts, OutputFormat::Json, &["video_id", "title", "tags"]).unwrap()
+  }
+
+  #[test]
+  fn unknown_fields_are_rejected() {
+    let opts = ListOptions { format: None, fields: vec!["views".to_string()] };
+    let err = RecordWriter::new(&opts, OutputFormat::Json, &["video_id", "title"]).err().unwrap();
+    assert_eq!(error::kind_of(&err), ErrorKind::InvalidInput);
+  }
+
+  #[test]
+  fn projection_keeps_selected_fields_in_order() {
+    let w = writer(OutputFormat::Jsonl, &["title", "video_id"]);
+    assert_eq!(w.fields, vec!["title", "video_id"]);
+    let projected = w.project(&json!({ "video_id": "abc", "title": "T", "tags": ["a"] }));
+    assert_eq!(projected, json!({ "video_id": "abc", "title": "T" }));
+    let all = writer(OutputFormat::Jsonl, &[]);
+    assert_eq!(all.project(&json!({ "video_id": "abc", "extra": 1 })), json!({ "video_id": "abc", "extra": 1 }));
+  }
+
+  #[test]
+  fn cells_and_escaping() {
+    assert_eq!(cell(&json!(["art", "glaze"])), "art, glaze");
+    assert_eq!(cell(&Value::Null), "");
+    assert_eq!(cell(&json!(3)), "3");
+    let csv = writer(OutputFormat::Csv, &[]);
+    assert_eq!(csv.escape("Glaze, \"fast\""), "\"Glaze, \"\"fast\"\"\"");
+    assert_eq!(csv.escape("plain"), "plain");
+    assert_eq!(writer(OutputFormat::Tsv, &[]).escape("a\tb\nc"), "a b c");
+  }
+
+  #[test]
+  fn table_aligns_and_fits_width() {
+    let fields = vec!["video_id".to_string(), "title".to_string()];
+    let rows = vec![
+      vec!["abc".to_string(), "A rather long title about glazing".to_string()],
+      vec!["defgh".to_string(), "Short".to_string()],
+    ];
+    let full = table(&fields, &rows, None);
+    assert_eq!(full[0], "VIDEO_ID  TITLE");
+    assert_eq!(full[2], "defgh     Short");
+
+    let fitted = table(&fields, &rows, Some(24));
+    assert!(fitted.iter().all(|l| l.width() <= 24), "{fitted:?}");
+    assert_eq!(fitted[1], "abc       A rather long…");
+  }
+
+  #[test]
+  fn truncate_counts_display_width() {
+    assert_eq!(truncate("日本語のタイトル", 7), "日本語…");
+    assert_eq!(truncate("short", 10), "short");
+  }
+}
@@ -0,0 +1,403 @@
+//! Headless playback for `yp play`: mpv without the TUI.
+//!
+//! Videos play one after another. A single status line on stderr shows the
+//! position; when stdin is a terminal it is put in raw mode and read for
+//! single-key controls. With `--transcript`, utterances are printed to stdout
+//! as playback reaches them — from the transcript store when the video was
+//! transcribed before, otherwise from a pipeline that runs alongside mpv.
+
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::terminal;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::cli::AbortOnDrop;
+use crate::display::DisplayMode;
+use crate::error::{self, ErrorKind};
+use crate::player::{MusicPlayer, VideoDetails};
+use crate::store;
+use crate::transcript::{TranscriptEvent, WhisperCache};
+use crate::youtube;
+
+/// How often the status line is redrawn (and keys polled).
+const TICK: Duration = Duration::from_millis(200);
+
+/// A jump between two ticks larger than this is a seek, not playback.
+const SEEK_JUMP_SECS: f64 = 5.0;
+
+/// What a key press asks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+  TogglePause,
+  /// Relative seek in seconds.
+  Seek(f64),
+  Next,
+  Quit,
+}
+
+/// Map a key to its control: space pauses, ←/→ seek 10s, ↓/↑ seek 60s, n skips, q quits.
+fn control_for(code: KeyCode, modifiers: KeyModifiers) -> Option<Control> {
+  match code {
+    KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => Some(Control::Quit),
+    KeyCode::Char(' ' | 'p') => Some(Control::TogglePause),
+    KeyCode::Left | KeyCode::Char('h') => Some(Control::Seek(-10.0)),
+    KeyCode::Right | KeyCode::Char('l') => Some(Control::Seek(10.0)),
+    KeyCode::Down | KeyCode::Char('j') => Some(Control::Seek(-60.0)),
+    KeyCode::Up | KeyCode::Char('k') => Some(Control::Seek(60.0)),
+    KeyCode::Char('n') => Some(Control::Next),
+    KeyCode::Char('q') | KeyCode::Esc => Some(Control::Quit),
+    _ => None,
+  }
+}
+
+/// Keeps the terminal in raw mode while alive, so keys arrive one at a time.
+struct RawMode;
+
+impl RawMode {
+  fn enable() -> Result<Self> {
+    terminal::enable_raw_mode().context("Failed to enable raw mode")?;
+    Ok(Self)
+  }
+}
+
+impl Drop for RawMode {
+  fn drop(&mut self) {
+    let _ = terminal::disable_raw_mode();
+  }
+}
+
+/// Reads keys on a blocking thread while alive, so the playback loop never
+/// blocks a runtime worker on the terminal.
+struct Keys {
+  rx: mpsc::UnboundedReceiver<Control>,
+  stop: Arc<AtomicBool>,
+}
+
+impl Keys {
+  fn start() -> Self {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::clone(&stop);
+    tokio::task::spawn_blocking(move || {
+      while !stopped.load(Ordering::Relaxed) {
+        match event::poll(TICK) {
+          Ok(false) => {}
+          Ok(true) => match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+              if let Some(control) = control_for(key.code, key.modifiers)
+                && tx.send(control).is_err()
+              {
+                return;
+              }
+            }
+            Ok(_) => {}
+            Err(e) => {
+              tracing::warn!(err = %e, "play: failed to read terminal event");
+              return;
+            }
+          },
+          Err(e) => {
+            tracing::warn!(err = %e, "play: failed to poll terminal events");
+            return;
+          }
+        }
+      }
+    });
+    Self { rx, stop }
+  }
+
+  /// The next control, waiting at most one tick.
+  async fn next(&mut self) -> Option<Control> {
+    match tokio::time::timeout(TICK, self.rx.recv()).await {
+      Ok(Some(control)) => Some(control),
+      // The reader stopped: keep ticking without keys.
+      Ok(None) => {
+        tokio::time::sleep(TICK).await;
+        None
+      }
+      Err(_) => None,
+    }
+  }
+}
+
+impl Drop for Keys {
+  fn drop(&mut self) {
+    self.stop.store(true, Ordering::Relaxed);
+  }
+}
+
+/// The updating status line on stderr (plain lines when stderr is not a terminal).
+struct StatusLine {
+  live: bool,
+  /// Raw mode needs explicit carriage returns.
+  raw: bool,
+}
+
+impl StatusLine {
+  fn draw(&self, line: &str) {
+    if !self.live {
+      return;
+    }
+    let width = terminal::size().map_or(80, |(w, _)| usize::from(w));
+    let mut stderr = std::io::stderr().lock();
+    let _ = write!(stderr, "\r\x1b[2K{}", crate::output::truncate(line, width.saturating_sub(1)));
+    let _ = stderr.flush();
+  }
+
+  fn clear(&self) {
+    if self.live {
+      let _ = write!(std::io::stderr(), "\r\x1b[2K");
+    }
+  }
+
+  /// Print a message on its own line, above the status line.
+  fn message(&self, text: &str) {
+    self.clear();
+    let end = if self.raw { "\r\n" } else { "\n" };
+    let _ = write!(std::io::stderr(), "{text}{end}");
+  }
+
+  /// Print a transcript line to stdout without tearing the status line.
+  fn print(&self, text: &str) -> Result<()> {
+    self.clear();
+    let end = if self.raw && std::io::stdout().is_terminal() { "\r\n" } else { "\n" };
+    let mut stdout = std::io::stdout().lock();
+    write!(stdout, "{text}{end}").context("Failed to write output")?;
+    stdout.flush().context("Failed to flush stdout")
+  }
+}
+
+/// `▶ 1:23 / 4:56  Title  [2/5]` from mpv's `Time: … | Title: … | pause pct%` status.
+fn status_text(
+  details: &VideoDetails,
+  mpv_status: Option<&str>,
+  paused: bool,
+  position: usize,
+  total: usize,
+) -> String {
+  let icon = if paused { "⏸" } else { "▶" };
+  let time = mpv_status
+    .and_then(|s| s.strip_prefix("Time: "))
+    .and_then(|s| s.split(" | ").next())
+    .map_or_else(|| "--:--".to_string(), |t| t.split(" / ").map(trim_hours).collect::<Vec<_>>().join(" / "));
+  let queue = if total > 1 { format!("  [{position}/{total}]") } else { String::new() };
+  format!("{icon} {time}  {}{queue}", details.title)
+}
+
+/// `00:01:23` → `1:23`; mpv's `/full` times always carry hours.
+fn trim_hours(time: &str) -> String {
+  let time = time.trim();
+  let time = time.strip_prefix("00:").unwrap_or(time);
+  time.strip_prefix('0').filter(|t| t.starts_with(|c: char| c.is_ascii_digit())).unwrap_or(time).to_string()
+}
+
+/// Utterances printed as playback reaches them.
+struct LiveTranscript {
+  /// `(start secs, line)`, in order.
+  lines: Vec<(f64, String)>,
+  /// Lines before this index have been printed (or skipped by a seek).
+  next: usize,
+  last_pos: Option<f64>,
+  rx: Option<mpsc::UnboundedReceiver<TranscriptEvent>>,
+  _pipeline: Option<AbortOnDrop<()>>,
+}
+
+impl LiveTranscript {
+  /// Load the stored transcript and, unless it is complete, transcribe the rest alongside playback.
+  fn start(video_id: &str, player: &MusicPlayer, whisper_cache: WhisperCache) -> Self {
+    let mut live = Self { lines: Vec::new(), next: 0, last_pos: None, rx: None, _pipeline: None };
+    let mut start_offset = 0;
+    if let Some(record) = store::load(video_id) {
+      live.extend(&record.to_utterances());
+      if record.complete {
+        return live;
+      }
+      start_offset = record.offset_secs;
+    }
+    let Some(details) = player.current_details.as_ref() else { return live };
+    let (tx, rx) = mpsc::unbounded_channel();
+    let duration_hint = details.duration.as_deref().and_then(crate::cli::parse_duration_secs);
+    let ipc_socket = player.ipc_socket_path().map(str::to_string);
+    live.rx = Some(rx);
+    live._pipeline = Some(AbortOnDrop(crate::transcript::spawn_transcription_pipeline(
+      tx,
+      details.url.clone(),
+      whisper_cache,
+      ipc_socket,
+      duration_hint,
+      start_offset,
+    )));
+    live
+  }
+
+  #[allow(clippy::cast_precision_loss)]
+  fn extend(&mut self, utterances: &[whisper_cli::Utternace]) {
+    self.lines.extend(utterances.iter().map(|u| {
+      let start = u.start as f64 / 100.0;
+      (start, format!("{}  {}", crate::summarize::timestamp(start), u.text.trim()))
+    }));
+  }
+
+  /// Take in pipeline events; returns a failure message to show once.
+  fn poll(&mut self) -> Option<String> {
+    let rx = self.rx.as_mut()?;
+    let mut failure = None;
+    let mut utterances = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+      match event {
+        TranscriptEvent::ChunkTranscribed(chunk) => utterances.extend(chunk),
+        TranscriptEvent::Failed(_, msg) => failure = Some(format!("Transcription failed: {msg}")),
+        TranscriptEvent::AudioExtracted | TranscriptEvent::DownloadProgress(..) | TranscriptEvent::Transcribed => {}
+      }
+    }
+    self.extend(&utterances);
+    failure
+  }
+
+  /// Lines playback has reached at `pos`. After a seek, printing resumes at the new position.
+  fn due(&mut self, pos: f64) -> &[(f64, String)] {
+    if self.last_pos.is_some_and(|last| pos < last - 1.0 || pos > last + SEEK_JUMP_SECS) {
+      self.next = self.lines.partition_point(|(start, _)| *start < pos - 1.0);
+    }
+    self.last_pos = Some(pos);
+    let from = self.next.min(self.lines.len());
+    self.next = from + self.lines[from..].partition_point(|(start, _)| *start <= pos);
+    &self.lines[from..self.next]
+  }
+}
+
+/// Play `video_ids` in order. Returns once the queue ends or `q` is pressed.
+///
+/// A video whose metadata cannot be fetched is skipped with a warning; the
+/// run fails only when nothing could be played, or when yt-dlp or mpv is missing.
+pub async fn play_queue(video_ids: &[String], transcript: bool) -> Result<()> {
+  let raw_mode = if std::io::stdin().is_terminal() { Some(RawMode::enable()?) } else { None };
+  let status = StatusLine { live: std::io::stderr().is_terminal(), raw: raw_mode.is_some() };
+  if raw_mode.is_some() {
+    status.message("space pause · ←/→ 10s · ↓/↑ 60s · n next · q quit");
+  }
+
+  let mut keys = raw_mode.as_ref().map(|_| Keys::start());
+  let mut player = MusicPlayer::new(DisplayMode::Direct);
+  let whisper_cache: WhisperCache = Arc::new(StdMutex::new(None));
+  let mut first_failure = None;
+  let mut played = 0;
+
+  'queue: for (i, video_id) in video_ids.iter().enumerate() {
+    let details = match youtube::get_video_info(video_id).await.context("Failed to get video info") {
+      Ok(details) => details,
+      Err(e) if error::kind_of(&e) == ErrorKind::DependencyMissing => return Err(e),
+      Err(e) => {
+        status.message(&format!("Skipping {video_id}: {e:#}"));
+        first_failure.get_or_insert(e);
+        continue;
+      }
+    };
+    if !status.live {
+      status.message(&format!("Playing [{}/{}] {video_id} — {}", i + 1, video_ids.len(), details.title));
+    }
+    player.play(details.clone(), None).await?;
+    played += 1;
+    let mut live = transcript.then(|| LiveTranscript::start(video_id, &player, whisper_cache.clone()));
+
+    loop {
+      player.check_mpv_status();
+      if player.has_exited() {
+        break;
+      }
+      let mpv_status = player.get_last_mpv_status();
+
+      if let Some(live) = live.as_mut() {
+        if let Some(failure) = live.poll() {
+          status.message(&failure);
+        }
+        if let Some(pos) = mpv_status.as_deref().and_then(crate::parse_mpv_time_secs) {
+          for (_, line) in live.due(pos) {
+            status.print(line)?;
+          }
+        }
+      }
+      status.draw(&status_text(&details, mpv_status.as_deref(), player.paused, i + 1, video_ids.len()));
+
+      let control = match keys.as_mut() {
+        Some(keys) => keys.next().await,
+        None => {
+          tokio::time::sleep(TICK).await;
+          None
+        }
+      };
+      match control {
+        Some(Control::TogglePause) => player.toggle_pause().await?,
+        Some(Control::Seek(secs)) => player.seek(secs).await?,
+        Some(Control::Next) => break,
+        Some(Control::Quit) => break 'queue,
+        None => {}
+      }
+    }
+  }
+
+  player.stop().await?;
+  status.clear();
+  drop(keys);
+  drop(raw_mode);
+
+  match first_failure {
+    Some(e) if played == 0 => Err(e),
+    _ => Ok(()),
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn details(title: &str) -> VideoDetails {
+    VideoDetails {
+      url: String::new(),
+      title: title.to_string(),
+      uploader: None,
+      duration: None,
+      upload_date: None,
+      view_count: None,
+      tags: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn keys_map_to_controls() {
+    assert_eq!(control_for(KeyCode::Char(' '), KeyModifiers::NONE), Some(Control::TogglePause));
+    assert_eq!(control_for(KeyCode::Left, KeyModifiers::NONE), Some(Control::Seek(-10.0)));
+    assert_eq!(control_for(KeyCode::Up, KeyModifiers::NONE), Some(Control::Seek(60.0)));
+    assert_eq!(control_for(KeyCode::Char('c'), KeyModifiers::CONTROL), Some(Control::Quit));
+    assert_eq!(control_for(KeyCode::Char('x'), KeyModifiers::NONE), None);
+  }
+
+  #[test]
+  fn status_text_shows_position_and_queue() {
+    let status = "Time: 00:01:23 / 01:04:56 | Title: Glazing | no 2%";
+    assert_eq!(status_text(&details("Glazing"), Some(status), false, 2, 5), "▶ 1:23 / 1:04:56  Glazing  [2/5]");
+    assert_eq!(status_text(&details("Glazing"), None, true, 1, 1), "⏸ --:--  Glazing");
+  }
+
+  #[test]
+  fn transcript_follows_playback_and_seeks() {
+    let mut live = LiveTranscript { lines: Vec::new(), next: 0, last_pos: None, rx: None, _pipeline: None };
+    live.lines = (0..10).map(|i| (f64::from(i) * 10.0, format!("line {i}"))).collect();
+    let texts = |lines: &[(f64, String)]| lines.iter().map(|(_, t)| t.clone()).collect::<Vec<_>>();
+
+    assert_eq!(texts(live.due(12.0)), vec!["line 0", "line 1"]);
+    assert!(live.due(13.0).is_empty());
+    // A jump forward skips what was passed over.
+    assert_eq!(texts(live.due(61.0)), vec!["line 6"]);
+    // Seeking back replays from the new position.
+    assert_eq!(texts(live.due(30.5)), vec!["line 3"]);
+  }
+}
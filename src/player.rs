@@ -55,6 +55,11 @@ impl MusicPlayer {
     self.current_process.is_some()
   }
 
+  /// Whether mpv has exited on its own, e.g. at the end of the track.
+  pub fn has_exited(&mut self) -> bool {
+    self.current_process.as_mut().is_some_and(|child| matches!(child.try_wait(), Ok(Some(_))))
+  }
+
   pub fn check_mpv_status(&mut self) {
     if let Some(rx) = &mut self.mpv_status_rx {
       while let Ok(status) = rx.try_recv() {
@@ -71,7 +76,8 @@ impl MusicPlayer {
     self.ipc_socket_path.as_deref()
   }
 
-  pub async fn play(&mut self, details: VideoDetails) -> Result<()> {
+  /// Start mpv on the video, optionally `start_secs` into it.
+  pub async fn play(&mut self, details: VideoDetails, start_secs: Option<f64>) -> Result<()> {
     self.stop().await.context("Failed to stop previous playback")?;
     self.current_details = Some(details.clone());
     self.paused = false;
@@ -88,6 +94,9 @@ impl MusicPlayer {
       &format!("--input-ipc-server={socket_path_str}"),
       &details.url,
     ]);
+    if let Some(start) = start_secs {
+      cmd.arg(format!("--start={start:.1}"));
+    }
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::piped());
     // Send stderr to null — if piped but never drained, the pipe buffer
@@ -96,7 +105,10 @@ impl MusicPlayer {
 
     let mut child = cmd.spawn().map_err(|e| {
       if e.kind() == std::io::ErrorKind::NotFound {
-        anyhow!("mpv not found. Install it with: brew install mpv (macOS) or apt install mpv (Linux)")
+        crate::error::fail(
+          crate::error::ErrorKind::DependencyMissing,
+          "mpv not found. Install it with: brew install mpv (macOS) or apt install mpv (Linux)",
+        )
       } else {
         anyhow!(e).context("Failed to spawn mpv process")
       }
@@ -123,19 +135,34 @@ impl MusicPlayer {
   }
 
   pub async fn toggle_pause(&mut self) -> Result<()> {
+    if self.ipc_socket_path.is_none() {
+      return Ok(());
+    }
+    self.send_ipc(b"{\"command\":[\"cycle\",\"pause\"]}\n").await.context("Failed to send pause command to mpv")?;
+    self.paused = !self.paused;
+    Ok(())
+  }
+
+  /// Seek `secs` forward (or backward when negative) from the current position.
+  pub async fn seek(&mut self, secs: f64) -> Result<()> {
+    let cmd = format!("{{\"command\":[\"seek\",{secs},\"relative\"]}}\n");
+    self.send_ipc(cmd.as_bytes()).await.context("Failed to send seek command to mpv")
+  }
+
+  /// Write one JSON command line to mpv's IPC socket (a no-op when nothing is playing).
+  async fn send_ipc(&self, cmd: &[u8]) -> Result<()> {
     let Some(ref socket_path) = self.ipc_socket_path else {
       return Ok(());
     };
     let stream = tokio::net::UnixStream::connect(socket_path).await.context("Failed to connect to mpv IPC socket")?;
     stream.writable().await.context("mpv IPC socket not writable")?;
-    let cmd = b"{\"command\":[\"cycle\",\"pause\"]}\n";
-    let written = stream.try_write(cmd).context("Failed to send pause command to mpv")?;
+    let written = stream.try_write(cmd)?;
     if written < cmd.len() {
       return Err(anyhow!("Partial write to mpv IPC socket: wrote {} of {} bytes", written, cmd.len()));
     }
-    self.paused = !self.paused;
     Ok(())
   }
+
   pub async fn stop(&mut self) -> Result<()> {
     if let Some(handle) = self.mpv_monitor_handle.take() {
       handle.abort();
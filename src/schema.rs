@@ -0,0 +1,1021 @@
+//! Versioned output types and their JSON Schemas.
+//!
+//! Every top-level JSON value yp prints (a document, or one JSONL line)
+//! carries `schema_version`, and `yp schema <command>` prints the JSON Schema
+//! of that command's output. The schemas are written by hand next to the
+//! serde types they describe; the tests serialize a fully populated value of
+//! each type and check that it and its schema list the same fields, and
+//! compare every command's schema with the copy recorded under
+//! `testdata/schemas/v<SCHEMA_VERSION>/`. Recorded versions are never
+//! rewritten, so changing an output shape fails the tests until
+//! `SCHEMA_VERSION` is bumped and the new version recorded.
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::error::ErrorKind;
+use crate::player::VideoDetails;
+use crate::youtube::{self, SearchEntry, VideoMeta};
+
+/// Version of every output shape. Bump it whenever a schema below changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+// ---------------------------------------------------------------------------
+// Output types
+// ---------------------------------------------------------------------------
+
+/// A value printed with `schema_version` as its first field.
+#[derive(Serialize)]
+pub struct Versioned<'a, T: Serialize + ?Sized> {
+  schema_version: u32,
+  #[serde(flatten)]
+  value: &'a T,
+}
+
+/// Wrap an object-shaped value for output.
+pub fn versioned<T: Serialize + ?Sized>(value: &T) -> Versioned<'_, T> {
+  Versioned { schema_version: SCHEMA_VERSION, value }
+}
+
+/// One video of `yp search` or `yp channel`.
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoEntry {
+  pub video_id: String,
+  pub title: String,
+  pub url: String,
+  pub uploader: Option<String>,
+  pub duration: Option<String>,
+  pub upload_date: Option<String>,
+  pub view_count: Option<String>,
+  pub tags: Vec<String>,
+}
+
+impl VideoEntry {
+  /// Field names in output order, for `--fields`.
+  pub const FIELDS: [&'static str; 8] =
+    ["video_id", "title", "url", "uploader", "duration", "upload_date", "view_count", "tags"];
+
+  /// Build from a listing entry; enriched metadata, when present, takes precedence.
+  pub fn new(entry: &SearchEntry, meta: Option<&VideoMeta>) -> Self {
+    let pick = |meta_field: Option<&Option<String>>, entry_field: &Option<String>| {
+      meta_field.and_then(Clone::clone).or_else(|| entry_field.clone())
+    };
+    let tags = pick(meta.map(|m| &m.tags), &entry.tags);
+    Self {
+      video_id: entry.video_id.clone(),
+      title: entry.title.clone(),
+      url: format!("https://youtube.com/watch?v={}", entry.video_id),
+      uploader: pick(meta.map(|m| &m.uploader), &entry.uploader),
+      duration: pick(meta.map(|m| &m.duration), &entry.duration),
+      upload_date: pick(meta.map(|m| &m.upload_date), &entry.upload_date),
+      view_count: pick(meta.map(|m| &m.view_count), &entry.view_count),
+      tags: tags
+        .map(|t| {
+          youtube::clean_tags(&t).split(',').map(str::trim).filter(|t| !t.is_empty()).map(String::from).collect()
+        })
+        .unwrap_or_default(),
+    }
+  }
+}
+
+/// `yp info`: the video's details plus its ID.
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoInfo<'a> {
+  pub video_id: &'a str,
+  #[serde(flatten)]
+  pub details: &'a VideoDetails,
+}
+
+impl VideoInfo<'_> {
+  /// Field names in output order, for `--fields`.
+  pub const FIELDS: [&'static str; 8] =
+    ["video_id", "url", "title", "uploader", "duration", "upload_date", "view_count", "tags"];
+}
+
+// ---------------------------------------------------------------------------
+// Schemas
+// ---------------------------------------------------------------------------
+
+/// `{"type": [t, "null"]}` for optional fields.
+fn nullable(t: &str) -> Value {
+  json!({ "type": [t, "null"] })
+}
+
+/// An object schema whose listed properties are all required.
+fn object(properties: Value, required: &[&str]) -> Value {
+  json!({
+    "type": "object",
+    "properties": properties,
+    "required": required,
+  })
+}
+
+/// `VideoEntry`: one search result or channel listing row.
+pub fn video_entry() -> Value {
+  object(
+    json!({
+      "video_id": { "type": "string" },
+      "title": { "type": "string" },
+      "url": { "type": "string" },
+      "uploader": nullable("string"),
+      "duration": nullable("string"),
+      "upload_date": nullable("string"),
+      "view_count": nullable("string"),
+      "tags": { "type": "array", "items": { "type": "string" } },
+    }),
+    &["video_id", "title", "url", "uploader", "duration", "upload_date", "view_count", "tags"],
+  )
+}
+
+/// `player::VideoDetails`: metadata for one video.
+pub fn video_details() -> Value {
+  object(
+    json!({
+      "url": { "type": "string" },
+      "title": { "type": "string" },
+      "uploader": nullable("string"),
+      "duration": nullable("string"),
+      "upload_date": nullable("string"),
+      "view_count": nullable("string"),
+      "tags": { "type": "array", "items": { "type": "string" } },
+    }),
+    &["url", "title", "uploader", "duration", "upload_date", "view_count", "tags"],
+  )
+}
+
+/// `keywords::Keyword`.
+fn keyword() -> Value {
+  object(
+    json!({
+      "phrase": { "type": "string" },
+      "score": { "type": "number", "description": "Relative score in 0–1" },
+      "first_at_secs": { "type": "number" },
+    }),
+    &["phrase", "score", "first_at_secs"],
+  )
+}
+
+/// `summarize::ClassifiedUtterance`.
+pub fn classified_utterance() -> Value {
+  object(
+    json!({
+      "start": { "type": "number" },
+      "end": { "type": "number" },
+      "text": { "type": "string" },
+      "class": {
+        "type": "string",
+        "enum": ["non_speech", "filler", "repetition", "boilerplate", "topic_shift", "question", "key_segment", "normal"],
+      },
+    }),
+    &["start", "end", "text", "class"],
+  )
+}
+
+/// Raw transcript line: `{start, end, text}` in seconds.
+pub fn raw_utterance() -> Value {
+  object(
+    json!({
+      "start": { "type": "number" },
+      "end": { "type": "number" },
+      "text": { "type": "string" },
+    }),
+    &["start", "end", "text"],
+  )
+}
+
+/// `summarize::SummaryStats`.
+fn summary_stats() -> Value {
+  let time_pair = json!({ "type": "array", "items": { "type": "number" }, "minItems": 2, "maxItems": 2 });
+  object(
+    json!({
+      "time_range": time_pair,
+      "total_utterances": { "type": "integer" },
+      "suppressed_utterances": { "type": "integer" },
+      "filler_ratio": { "type": "number" },
+      "non_speech_secs": { "type": "number" },
+      "abstract": { "type": "string" },
+      "keywords": { "type": "array", "items": keyword() },
+      "topics": {
+        "type": "array",
+        "items": object(
+          json!({
+            "start_secs": { "type": "number" },
+            "end_secs": { "type": "number" },
+            "summary": { "type": "string" },
+            "utterance_count": { "type": "integer" },
+            "keywords": { "type": "array", "items": keyword() },
+          }),
+          &["start_secs", "end_secs", "summary", "utterance_count", "keywords"],
+        ),
+      },
+      "key_segments": {
+        "type": "array",
+        "items": object(json!({ "at_secs": { "type": "number" }, "text": { "type": "string" } }), &["at_secs", "text"]),
+      },
+      "qa_pairs": {
+        "type": "array",
+        "items": object(
+          json!({
+            "asked_at_secs": { "type": "number" },
+            "question": { "type": "string" },
+            "answer": { "type": "string" },
+            "answer_start_secs": { "type": "number" },
+            "answer_end_secs": { "type": "number" },
+          }),
+          &["asked_at_secs", "question", "answer", "answer_start_secs", "answer_end_secs"],
+        ),
+      },
+    }),
+    &[
+      "time_range",
+      "total_utterances",
+      "suppressed_utterances",
+      "filler_ratio",
+      "non_speech_secs",
+      "abstract",
+      "keywords",
+      "topics",
+      "key_segments",
+      "qa_pairs",
+    ],
+  )
+}
+
+/// `llm::LlmSummary` (present only with `--llm`).
+fn llm_summary() -> Value {
+  object(
+    json!({
+      "model": { "type": "string" },
+      "abstract": { "type": "string" },
+      "takeaways": { "type": "array", "items": { "type": "string" } },
+      "topic_titles": {
+        "type": "array",
+        "items": object(
+          json!({ "start_secs": { "type": "number" }, "title": { "type": "string" } }),
+          &["start_secs", "title"],
+        ),
+      },
+      "requests": { "type": "integer" },
+    }),
+    &["model", "abstract", "takeaways", "topic_titles", "requests"],
+  )
+}
+
+/// `summarize::SummaryOutput`: the `yp summarize` document.
+pub fn summary_output() -> Value {
+  object(
+    json!({
+      "_hint": { "type": "string" },
+      "video": video_details(),
+      "summary": summary_stats(),
+      "llm": llm_summary(),
+      "utterances": { "type": "array", "items": classified_utterance() },
+    }),
+    &["_hint", "video", "summary", "utterances"],
+  )
+}
+
+/// `yp summarize --raw`: metadata plus the unprocessed transcript.
+fn raw_transcript_document() -> Value {
+  object(
+    json!({
+      "_hint": { "type": "string" },
+      "video": video_details(),
+      "utterances": { "type": "array", "items": raw_utterance() },
+    }),
+    &["_hint", "video", "utterances"],
+  )
+}
+
+/// `chapters::Chapter`.
+fn chapter() -> Value {
+  object(
+    json!({
+      "start_secs": { "type": "number" },
+      "end_secs": { "type": "number" },
+      "timestamp": { "type": "string", "description": "m:ss or h:mm:ss start" },
+      "title": { "type": "string" },
+    }),
+    &["start_secs", "end_secs", "timestamp", "title"],
+  )
+}
+
+/// `digest::Digest`.
+fn digest() -> Value {
+  let strings = json!({ "type": "array", "items": { "type": "string" } });
+  object(
+    json!({
+      "_hint": { "type": "string" },
+      "channel": { "type": "string" },
+      "generated_at": { "type": "string", "description": "RFC 3339" },
+      "video_count": { "type": "integer" },
+      "date_range": {
+        "type": ["array", "null"],
+        "items": { "type": "string" },
+        "minItems": 2,
+        "maxItems": 2,
+        "description": "Oldest and newest upload date",
+      },
+      "themes": {
+        "type": "array",
+        "items": object(
+          json!({
+            "phrase": { "type": "string" },
+            "videos": { "type": "integer" },
+            "score": { "type": "number" },
+            "related": {
+              "type": "array",
+              "items": object(
+                json!({
+                  "video_id": { "type": "string" },
+                  "title": { "type": "string" },
+                  "relevance": { "type": "number" },
+                  "at_secs": { "type": "number" },
+                  "url": { "type": "string" },
+                }),
+                &["video_id", "title", "relevance", "at_secs"],
+              ),
+            },
+          }),
+          &["phrase", "videos", "score", "related"],
+        ),
+      },
+      "timeline": {
+        "type": "array",
+        "items": object(
+          json!({
+            "date": nullable("string"),
+            "video_id": { "type": "string" },
+            "title": { "type": "string" },
+            "themes": strings,
+          }),
+          &["date", "video_id", "title", "themes"],
+        ),
+      },
+      "videos": {
+        "type": "array",
+        "items": object(
+          json!({
+            "video_id": { "type": "string" },
+            "title": { "type": "string" },
+            "url": { "type": "string" },
+            "upload_date": nullable("string"),
+            "duration": nullable("string"),
+            "one_liner": { "type": "string" },
+            "keywords": strings,
+            "themes": strings,
+          }),
+          &["video_id", "title", "url", "upload_date", "duration", "one_liner", "keywords", "themes"],
+        ),
+      },
+    }),
+    &["_hint", "channel", "generated_at", "video_count", "date_range", "themes", "timeline", "videos"],
+  )
+}
+
+/// `index::Hit`.
+fn hit() -> Value {
+  object(
+    json!({
+      "video_id": { "type": "string" },
+      "title": nullable("string"),
+      "at_secs": { "type": "number" },
+      "timestamp": { "type": "string" },
+      "snippet": { "type": "string" },
+      "url": { "type": "string" },
+    }),
+    &["video_id", "title", "at_secs", "timestamp", "snippet", "url"],
+  )
+}
+
+/// `clip::Clip`.
+fn clip() -> Value {
+  object(
+    json!({
+      "file": { "type": "string" },
+      "from_secs": { "type": "number" },
+      "to_secs": { "type": "number" },
+      "text": { "type": "string", "description": "Key segment text (--key-segments only)" },
+    }),
+    &["file", "from_secs", "to_secs"],
+  )
+}
+
+/// Per-video record of a multi-video transcript run (stdin pipe or `transcribe-batch`).
+fn transcript_record() -> Value {
+  object(
+    json!({
+      "video_id": { "type": "string" },
+      "status": { "type": "string", "enum": ["ok", "error"] },
+      "utterances": { "type": "array", "items": { "oneOf": [classified_utterance(), raw_utterance()] } },
+      "error": error_detail(),
+    }),
+    &["video_id", "status"],
+  )
+}
+
+/// Per-video record of a summarize run over piped IDs or `--latest`.
+fn summary_record() -> Value {
+  object(
+    json!({
+      "video_id": { "type": "string" },
+      "status": { "type": "string", "enum": ["ok", "error"] },
+      "summary": { "oneOf": [with_version(summary_output()), with_version(raw_transcript_document())] },
+      "error": error_detail(),
+    }),
+    &["video_id", "status"],
+  )
+}
+
+/// `transcribe-batch` report (`report.json` with `--out-dir`).
+fn batch_report() -> Value {
+  object(
+    json!({
+      "total": { "type": "integer" },
+      "succeeded": { "type": "integer" },
+      "failed": { "type": "integer" },
+      "finished_at": { "type": "string", "description": "RFC 3339" },
+      "videos": {
+        "type": "array",
+        "items": object(
+          json!({
+            "video_id": { "type": "string" },
+            "status": { "type": "string", "enum": ["ok", "error"] },
+            "segments": { "type": "integer" },
+            "error": error_detail(),
+          }),
+          &["video_id", "status"],
+        ),
+      },
+    }),
+    &["total", "succeeded", "failed", "finished_at", "videos"],
+  )
+}
+
+/// `yp watch` events, one per line.
+fn watch_events() -> Vec<(&'static str, Value)> {
+  let event = |name: &str, mut properties: Value, required: &[&str]| {
+    properties["event"] = json!({ "type": "string", "const": name });
+    properties["channel"] = json!({ "type": "string", "description": "As given on the command line or in [watch]" });
+    let required: Vec<&str> = ["event", "channel"].iter().chain(required).copied().collect();
+    with_version(object(properties, &required))
+  };
+  vec![
+    (
+      "New video",
+      event(
+        "new_video",
+        json!({ "detected_at": { "type": "string", "description": "RFC 3339" }, "video": video_entry() }),
+        &["detected_at", "video"],
+      ),
+    ),
+    (
+      "Transcript",
+      event(
+        "transcript",
+        json!({
+          "video_id": { "type": "string" },
+          "segments": { "type": "integer" },
+          "file": { "type": "string", "description": "With --out-dir" },
+        }),
+        &["video_id", "segments"],
+      ),
+    ),
+    (
+      "Summary",
+      event(
+        "summary",
+        json!({
+          "video_id": { "type": "string" },
+          "summary": with_version(summary_output()),
+          "file": { "type": "string", "description": "With --out-dir, instead of summary" },
+        }),
+        &["video_id"],
+      ),
+    ),
+    (
+      "Watch error",
+      event(
+        "error",
+        json!({ "video_id": { "type": "string", "description": "Absent when listing the channel failed" }, "error": error_detail() }),
+        &["error"],
+      ),
+    ),
+  ]
+}
+
+/// `{code, message}` of a failure; see `error::ErrorKind`.
+fn error_detail() -> Value {
+  let codes: Vec<&str> = ErrorKind::ALL.iter().map(|k| k.code()).collect();
+  object(
+    json!({
+      "code": { "type": "string", "enum": codes },
+      "message": { "type": "string" },
+    }),
+    &["code", "message"],
+  )
+}
+
+/// Add the `schema_version` property to an object schema.
+fn with_version(mut schema: Value) -> Value {
+  schema["properties"]["schema_version"] = json!({ "type": "integer", "const": SCHEMA_VERSION });
+  if let Some(required) = schema["required"].as_array_mut() {
+    required.insert(0, json!("schema_version"));
+  }
+  schema
+}
+
+/// Commands with JSON output, for `yp schema`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SchemaCommand {
+  Search,
+  Channel,
+  Info,
+  Transcript,
+  Summarize,
+  Chapters,
+  Clip,
+  Digest,
+  Grep,
+  Watch,
+  TranscribeBatch,
+  Jobs,
+  Boilerplate,
+}
+
+impl SchemaCommand {
+  /// The command name as typed on the command line.
+  pub fn name(self) -> String {
+    clap::ValueEnum::to_possible_value(&self).map(|v| v.get_name().to_string()).unwrap_or_default()
+  }
+}
+
+fn titled(title: &str, mut schema: Value) -> Value {
+  schema["title"] = json!(title);
+  schema
+}
+
+/// Alternatives a command may print, each titled.
+fn one_of(alternatives: Vec<(&str, Value)>) -> Value {
+  let alternatives: Vec<Value> = alternatives.into_iter().map(|(title, schema)| titled(title, schema)).collect();
+  json!({ "oneOf": alternatives })
+}
+
+/// The JSON Schema of one command's output. JSONL commands describe one line.
+pub fn command_schema(command: SchemaCommand) -> Value {
+  let (output, description, mut schema) = match command {
+    SchemaCommand::Search => {
+      ("json", "Array of matching videos.", json!({ "type": "array", "items": with_version(video_entry()) }))
+    }
+    SchemaCommand::Channel => ("jsonl", "One video per line, newest first.", with_version(video_entry())),
+    SchemaCommand::Info => ("json", "Metadata of one video.", {
+      let mut info = video_details();
+      info["properties"]["video_id"] = json!({ "type": "string" });
+      if let Some(required) = info["required"].as_array_mut() {
+        required.insert(0, json!("video_id"));
+      }
+      with_version(info)
+    }),
+    SchemaCommand::Transcript => (
+      "jsonl",
+      "One utterance per line (classified, or raw with --raw); one record per video when several are piped in.",
+      one_of(vec![
+        ("Classified utterance", with_version(classified_utterance())),
+        ("Raw utterance", with_version(raw_utterance())),
+        ("Video record", with_version(transcript_record())),
+      ]),
+    ),
+    SchemaCommand::Summarize => (
+      "json",
+      "The summary document (or the raw transcript with --raw); one JSONL record per video for \
+       several videos (piped in or --latest N).",
+      one_of(vec![
+        ("Summary", with_version(summary_output())),
+        ("Raw transcript", with_version(raw_transcript_document())),
+        ("Video record", with_version(summary_record())),
+      ]),
+    ),
+    SchemaCommand::Chapters => (
+      "json",
+      "Chapters drafted from topic segments, with the paste-ready description.",
+      with_version(object(
+        json!({
+          "_hint": { "type": "string" },
+          "video": video_details(),
+          "chapters": { "type": "array", "items": chapter() },
+          "description": { "type": "string" },
+        }),
+        &["_hint", "video", "chapters", "description"],
+      )),
+    ),
+    SchemaCommand::Clip => ("jsonl", "One exported clip per line.", with_version(clip())),
+    SchemaCommand::Digest => {
+      ("json", "Channel digest: themes, timeline and one line per video.", with_version(digest()))
+    }
+    SchemaCommand::Grep => ("jsonl", "One transcript hit per line, most recent videos first.", with_version(hit())),
+    SchemaCommand::Watch => (
+      "jsonl",
+      "One event per line: each new upload, then its transcript or summary with --transcribe/--summarize.",
+      one_of(watch_events()),
+    ),
+    SchemaCommand::TranscribeBatch => (
+      "jsonl",
+      "One record per video as it finishes; with --out-dir, report.json holds the report instead.",
+      one_of(vec![("Video record", with_version(transcript_record())), ("Report", with_version(batch_report()))]),
+    ),
+    SchemaCommand::Jobs => (
+      "jsonl",
+      "One interrupted transcription per line.",
+      with_version(object(
+        json!({
+          "video_id": { "type": "string" },
+          "title": nullable("string"),
+          "url": { "type": "string" },
+          "offset_secs": { "type": "integer", "description": "Audio transcribed so far" },
+          "utterances": { "type": "integer", "description": "Utterances stored so far" },
+          "updated_at": { "type": "string", "description": "RFC 3339" },
+        }),
+        &["video_id", "title", "url", "offset_secs", "utterances", "updated_at"],
+      )),
+    ),
+    SchemaCommand::Boilerplate => (
+      "json",
+      "The learned intro/outro model, summarized.",
+      with_version(object(
+        json!({
+          "channel": { "type": "string" },
+          "uploader": nullable("string"),
+          "videos": { "type": "integer", "description": "Transcripts the model was built from" },
+          "shingles": { "type": "integer" },
+          "phrases": {
+            "type": "array",
+            "items": object(json!({ "text": { "type": "string" }, "videos": { "type": "integer" } }), &["text", "videos"]),
+          },
+          "built_at": { "type": "string", "description": "RFC 3339" },
+        }),
+        &["channel", "uploader", "videos", "shingles", "phrases", "built_at"],
+      )),
+    ),
+  };
+
+  // A failed command prints its error as the last line of stdout instead.
+  let error = with_version(object(json!({ "error": error_detail() }), &["error"]));
+  match schema.get_mut("oneOf").and_then(Value::as_array_mut) {
+    Some(alternatives) => alternatives.push(titled("Error", error)),
+    None => schema = one_of(vec![("Result", schema), ("Error", error)]),
+  }
+
+  let name = command.name();
+  let mut document = json!({
+    "$schema": "https://json-schema.org/draft/2020-12/schema",
+    "$id": format!("urn:yp:schema:{SCHEMA_VERSION}:{name}"),
+    "title": format!("yp {name}"),
+    "description": description,
+    "x-yp-output": output,
+  });
+  if let (Some(doc), Some(body)) = (document.as_object_mut(), schema.as_object_mut()) {
+    doc.append(body);
+  }
+  document
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::chapters::Chapter;
+  use crate::clip::Clip;
+  use crate::index::Hit;
+  use crate::keywords::Keyword;
+  use crate::llm::{LlmSummary, TopicTitle};
+  use crate::summarize::{ClassifiedUtterance, KeySegment, QaPair, SummaryOutput, TopicSegment, UtteranceClass};
+
+  /// Whether `value` is of the JSON Schema type `t`.
+  fn has_type(value: &Value, t: &str) -> bool {
+    match t {
+      "string" => value.is_string(),
+      "integer" => value.is_i64() || value.is_u64(),
+      "number" => value.is_number(),
+      "boolean" => value.is_boolean(),
+      "array" => value.is_array(),
+      "object" => value.is_object(),
+      "null" => value.is_null(),
+      _ => false,
+    }
+  }
+
+  /// Every field of `value` is a schema property, every required property is present,
+  /// `type`s, `enum`s and `const`s hold, and nested objects/arrays match recursively.
+  /// `oneOf` needs one match.
+  fn check(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    if let Some(alternatives) = schema["oneOf"].as_array() {
+      let errors: Vec<String> = alternatives.iter().filter_map(|alt| check(alt, value, path).err()).collect();
+      return if errors.len() < alternatives.len() { Ok(()) } else { Err(errors.join("; ")) };
+    }
+    if let Some(expected) = schema.get("const")
+      && expected != value
+    {
+      return Err(format!("{path}: expected {expected}, got {value}"));
+    }
+    if let Some(expected) = schema.get("type") {
+      let types: Vec<&str> = match expected {
+        Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+        other => other.as_str().into_iter().collect(),
+      };
+      if !types.iter().any(|t| has_type(value, t)) {
+        return Err(format!("{path}: expected type {expected}, got {value}"));
+      }
+    }
+    if let Some(allowed) = schema["enum"].as_array()
+      && !allowed.contains(value)
+    {
+      return Err(format!("{path}: {value} is not one of {}", schema["enum"]));
+    }
+    match value {
+      Value::Object(map) => {
+        let props = schema["properties"].as_object().ok_or_else(|| format!("{path}: schema has no properties"))?;
+        for (key, v) in map {
+          let prop = props.get(key).ok_or_else(|| format!("{path}.{key}: missing from schema"))?;
+          check(prop, v, &format!("{path}.{key}"))?;
+        }
+        for key in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+          if !map.contains_key(key) {
+            return Err(format!("{path}.{key}: required but not serialized"));
+          }
+        }
+        Ok(())
+      }
+      Value::Array(items) => {
+        let item_schema = schema.get("items").ok_or_else(|| format!("{path}: schema has no items"))?;
+        items.iter().enumerate().try_for_each(|(i, item)| check(item_schema, item, &format!("{path}[{i}]")))
+      }
+      _ => Ok(()),
+    }
+  }
+
+  fn assert_matches(schema: &Value, value: &impl Serialize, path: &str) {
+    let value = serde_json::to_value(value).unwrap();
+    if let Err(e) = check(schema, &value, path) {
+      panic!("{e}");
+    }
+  }
+
+  /// The reverse of `check`: every property the schema lists is serialized,
+  /// recursing into objects and array items. With `check`, a fully populated
+  /// value catches drift between a struct and its hand-written schema either way.
+  fn assert_serialized(schema: &Value, value: &Value, path: &str) {
+    if let Some(props) = schema["properties"].as_object() {
+      for (key, prop) in props {
+        let field = value.get(key).unwrap_or_else(|| panic!("{path}.{key}: in the schema but not serialized"));
+        assert_serialized(prop, field, &format!("{path}.{key}"));
+      }
+    }
+    if let (Some(items), Some(values)) = (schema.get("items"), value.as_array()) {
+      for (i, item) in values.iter().enumerate() {
+        assert_serialized(items, item, &format!("{path}[{i}]"));
+      }
+    }
+  }
+
+  fn keyword() -> Keyword {
+    Keyword { phrase: "glaze".to_string(), score: 1.0, first_at_secs: 3.0 }
+  }
+
+  fn details() -> VideoDetails {
+    VideoDetails {
+      url: "https://youtube.com/watch?v=abc".to_string(),
+      title: "Test".to_string(),
+      uploader: Some("Painter".to_string()),
+      duration: Some("2:08".to_string()),
+      upload_date: None,
+      view_count: None,
+      tags: vec!["art".to_string()],
+    }
+  }
+
+  fn summary() -> SummaryOutput {
+    let mut output = SummaryOutput::fixture("Test", 128.0);
+    output.video = details();
+    let stats = &mut output.summary;
+    stats.total_utterances = 1;
+    stats.abstract_text = "A.".to_string();
+    stats.keywords = vec![keyword()];
+    stats.topics = vec![TopicSegment {
+      start_secs: 0.0,
+      end_secs: 1.0,
+      summary: "A.".to_string(),
+      utterance_count: 1,
+      keywords: vec![keyword()],
+    }];
+    stats.key_segments = vec![KeySegment { at_secs: 0.0, text: "A.".to_string() }];
+    stats.qa_pairs = vec![QaPair {
+      asked_at_secs: 0.0,
+      question: "Why?".to_string(),
+      answer: "Because.".to_string(),
+      answer_start_secs: 1.0,
+      answer_end_secs: 2.0,
+    }];
+    output.llm = Some(LlmSummary {
+      model: "m".to_string(),
+      abstract_text: "A.".to_string(),
+      takeaways: vec!["T".to_string()],
+      topic_titles: vec![TopicTitle { start_secs: 0.0, title: "T".to_string() }],
+      requests: 1,
+    });
+    output.utterances =
+      vec![ClassifiedUtterance { start: 0.0, end: 1.0, text: "A.".to_string(), class: UtteranceClass::KeySegment }];
+    output
+  }
+
+  #[test]
+  fn video_entry_merges_meta_and_splits_tags() {
+    let entry = SearchEntry {
+      title: "Test".to_string(),
+      video_id: "abc".to_string(),
+      upload_date: Some("2024-03-01".to_string()),
+      tags: Some("art, glaze".to_string()),
+      duration: None,
+      view_count: None,
+      uploader: None,
+      enriched: false,
+    };
+    let meta = VideoMeta {
+      video_id: "abc".to_string(),
+      upload_date: None,
+      tags: None,
+      duration: Some("2:08".to_string()),
+      view_count: None,
+      uploader: Some("Painter".to_string()),
+    };
+    let video = VideoEntry::new(&entry, Some(&meta));
+    assert_eq!(video.tags, vec!["art", "glaze"]);
+    assert_eq!(video.duration.as_deref(), Some("2:08"));
+    assert_eq!(video.upload_date.as_deref(), Some("2024-03-01"));
+    assert_matches(&command_schema(SchemaCommand::Channel), &versioned(&video), "VideoEntry");
+    assert_matches(&command_schema(SchemaCommand::Search), &vec![versioned(&video)], "search");
+  }
+
+  #[test]
+  fn versioned_outputs_match_command_schemas() {
+    let details = details();
+    let info = VideoInfo { video_id: "abc", details: &details };
+    assert_matches(&command_schema(SchemaCommand::Info), &versioned(&info), "info");
+    assert_matches(&command_schema(SchemaCommand::Summarize), &versioned(&summary()), "summarize");
+    assert_matches(
+      &command_schema(SchemaCommand::Chapters),
+      &versioned(&json!({
+        "_hint": "",
+        "video": details,
+        "chapters": [Chapter { start_secs: 0.0, end_secs: 10.0, timestamp: "0:00".to_string(), title: "Intro".to_string() }],
+        "description": "0:00 Intro\n",
+      })),
+      "chapters",
+    );
+    let clip = Clip { file: "a.m4a".into(), from_secs: 1.0, to_secs: 2.0, text: None };
+    assert_matches(&command_schema(SchemaCommand::Clip), &versioned(&clip), "clip");
+    let hit = Hit {
+      video_id: "abc".to_string(),
+      title: None,
+      at_secs: 3.0,
+      timestamp: "0:03".to_string(),
+      snippet: "glaze".to_string(),
+      url: "https://youtube.com/watch?v=abc&t=3s".to_string(),
+    };
+    assert_matches(&command_schema(SchemaCommand::Grep), &versioned(&hit), "grep");
+    let entry = SearchEntry {
+      title: "Test".to_string(),
+      video_id: "abc".to_string(),
+      upload_date: None,
+      tags: None,
+      duration: None,
+      view_count: None,
+      uploader: None,
+      enriched: false,
+    };
+    let new_video = json!({
+      "event": "new_video",
+      "channel": "@a",
+      "detected_at": "2024-03-01T12:00:00+00:00",
+      "video": VideoEntry::new(&entry, None),
+    });
+    assert_matches(&command_schema(SchemaCommand::Watch), &versioned(&new_video), "watch");
+    let summary = json!({ "event": "summary", "channel": "@a", "video_id": "abc", "summary": versioned(&summary()) });
+    assert_matches(&command_schema(SchemaCommand::Watch), &versioned(&summary), "watch");
+  }
+
+  #[test]
+  fn structs_and_schemas_agree_both_ways() {
+    let entry = SearchEntry {
+      title: "Test".to_string(),
+      video_id: "abc".to_string(),
+      upload_date: Some("2024-03-01".to_string()),
+      tags: Some("art, glaze".to_string()),
+      duration: Some("2:08".to_string()),
+      view_count: Some("1,024".to_string()),
+      uploader: Some("Painter".to_string()),
+      enriched: true,
+    };
+    let details =
+      VideoDetails { upload_date: Some("2024-03-01".to_string()), view_count: Some("1,024".to_string()), ..details() };
+    let cases = [
+      ("VideoEntry", video_entry(), serde_json::to_value(VideoEntry::new(&entry, None)).unwrap()),
+      ("VideoDetails", video_details(), serde_json::to_value(&details).unwrap()),
+      ("SummaryOutput", summary_output(), serde_json::to_value(summary()).unwrap()),
+    ];
+    for (name, schema, value) in cases {
+      if let Err(e) = check(&schema, &value, name) {
+        panic!("{e}");
+      }
+      assert_serialized(&schema, &value, name);
+    }
+  }
+
+  #[test]
+  fn leaf_types_and_items_are_checked() {
+    let schema = video_entry();
+    let mut entry = serde_json::to_value(VideoEntry::new(
+      &SearchEntry {
+        title: "Test".to_string(),
+        video_id: "abc".to_string(),
+        upload_date: None,
+        tags: Some("art".to_string()),
+        duration: None,
+        view_count: None,
+        uploader: None,
+        enriched: false,
+      },
+      None,
+    ))
+    .unwrap();
+    assert!(check(&schema, &entry, "VideoEntry").is_ok());
+    entry["tags"] = json!("art");
+    assert!(check(&schema, &entry, "VideoEntry").is_err());
+    entry["tags"] = json!([1]);
+    assert!(check(&schema, &entry, "VideoEntry").is_err());
+    entry["tags"] = json!([]);
+    entry["uploader"] = json!(3);
+    assert!(check(&schema, &entry, "VideoEntry").is_err());
+    let class = &classified_utterance()["properties"]["class"];
+    assert!(check(class, &json!("normal"), "class").is_ok());
+    assert!(check(class, &json!("chatter"), "class").is_err());
+  }
+
+  #[test]
+  fn versions_are_checked() {
+    let mut entry = serde_json::to_value(versioned(&VideoInfo { video_id: "abc", details: &details() })).unwrap();
+    entry["schema_version"] = json!(SCHEMA_VERSION + 1);
+    assert!(check(&command_schema(SchemaCommand::Info), &entry, "info").is_err());
+  }
+
+  #[test]
+  fn utterance_classes_are_enumerated() {
+    let classes = [
+      UtteranceClass::NonSpeech,
+      UtteranceClass::Filler,
+      UtteranceClass::Repetition,
+      UtteranceClass::Boilerplate,
+      UtteranceClass::TopicShift,
+      UtteranceClass::Question,
+      UtteranceClass::KeySegment,
+      UtteranceClass::Normal,
+    ];
+    let schema = classified_utterance();
+    let allowed = schema["properties"]["class"]["enum"].as_array().unwrap();
+    assert_eq!(allowed.len(), classes.len());
+    for class in classes {
+      assert!(allowed.contains(&serde_json::to_value(class).unwrap()));
+    }
+  }
+
+  /// Every command's schema equals the copy recorded for this `SCHEMA_VERSION`.
+  /// Missing copies are written with `YP_UPDATE_GOLDEN=1`; existing ones never
+  /// are, so a changed shape needs a version bump.
+  #[test]
+  fn schemas_match_recorded_version() {
+    use clap::ValueEnum;
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("testdata/schemas/v{SCHEMA_VERSION}"));
+    for &command in SchemaCommand::value_variants() {
+      let path = dir.join(format!("{}.json", command.name()));
+      let actual = command_schema(command);
+      match std::fs::read_to_string(&path) {
+        Ok(recorded) => {
+          let recorded: Value = serde_json::from_str(&recorded).unwrap();
+          assert!(
+            recorded == actual,
+            "The output schema of `yp {}` changed. Bump SCHEMA_VERSION and record the new version \
+             with YP_UPDATE_GOLDEN=1 cargo test.",
+            command.name()
+          );
+        }
+        Err(_) if std::env::var_os("YP_UPDATE_GOLDEN").is_some() => {
+          std::fs::create_dir_all(&dir).unwrap();
+          std::fs::write(&path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+        }
+        Err(_) => panic!("No recorded schema at {}; run with YP_UPDATE_GOLDEN=1", path.display()),
+      }
+    }
+  }
+}
@@ -0,0 +1,296 @@
+//! Sentence and paragraph reconstruction from whisper fragments.
+//!
+//! Whisper cuts utterances at arbitrary points (chunk edges, breaths), so a
+//! single thought is often spread over several fragments and one fragment may
+//! end one sentence and start the next. This stage runs between transcription
+//! and `summarize::classify`:
+//!
+//! 1. **Split** — break fragments at internal sentence boundaries, splitting the
+//!    time range proportionally to text length
+//! 2. **Merge** — join pieces into sentences using terminal punctuation, the
+//!    capitalization of the next piece, and the pause between them
+//! 3. **Group** — optionally collect sentences into paragraphs at long pauses
+//!
+//! Timestamps stay in whisper centiseconds throughout.
+
+use crate::summarize;
+
+/// Pause (cs) that ends a sentence regardless of punctuation.
+const LONG_PAUSE_CS: i64 = 200;
+
+/// Pause (cs) that, together with a capitalized next piece, ends an unpunctuated sentence.
+const SHORT_PAUSE_CS: i64 = 80;
+
+/// Hard cap on sentence length so unpunctuated transcripts don't merge into one blob.
+const MAX_SENTENCE_CS: i64 = 3000;
+const MAX_SENTENCE_WORDS: usize = 80;
+
+/// Pause (cs) between sentences that starts a new paragraph.
+const PARAGRAPH_PAUSE_CS: i64 = 250;
+
+/// Paragraph size caps (sentences, duration in cs).
+const MAX_PARAGRAPH_SENTENCES: usize = 6;
+const MAX_PARAGRAPH_CS: i64 = 6000;
+
+/// Abbreviations whose trailing period does not end a sentence.
+const ABBREVIATIONS: &[&str] = &["mr", "mrs", "ms", "dr", "prof", "st", "vs", "etc", "e.g", "i.e", "approx", "no"];
+
+/// A run of consecutive sentences, used for readable transcript views and exports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paragraph {
+  pub start: i64,
+  pub stop: i64,
+  pub sentences: Vec<String>,
+}
+
+impl Paragraph {
+  /// The paragraph as a single line of text.
+  pub fn text(&self) -> String {
+    self.sentences.join(" ")
+  }
+}
+
+/// Whether `text` ends with sentence-terminal punctuation (allowing closing quotes/brackets).
+fn ends_sentence(text: &str) -> bool {
+  let trimmed = text.trim_end().trim_end_matches(['"', '\'', ')', ']', '”', '’']);
+  let Some(last) = trimmed.chars().last() else { return false };
+  if !matches!(last, '.' | '!' | '?' | '…') {
+    return false;
+  }
+  if last == '.' {
+    let word = trimmed[..trimmed.len() - 1].rsplit(char::is_whitespace).next().unwrap_or("");
+    if ABBREVIATIONS.contains(&word.to_lowercase().as_str()) {
+      return false;
+    }
+  }
+  true
+}
+
+/// Whether `text` starts like a new sentence: an uppercase letter (other than a lone "I"), digit, or quote.
+fn starts_sentence(text: &str) -> bool {
+  let trimmed = text.trim_start();
+  let Some(first) = trimmed.chars().next() else { return false };
+  if first == 'I' && trimmed.chars().nth(1).is_none_or(|c| !c.is_alphabetic()) {
+    return false;
+  }
+  first.is_uppercase() || first.is_ascii_digit() || matches!(first, '"' | '“')
+}
+
+/// Split one fragment at internal sentence boundaries, interpolating timestamps by character count.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn split_fragment(start: i64, stop: i64, text: &str) -> Vec<(i64, i64, String)> {
+  let text = text.trim();
+  let mut cuts: Vec<usize> = Vec::new();
+  for (i, c) in text.char_indices() {
+    if c.is_whitespace() && ends_sentence(&text[..i]) && starts_sentence(&text[i..]) {
+      cuts.push(i);
+    }
+  }
+  if cuts.is_empty() {
+    return vec![(start, stop, text.to_string())];
+  }
+
+  let total_chars = text.chars().count().max(1) as i64;
+  let span = (stop - start).max(0);
+  let mut pieces = Vec::with_capacity(cuts.len() + 1);
+  let mut piece_start_byte = 0;
+  let mut piece_start_cs = start;
+  for end_byte in cuts.into_iter().chain(std::iter::once(text.len())) {
+    let chars_so_far = text[..end_byte].chars().count() as i64;
+    let piece_stop_cs = if end_byte == text.len() { stop } else { start + span * chars_so_far / total_chars };
+    let piece = text[piece_start_byte..end_byte].trim();
+    if !piece.is_empty() {
+      pieces.push((piece_start_cs, piece_stop_cs, piece.to_string()));
+    }
+    piece_start_byte = end_byte;
+    piece_start_cs = piece_stop_cs;
+  }
+  pieces
+}
+
+/// Merge whisper fragments into sentences with merged time ranges.
+///
+/// Input and output are `(start_cs, stop_cs, text)` triples, so the result can
+/// be passed straight to `summarize::classify`. Non-speech fragments (`[Music]`,
+/// `♪`) are never merged with speech and pass through on their own.
+pub fn sentences(fragments: &[(i64, i64, String)]) -> Vec<(i64, i64, String)> {
+  let pieces: Vec<(i64, i64, String)> =
+    fragments.iter().flat_map(|(start, stop, text)| split_fragment(*start, *stop, text)).collect();
+
+  let mut out: Vec<(i64, i64, String)> = Vec::new();
+  let mut current: Option<(i64, i64, String)> = None;
+
+  for (i, (start, stop, text)) in pieces.iter().enumerate() {
+    if summarize::is_non_speech(text) {
+      out.extend(current.take());
+      out.push((*start, *stop, text.clone()));
+      continue;
+    }
+
+    let sentence = match current.take() {
+      Some((s, _, mut t)) => {
+        t.push(' ');
+        t.push_str(text);
+        (s, *stop, t)
+      }
+      None => (*start, *stop, text.clone()),
+    };
+
+    let boundary = match pieces.get(i + 1) {
+      None => true,
+      Some((next_start, _, next_text)) => {
+        let pause = next_start - stop;
+        let punctuated = ends_sentence(&sentence.2);
+        let capitalized = starts_sentence(next_text);
+        pause >= LONG_PAUSE_CS
+          || (punctuated && (capitalized || pause >= SHORT_PAUSE_CS))
+          || (capitalized && pause >= SHORT_PAUSE_CS)
+          || sentence.1 - sentence.0 >= MAX_SENTENCE_CS
+          || sentence.2.split_whitespace().count() >= MAX_SENTENCE_WORDS
+          || summarize::is_non_speech(next_text)
+      }
+    };
+
+    if boundary {
+      out.push(sentence);
+    } else {
+      current = Some(sentence);
+    }
+  }
+  out.extend(current);
+  out
+}
+
+/// Group sentences into paragraphs, breaking at long pauses and size caps.
+///
+/// Non-speech sentences end the current paragraph and are dropped.
+pub fn paragraphs(sentences: &[(i64, i64, String)]) -> Vec<Paragraph> {
+  let mut out: Vec<Paragraph> = Vec::new();
+  let mut current: Option<Paragraph> = None;
+
+  for (start, stop, text) in sentences {
+    if summarize::is_non_speech(text) {
+      out.extend(current.take());
+      continue;
+    }
+    if let Some(p) = current.as_mut()
+      && start - p.stop < PARAGRAPH_PAUSE_CS
+      && p.sentences.len() < MAX_PARAGRAPH_SENTENCES
+      && stop - p.start <= MAX_PARAGRAPH_CS
+    {
+      p.stop = *stop;
+      p.sentences.push(text.trim().to_string());
+      continue;
+    }
+    out.extend(current.take());
+    current = Some(Paragraph { start: *start, stop: *stop, sentences: vec![text.trim().to_string()] });
+  }
+  out.extend(current);
+  out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn frag(start: i64, stop: i64, text: &str) -> (i64, i64, String) {
+    (start, stop, text.to_string())
+  }
+
+  #[test]
+  fn merges_mid_sentence_fragments() {
+    let input = vec![frag(0, 200, "So the first thing we"), frag(210, 400, "need to do is mix the paint.")];
+    let out = sentences(&input);
+    assert_eq!(out, vec![frag(0, 400, "So the first thing we need to do is mix the paint.")]);
+  }
+
+  #[test]
+  fn splits_fragment_with_two_sentences() {
+    let pieces = split_fragment(0, 1000, "That is done. Now we start");
+    assert_eq!(pieces.len(), 2);
+    assert_eq!(pieces[0].2, "That is done.");
+    assert_eq!(pieces[1].2, "Now we start");
+    assert_eq!(pieces[0].1, pieces[1].0);
+    assert_eq!(pieces[1].1, 1000);
+  }
+
+  #[test]
+  fn sentence_spans_fragment_boundary_after_split() {
+    let input = vec![frag(0, 1000, "That is done. Now we start"), frag(1010, 1500, "the second layer.")];
+    let out = sentences(&input);
+    assert_eq!(out.len(), 2);
+    assert_eq!(out[0].2, "That is done.");
+    assert_eq!(out[1].2, "Now we start the second layer.");
+    assert_eq!(out[1].1, 1500);
+  }
+
+  #[test]
+  fn abbreviation_does_not_end_sentence() {
+    assert!(!ends_sentence("I asked Dr."));
+    assert!(ends_sentence("It works."));
+    assert!(ends_sentence("Really?\""));
+  }
+
+  #[test]
+  fn lone_i_is_not_a_sentence_start() {
+    assert!(!starts_sentence("I think so"));
+    assert!(starts_sentence("It is"));
+    assert!(!starts_sentence("and then"));
+  }
+
+  #[test]
+  fn long_pause_ends_unpunctuated_sentence() {
+    let input = vec![frag(0, 200, "and that is it"), frag(500, 700, "and then more")];
+    assert_eq!(sentences(&input).len(), 2);
+  }
+
+  #[test]
+  fn capitalized_after_pause_ends_unpunctuated_sentence() {
+    let input = vec![frag(0, 200, "and that is it"), frag(300, 500, "Next we paint")];
+    assert_eq!(sentences(&input).len(), 2);
+  }
+
+  #[test]
+  fn non_speech_is_kept_separate() {
+    let input = vec![frag(0, 100, "We begin"), frag(100, 300, "[Music]"), frag(300, 400, "right here.")];
+    let out = sentences(&input);
+    assert_eq!(out.len(), 3);
+    assert_eq!(out[1].2, "[Music]");
+  }
+
+  #[test]
+  fn unpunctuated_run_is_capped() {
+    let input: Vec<_> = (0..40).map(|i| frag(i * 100, i * 100 + 100, "one two three")).collect();
+    let out = sentences(&input);
+    assert!(out.len() > 1);
+    assert!(out.iter().all(|s| s.1 - s.0 <= MAX_SENTENCE_CS));
+  }
+
+  #[test]
+  fn paragraphs_break_on_pause() {
+    let input = vec![frag(0, 100, "One."), frag(110, 200, "Two."), frag(600, 700, "Three.")];
+    let paras = paragraphs(&input);
+    assert_eq!(paras.len(), 2);
+    assert_eq!(paras[0].text(), "One. Two.");
+    assert_eq!((paras[0].start, paras[0].stop), (0, 200));
+    assert_eq!(paras[1].sentences, vec!["Three.".to_string()]);
+  }
+
+  #[test]
+  fn paragraphs_cap_sentence_count() {
+    let input: Vec<_> = (0..10).map(|i| frag(i * 100, i * 100 + 90, "Short.")).collect();
+    let paras = paragraphs(&input);
+    assert_eq!(paras.len(), 2);
+    assert_eq!(paras[0].sentences.len(), MAX_PARAGRAPH_SENTENCES);
+  }
+
+  #[test]
+  fn empty_input() {
+    assert!(sentences(&[]).is_empty());
+    assert!(paragraphs(&[]).is_empty());
+  }
+}
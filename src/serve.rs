@@ -0,0 +1,733 @@
+//! Local HTTP API: `yp serve`.
+//!
+//! A small HTTP/1.1 server on a tokio `TcpListener` for tools that want
+//! requests instead of pipes. Every response closes its connection, bodies are
+//! JSON (or NDJSON for transcripts), and errors use the CLI's
+//! `{"error": {code, message}}` object with a status code for the kind.
+//!
+//! Transcripts go through the transcript store, so a video transcribed once is
+//! served from disk afterwards; the whisper model is loaded once per server,
+//! and requests for a video that is already being transcribed wait for that
+//! run instead of starting a second one. Long transcriptions can also run as
+//! background jobs that are polled by ID.
+//!
+//! | Method | Path                              | Result |
+//! |--------|-----------------------------------|--------|
+//! | GET    | `/health`                         | `{status, version}` |
+//! | GET    | `/search?q=&limit=`               | `[VideoEntry]`, as `yp search` |
+//! | GET    | `/channel?channel=&start=&count=` | `{channel_url, start, count, videos}` |
+//! | GET    | `/videos/{id}`                    | `VideoDetails` + `video_id`, as `yp info` |
+//! | GET    | `/videos/{id}/transcript?raw=`    | NDJSON utterances (raw ones stream per chunk) |
+//! | GET    | `/videos/{id}/summary?format=`    | summary in json, md, txt or yaml |
+//! | POST   | `/jobs?video=&kind=`              | `202` + job (`kind`: transcript or summary) |
+//! | GET    | `/jobs`, `/jobs/{id}`             | jobs; one job includes its `result` |
+//! | DELETE | `/jobs/{id}`                      | cancel a job |
+
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+use crate::cache;
+use crate::cli::{self, AbortOnDrop};
+use crate::error::{self, ErrorKind};
+use crate::schema::{self, VideoEntry, VideoInfo};
+use crate::summarize;
+use crate::transcript::WhisperCache;
+use crate::youtube;
+
+/// Longest request head (request line + headers) accepted, in bytes.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// How long a client may take to send its request head.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Finished jobs kept for polling; older ones are dropped first.
+const MAX_FINISHED_JOBS: usize = 50;
+
+// ---------------------------------------------------------------------------
+// Requests and routes
+// ---------------------------------------------------------------------------
+
+/// A parsed request line.
+#[derive(Debug, PartialEq)]
+struct Request {
+  method: String,
+  /// The path as sent, for logging.
+  path: String,
+  /// Path segments, each percent-decoded after splitting.
+  segments: Vec<String>,
+  query: HashMap<String, String>,
+}
+
+impl Request {
+  fn param(&self, key: &str) -> Option<&str> {
+    self.query.get(key).map(String::as_str).filter(|v| !v.is_empty())
+  }
+
+  /// A flag: present without a value, `1`, `true` or `yes`.
+  fn flag(&self, key: &str) -> bool {
+    self.query.get(key).is_some_and(|v| matches!(v.as_str(), "" | "1" | "true" | "yes"))
+  }
+
+  fn number(&self, key: &str) -> Result<Option<usize>, ApiError> {
+    self
+      .param(key)
+      .map(|v| v.parse().map_err(|_| ApiError::bad_request(format!("{key} must be a non-negative integer"))))
+      .transpose()
+  }
+}
+
+/// Decode `%XX` escapes and `+` in a query component.
+fn percent_decode(s: &str) -> String {
+  unescape(s, true)
+}
+
+/// Decode `%XX` escapes in a path segment (`+` stays literal).
+fn decode_segment(s: &str) -> String {
+  unescape(s, false)
+}
+
+fn unescape(s: &str, plus_is_space: bool) -> String {
+  let bytes = s.as_bytes();
+  let mut out = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'+' if plus_is_space => out.push(b' '),
+      b'%' if i + 2 < bytes.len() => {
+        match std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+          Some(b) => {
+            out.push(b);
+            i += 2;
+          }
+          None => out.push(b'%'),
+        }
+      }
+      b => out.push(b),
+    }
+    i += 1;
+  }
+  String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Parse `GET /path?a=1&b HTTP/1.1`.
+fn parse_request_line(line: &str) -> Option<Request> {
+  let mut parts = line.split_whitespace();
+  let method = parts.next()?.to_ascii_uppercase();
+  let target = parts.next()?;
+  if !parts.next()?.starts_with("HTTP/1.") {
+    return None;
+  }
+  let (path, query) = target.split_once('?').unwrap_or((target, ""));
+  let query = query
+    .split('&')
+    .filter(|p| !p.is_empty())
+    .map(|p| {
+      let (k, v) = p.split_once('=').unwrap_or((p, ""));
+      (percent_decode(k), percent_decode(v))
+    })
+    .collect();
+  // Split before decoding, so an escaped `/` stays inside its segment.
+  let segments = path.split('/').filter(|s| !s.is_empty()).map(decode_segment).collect();
+  Some(Request { method, path: path.to_string(), segments, query })
+}
+
+/// What a request asks for.
+#[derive(Debug, PartialEq)]
+enum Route {
+  Health,
+  Search,
+  Channel,
+  Info(String),
+  Transcript(String),
+  Summary(String),
+  CreateJob,
+  ListJobs,
+  GetJob(String),
+  CancelJob(String),
+  /// Known path, wrong method.
+  MethodNotAllowed,
+  NotFound,
+}
+
+fn route(method: &str, segments: &[String]) -> Route {
+  let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+  let route = match segments.as_slice() {
+    ["health"] => (vec!["GET"], Route::Health),
+    ["search"] => (vec!["GET"], Route::Search),
+    ["channel"] => (vec!["GET"], Route::Channel),
+    ["videos", id] => (vec!["GET"], Route::Info(cli::extract_video_id(id))),
+    ["videos", id, "transcript"] => (vec!["GET"], Route::Transcript(cli::extract_video_id(id))),
+    ["videos", id, "summary"] => (vec!["GET"], Route::Summary(cli::extract_video_id(id))),
+    ["jobs"] if method == "POST" => (vec!["POST"], Route::CreateJob),
+    ["jobs"] => (vec!["GET", "POST"], Route::ListJobs),
+    ["jobs", id] if method == "DELETE" => (vec!["DELETE"], Route::CancelJob((*id).to_string())),
+    ["jobs", id] => (vec!["GET", "DELETE"], Route::GetJob((*id).to_string())),
+    _ => return Route::NotFound,
+  };
+  if route.0.contains(&method) { route.1 } else { Route::MethodNotAllowed }
+}
+
+// ---------------------------------------------------------------------------
+// Responses
+// ---------------------------------------------------------------------------
+
+/// An error response: status code plus the CLI's error object.
+#[derive(Debug)]
+struct ApiError {
+  status: u16,
+  kind: ErrorKind,
+  message: String,
+}
+
+impl ApiError {
+  fn bad_request(message: impl Into<String>) -> Self {
+    Self { status: 400, kind: ErrorKind::InvalidInput, message: message.into() }
+  }
+
+  fn not_found(message: impl Into<String>) -> Self {
+    Self { status: 404, kind: ErrorKind::NotFound, message: message.into() }
+  }
+
+  /// The same error object the CLI prints.
+  fn body(&self) -> Value {
+    json!({
+      "schema_version": schema::SCHEMA_VERSION,
+      "error": { "code": self.kind.code(), "message": self.message },
+    })
+  }
+}
+
+/// Failures of yt-dlp, ffmpeg or whisper behind a request, by kind.
+impl From<anyhow::Error> for ApiError {
+  fn from(e: anyhow::Error) -> Self {
+    let kind = error::kind_of(&e);
+    let status = match kind {
+      ErrorKind::InvalidInput => 400,
+      ErrorKind::NotFound => 404,
+      ErrorKind::Unavailable | ErrorKind::AgeRestricted => 403,
+      ErrorKind::DependencyMissing | ErrorKind::Internal => 500,
+      ErrorKind::Network | ErrorKind::TranscriptionFailed => 502,
+    };
+    Self { status, kind, message: format!("{e:#}") }
+  }
+}
+
+fn reason(status: u16) -> &'static str {
+  match status {
+    200 => "OK",
+    202 => "Accepted",
+    400 => "Bad Request",
+    403 => "Forbidden",
+    404 => "Not Found",
+    405 => "Method Not Allowed",
+    408 => "Request Timeout",
+    500 => "Internal Server Error",
+    _ => "Bad Gateway",
+  }
+}
+
+/// Write the status line and headers; `len: None` means the body runs until the connection closes.
+async fn write_head(stream: &mut TcpStream, status: u16, content_type: &str, len: Option<usize>) -> Result<()> {
+  let mut head =
+    format!("HTTP/1.1 {status} {}\r\nContent-Type: {content_type}\r\nConnection: close\r\n", reason(status));
+  if let Some(len) = len {
+    head.push_str(&format!("Content-Length: {len}\r\n"));
+  }
+  head.push_str("\r\n");
+  stream.write_all(head.as_bytes()).await.context("Failed to write response head")
+}
+
+async fn write_body(stream: &mut TcpStream, status: u16, content_type: &str, body: &str) -> Result<()> {
+  write_head(stream, status, content_type, Some(body.len())).await?;
+  stream.write_all(body.as_bytes()).await.context("Failed to write response body")
+}
+
+async fn write_json(stream: &mut TcpStream, status: u16, value: &Value) -> Result<()> {
+  let mut body = serde_json::to_string_pretty(value).context("Failed to serialize response")?;
+  body.push('\n');
+  write_body(stream, status, "application/json", &body).await
+}
+
+/// Write one NDJSON line of a streamed body.
+async fn write_line(stream: &mut TcpStream, value: &Value) -> Result<()> {
+  let mut line = serde_json::to_string(value).context("Failed to serialize line")?;
+  line.push('\n');
+  stream.write_all(line.as_bytes()).await.context("Failed to write line")?;
+  stream.flush().await.context("Failed to flush stream")
+}
+
+// ---------------------------------------------------------------------------
+// Jobs
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum JobKind {
+  Transcript,
+  Summary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum JobStatus {
+  Running,
+  Done,
+  Failed,
+  Cancelled,
+}
+
+/// A background transcription or summary.
+#[derive(Debug, Serialize)]
+struct Job {
+  job_id: String,
+  video_id: String,
+  kind: JobKind,
+  status: JobStatus,
+  created_at: String,
+  /// Seconds of audio transcribed so far.
+  progress_secs: f64,
+  /// Video length, when known.
+  total_secs: Option<f64>,
+  /// `{code, message}` of a failed job.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  error: Option<Value>,
+  #[serde(skip)]
+  result: Option<Value>,
+  #[serde(skip)]
+  abort: Option<tokio::task::AbortHandle>,
+}
+
+/// State shared by every connection.
+struct Server {
+  whisper: WhisperCache,
+  jobs: StdMutex<BTreeMap<u64, Job>>,
+  next_job: AtomicU64,
+  /// One lock per video being transcribed, so concurrent requests share a run.
+  transcribing: StdMutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl Server {
+  fn jobs(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, Job>> {
+    self.jobs.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+  }
+
+  fn video_lock(&self, video_id: &str) -> Arc<tokio::sync::Mutex<()>> {
+    let mut locks = self.transcribing.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    Arc::clone(locks.entry(video_id.to_string()).or_default())
+  }
+
+  /// Transcribe (or load) a video, one run per video at a time.
+  async fn transcribe(
+    &self,
+    video_id: &str,
+    duration: Option<u32>,
+    fresh: bool,
+    on_utterances: &(dyn Fn(&[whisper_cli::Utternace]) + Sync),
+  ) -> Result<Vec<whisper_cli::Utternace>> {
+    let lock = self.video_lock(video_id);
+    let _guard = lock.lock().await;
+    cli::run_transcription_reporting(video_id, duration, fresh, Arc::clone(&self.whisper), "", on_utterances).await
+  }
+
+  /// Fetch metadata, transcribe and reduce to a summary.
+  async fn summarize(
+    &self,
+    video_id: &str,
+    req: &Request,
+    on_utterances: &(dyn Fn(&[whisper_cli::Utternace]) + Sync),
+  ) -> Result<summarize::SummaryOutput, ApiError> {
+    let flags = cli::SummaryFlags {
+      llm: req.flag("llm"),
+      max_tokens: req.number("max_tokens")?,
+      keep_boilerplate: req.flag("keep_boilerplate"),
+    };
+    let fresh = req.flag("fresh");
+    let output = cli::summarize_video(video_id, &flags, async |duration| {
+      self.transcribe(video_id, duration, fresh, on_utterances).await
+    })
+    .await?;
+    Ok(output)
+  }
+}
+
+/// Drop the oldest finished jobs beyond `MAX_FINISHED_JOBS`.
+fn prune_jobs(jobs: &mut BTreeMap<u64, Job>) {
+  let finished: Vec<u64> = jobs.iter().filter(|(_, j)| j.status != JobStatus::Running).map(|(id, _)| *id).collect();
+  for id in finished.iter().take(finished.len().saturating_sub(MAX_FINISHED_JOBS)) {
+    jobs.remove(id);
+  }
+}
+
+fn job_number(job_id: &str) -> Option<u64> {
+  job_id.strip_prefix("job-")?.parse().ok()
+}
+
+async fn create_job(server: &Arc<Server>, req: Request) -> Result<(u16, Value), ApiError> {
+  let video_id = cli::extract_video_id(req.param("video").ok_or_else(|| ApiError::bad_request("Missing video"))?);
+  let kind = match req.param("kind").unwrap_or("transcript") {
+    "transcript" => JobKind::Transcript,
+    "summary" => JobKind::Summary,
+    other => return Err(ApiError::bad_request(format!("Unknown job kind: {other} (transcript or summary)"))),
+  };
+  req.number("max_tokens")?;
+
+  let n = server.next_job.fetch_add(1, Ordering::Relaxed) + 1;
+  let job = Job {
+    job_id: format!("job-{n}"),
+    video_id: video_id.clone(),
+    kind,
+    status: JobStatus::Running,
+    created_at: chrono::Local::now().to_rfc3339(),
+    progress_secs: 0.0,
+    total_secs: None,
+    error: None,
+    result: None,
+    abort: None,
+  };
+  let body = serde_json::to_value(&job).context("Failed to serialize job")?;
+  server.jobs().insert(n, job);
+
+  let state = Arc::clone(server);
+  let task = tokio::spawn(async move {
+    #[allow(clippy::cast_precision_loss)]
+    let progress = |batch: &[whisper_cli::Utternace]| {
+      if let Some(last) = batch.last()
+        && let Some(job) = state.jobs().get_mut(&n)
+      {
+        job.progress_secs = last.stop as f64 / 100.0;
+      }
+    };
+    let result: Result<Value, ApiError> = match kind {
+      JobKind::Transcript => {
+        let duration = cli::video_duration(&video_id, "").await;
+        if let Some(job) = state.jobs().get_mut(&n) {
+          job.total_secs = duration.map(f64::from);
+        }
+        state
+          .transcribe(&video_id, duration, req.flag("fresh"), &progress)
+          .await
+          .and_then(|u| cli::transcript_values(&u, req.flag("raw")))
+          .map(Value::Array)
+          .map_err(ApiError::from)
+      }
+      JobKind::Summary => state
+        .summarize(&video_id, &req, &progress)
+        .await
+        .and_then(|o| Ok(serde_json::to_value(o).context("Failed to serialize summary")?)),
+    };
+
+    let mut jobs = state.jobs();
+    if let Some(job) = jobs.get_mut(&n) {
+      job.abort = None;
+      match result {
+        Ok(value) => {
+          job.status = JobStatus::Done;
+          job.progress_secs = job.total_secs.unwrap_or(job.progress_secs);
+          job.result = Some(value);
+        }
+        Err(e) => {
+          tracing::warn!(job = n, err = %e.message, "serve: job failed");
+          job.status = JobStatus::Failed;
+          job.error = Some(e.body()["error"].take());
+        }
+      }
+    }
+    prune_jobs(&mut jobs);
+  });
+  if let Some(job) = server.jobs().get_mut(&n).filter(|j| j.status == JobStatus::Running) {
+    job.abort = Some(task.abort_handle());
+  }
+  Ok((202, body))
+}
+
+fn get_job(server: &Server, job_id: &str) -> Result<Value, ApiError> {
+  let jobs = server.jobs();
+  let job = job_number(job_id).and_then(|n| jobs.get(&n)).ok_or_else(|| ApiError::not_found("No such job"))?;
+  let mut body = serde_json::to_value(job).context("Failed to serialize job")?;
+  if let Some(result) = &job.result {
+    body["result"] = result.clone();
+  }
+  Ok(body)
+}
+
+fn cancel_job(server: &Server, job_id: &str) -> Result<Value, ApiError> {
+  let mut jobs = server.jobs();
+  let job = job_number(job_id).and_then(|n| jobs.get_mut(&n)).ok_or_else(|| ApiError::not_found("No such job"))?;
+  if let Some(task) = job.abort.take() {
+    task.abort();
+    job.status = JobStatus::Cancelled;
+  }
+  Ok(serde_json::to_value(&*job).context("Failed to serialize job")?)
+}
+
+// ---------------------------------------------------------------------------
+// Handlers
+// ---------------------------------------------------------------------------
+
+/// Stream a transcript as NDJSON. Raw utterances are written as each chunk is
+/// transcribed; classified ones once the transcript is complete. A failure
+/// after the headers are sent ends the stream with an error object.
+async fn stream_transcript(
+  server: &Arc<Server>,
+  stream: &mut TcpStream,
+  video_id: String,
+  req: &Request,
+) -> Result<()> {
+  let raw = req.flag("raw");
+  let fresh = req.flag("fresh");
+  let (tx, mut rx) = mpsc::unbounded_channel::<Vec<Value>>();
+  let state = Arc::clone(server);
+  // Dropped (and the transcription aborted) if the client goes away.
+  let mut task = AbortOnDrop(tokio::spawn(async move {
+    let on_utterances = |batch: &[whisper_cli::Utternace]| {
+      if raw && let Ok(values) = cli::transcript_values(batch, true) {
+        let _ = tx.send(values);
+      }
+    };
+    // Without the duration, a dropped stream would look like the end of the video.
+    let duration = cli::video_duration(&video_id, "").await;
+    let utterances = state.transcribe(&video_id, duration, fresh, &on_utterances).await?;
+    if !raw {
+      let _ = tx.send(cli::transcript_values(&utterances, false)?);
+    }
+    Ok::<(), anyhow::Error>(())
+  }));
+
+  write_head(stream, 200, "application/x-ndjson", None).await?;
+  while let Some(batch) = rx.recv().await {
+    for value in &batch {
+      write_line(stream, value).await?;
+    }
+  }
+  match (&mut task.0).await {
+    Ok(Ok(())) => Ok(()),
+    Ok(Err(e)) => write_line(stream, &ApiError::from(e).body()).await,
+    Err(e) => write_line(stream, &ApiError::from(anyhow!("Transcription task failed: {e}")).body()).await,
+  }
+}
+
+async fn render_summary(server: &Server, video_id: &str, req: &Request) -> Result<(String, &'static str), ApiError> {
+  let format = match req.param("format").unwrap_or("json") {
+    "json" => summarize::SummaryFormat::Json,
+    "md" | "markdown" => summarize::SummaryFormat::Md,
+    "txt" | "text" => summarize::SummaryFormat::Txt,
+    "yaml" | "yml" => summarize::SummaryFormat::Yaml,
+    other => return Err(ApiError::bad_request(format!("Unknown format: {other} (json, md, txt or yaml)"))),
+  };
+  let output = server.summarize(video_id, req, &|_| {}).await?;
+  let body = summarize::render(&output, format, req.flag("with_transcript"))?;
+  let content_type = match format {
+    summarize::SummaryFormat::Json => "application/json",
+    summarize::SummaryFormat::Md => "text/markdown; charset=utf-8",
+    summarize::SummaryFormat::Txt => "text/plain; charset=utf-8",
+    summarize::SummaryFormat::Yaml => "application/yaml",
+  };
+  Ok((body, content_type))
+}
+
+/// Handle a JSON request; streaming and text responses are handled in `handle_connection`.
+async fn respond(server: &Arc<Server>, route: Route, req: Request) -> Result<(u16, Value), ApiError> {
+  match route {
+    Route::Health => Ok((200, json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }))),
+    Route::Search => {
+      let query = req.param("q").ok_or_else(|| ApiError::bad_request("Missing q"))?;
+      let limit = req.number("limit")?.unwrap_or(20);
+      let mut results = youtube::search_youtube(query).await.context("YouTube search failed")?;
+      results.truncate(limit);
+      cli::remember_videos(&results);
+      let entries: Vec<VideoEntry> = results.iter().map(|e| VideoEntry::new(e, None)).collect();
+      let versioned: Vec<_> = entries.iter().map(schema::versioned).collect();
+      Ok((200, serde_json::to_value(versioned).context("Failed to serialize search results")?))
+    }
+    Route::Channel => {
+      let channel = req
+        .param("channel")
+        .map_or_else(|| crate::constants::constants().pastel_sketchbook_channel.clone(), str::to_string);
+      let channel_url = youtube::detect_channel_url(&channel)
+        .ok_or_else(|| ApiError::bad_request(format!("Could not detect channel URL from: {channel}")))?;
+      let start = req.number("start")?.unwrap_or(1).max(1);
+      let count = req.number("count")?.unwrap_or(30);
+      let videos = youtube::list_channel_videos(&channel_url, start, Some(count))
+        .await
+        .context("Failed to list channel videos")?;
+      cli::remember_videos(&videos);
+      let videos: Vec<VideoEntry> = videos.iter().map(|e| VideoEntry::new(e, None)).collect();
+      Ok((200, json!({ "channel_url": channel_url, "start": start, "count": count, "videos": videos })))
+    }
+    Route::Info(video_id) => {
+      let details = youtube::get_video_info(&video_id).await.context("Failed to get video info")?;
+      if let Err(e) = cache::append_videos(&[(&video_id, &details.title)]) {
+        tracing::warn!("Failed to update video cache: {}", e);
+      }
+      let info = VideoInfo { video_id: &video_id, details: &details };
+      Ok((200, serde_json::to_value(schema::versioned(&info)).context("Failed to serialize video details")?))
+    }
+    Route::CreateJob => create_job(server, req).await,
+    Route::ListJobs => {
+      let jobs: Vec<Value> = server.jobs().values().filter_map(|j| serde_json::to_value(j).ok()).collect();
+      Ok((200, Value::Array(jobs)))
+    }
+    Route::GetJob(id) => Ok((200, get_job(server, &id)?)),
+    Route::CancelJob(id) => Ok((200, cancel_job(server, &id)?)),
+    Route::MethodNotAllowed => {
+      Err(ApiError { status: 405, kind: ErrorKind::InvalidInput, message: "Method not allowed".into() })
+    }
+    Route::NotFound | Route::Transcript(_) | Route::Summary(_) => Err(ApiError::not_found("No such endpoint")),
+  }
+}
+
+/// Read the request head, ignoring headers and any body.
+async fn read_request(stream: &mut TcpStream) -> Result<Option<Request>> {
+  let mut reader = BufReader::new(stream);
+  let mut line = String::new();
+  reader.read_line(&mut line).await.context("Failed to read request line")?;
+  let request = parse_request_line(line.trim_end());
+  let mut read = line.len();
+  loop {
+    let mut header = String::new();
+    let n = reader.read_line(&mut header).await.context("Failed to read request headers")?;
+    read += n;
+    if n == 0 || header.trim_end().is_empty() || read > MAX_HEAD_BYTES {
+      break;
+    }
+  }
+  Ok(request)
+}
+
+async fn handle_connection(server: Arc<Server>, mut stream: TcpStream) -> Result<()> {
+  let Ok(request) = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await else {
+    let e = ApiError { status: 408, kind: ErrorKind::InvalidInput, message: "Timed out reading the request".into() };
+    return write_json(&mut stream, e.status, &e.body()).await;
+  };
+  let Some(req) = request? else {
+    return write_json(&mut stream, 400, &ApiError::bad_request("Malformed request line").body()).await;
+  };
+  tracing::info!(method = %req.method, path = %req.path, "serve: request");
+  match route(&req.method, &req.segments) {
+    Route::Transcript(video_id) => stream_transcript(&server, &mut stream, video_id, &req).await,
+    Route::Summary(video_id) => match render_summary(&server, &video_id, &req).await {
+      Ok((body, content_type)) => write_body(&mut stream, 200, content_type, &body).await,
+      Err(e) => write_json(&mut stream, e.status, &e.body()).await,
+    },
+    route => match respond(&server, route, req).await {
+      Ok((status, body)) => write_json(&mut stream, status, &body).await,
+      Err(e) => write_json(&mut stream, e.status, &e.body()).await,
+    },
+  }
+}
+
+/// Serve the HTTP API on `bind` until interrupted.
+pub async fn serve(bind: &str) -> Result<()> {
+  let listener = TcpListener::bind(bind).await.with_context(|| format!("Failed to bind {bind}"))?;
+  let addr = listener.local_addr().context("Failed to read bound address")?;
+  eprintln!("yp API listening on http://{addr} (Ctrl+C to stop)");
+
+  let server = Arc::new(Server {
+    whisper: Arc::new(StdMutex::new(None)),
+    jobs: StdMutex::new(BTreeMap::new()),
+    next_job: AtomicU64::new(0),
+    transcribing: StdMutex::new(HashMap::new()),
+  });
+  loop {
+    tokio::select! {
+      accepted = listener.accept() => {
+        let (stream, peer) = accepted.context("Failed to accept connection")?;
+        let server = Arc::clone(&server);
+        tokio::spawn(async move {
+          if let Err(e) = handle_connection(server, stream).await {
+            tracing::debug!(%peer, err = %e, "serve: connection ended");
+          }
+        });
+      }
+      _ = tokio::signal::ctrl_c() => {
+        eprintln!("Stopping.");
+        return Ok(());
+      }
+    }
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_request_lines() {
+    let req = parse_request_line("GET /search?q=ambient+guitar%21&limit=5&raw HTTP/1.1").unwrap();
+    assert_eq!(req.method, "GET");
+    assert_eq!(req.path, "/search");
+    assert_eq!(req.param("q"), Some("ambient guitar!"));
+    assert_eq!(req.number("limit").unwrap(), Some(5));
+    assert!(req.flag("raw"));
+    assert!(!req.flag("fresh"));
+    assert!(req.number("q").is_err());
+    assert!(parse_request_line("GET /search").is_none());
+    assert!(parse_request_line("hello").is_none());
+    assert_eq!(percent_decode("100%"), "100%");
+    assert_eq!(percent_decode("%zz%4"), "%zz%4");
+  }
+
+  fn segments(path: &str) -> Vec<String> {
+    parse_request_line(&format!("GET {path} HTTP/1.1")).unwrap().segments
+  }
+
+  #[test]
+  fn path_segments_are_decoded_after_splitting() {
+    let req = parse_request_line("GET /videos/https:%2F%2Fyoutu.be%2Fabc123/summary HTTP/1.1").unwrap();
+    assert_eq!(req.segments, vec!["videos", "https://youtu.be/abc123", "summary"]);
+    assert_eq!(route(&req.method, &req.segments), Route::Summary("abc123".to_string()));
+    let req = parse_request_line("GET /videos/a+b HTTP/1.1").unwrap();
+    assert_eq!(req.segments, vec!["videos", "a+b"]);
+  }
+
+  #[test]
+  fn routes_paths() {
+    assert_eq!(route("GET", &segments("/health")), Route::Health);
+    assert_eq!(route("GET", &segments("/videos/abc123")), Route::Info("abc123".to_string()));
+    assert_eq!(route("GET", &segments("/videos/abc123/transcript")), Route::Transcript("abc123".to_string()));
+    assert_eq!(route("GET", &segments("/videos/abc123/summary")), Route::Summary("abc123".to_string()));
+    assert_eq!(route("POST", &segments("/jobs")), Route::CreateJob);
+    assert_eq!(route("GET", &segments("/jobs")), Route::ListJobs);
+    assert_eq!(route("GET", &segments("/jobs/job-3")), Route::GetJob("job-3".to_string()));
+    assert_eq!(route("DELETE", &segments("/jobs/job-3")), Route::CancelJob("job-3".to_string()));
+    assert_eq!(route("POST", &segments("/search")), Route::MethodNotAllowed);
+    assert_eq!(route("GET", &segments("/nope")), Route::NotFound);
+  }
+
+  #[test]
+  fn finished_jobs_are_pruned_oldest_first() {
+    let job = |n: u64, status| Job {
+      job_id: format!("job-{n}"),
+      video_id: String::new(),
+      kind: JobKind::Transcript,
+      status,
+      created_at: String::new(),
+      progress_secs: 0.0,
+      total_secs: None,
+      error: None,
+      result: None,
+      abort: None,
+    };
+    let mut jobs: BTreeMap<u64, Job> = BTreeMap::new();
+    jobs.insert(0, job(0, JobStatus::Running));
+    for n in 1..=(MAX_FINISHED_JOBS as u64 + 2) {
+      jobs.insert(n, job(n, JobStatus::Done));
+    }
+    prune_jobs(&mut jobs);
+    assert!(jobs.contains_key(&0), "running jobs are kept");
+    assert!(!jobs.contains_key(&1) && !jobs.contains_key(&2));
+    assert_eq!(jobs.len(), MAX_FINISHED_JOBS + 1);
+    assert_eq!(job_number("job-12"), Some(12));
+    assert_eq!(job_number("12"), None);
+  }
+}
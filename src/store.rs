@@ -0,0 +1,357 @@
+//! Per-video transcript store.
+//!
+//! Every transcription job logs its progress to
+//! `<cache_dir>/yp/transcripts/<video_id>.jsonl`, one line per event: a start
+//! line naming the video, one line per transcribed chunk (its utterances plus
+//! the offset of the next chunk to fetch) and, once the pipeline reaches the
+//! end of the stream, a completion line. A checkpoint only appends, so long
+//! videos cost the same per chunk as short ones. An interrupted job (Ctrl+A,
+//! Ctrl+S, network loss, crash) leaves a log without a completion line, which
+//! the TUI and `yp transcript` resume from instead of starting over; a
+//! completed log doubles as a cached transcript. Completing or discarding a
+//! record refreshes the video's entry in the search index (`index`).
+//!
+//! One pipeline run at a time writes a video's log: the run holds
+//! `<video_id>.lock` next to it (see [`lock`]), across processes, and
+//! discarding takes the same lock.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::error::{self, ErrorKind};
+
+/// A single utterance as persisted on disk (centisecond timestamps, like whisper).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredUtterance {
+  pub start: i64,
+  pub stop: i64,
+  pub text: String,
+}
+
+/// Transcription progress for one video, replayed from its log.
+#[derive(Debug, Clone)]
+pub struct TranscriptRecord {
+  pub video_id: String,
+  pub url: String,
+  /// Offset (seconds) of the next chunk to transcribe.
+  pub offset_secs: u32,
+  /// Whether the pipeline reached the end of the stream.
+  pub complete: bool,
+  /// Last write time (RFC 3339).
+  pub updated_at: String,
+  pub utterances: Vec<StoredUtterance>,
+}
+
+/// One line of a record's log.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "entry", rename_all = "snake_case")]
+enum Entry {
+  /// First line: the video the log belongs to.
+  Start { video_id: String, url: String, at: String },
+  /// A transcribed chunk and the offset of the chunk after it.
+  Chunk { next_offset: u32, utterances: Vec<StoredUtterance>, at: String },
+  /// The pipeline reached the end of the stream.
+  Complete { at: String },
+}
+
+impl TranscriptRecord {
+  /// Convert the stored utterances back into whisper utterances.
+  pub fn to_utterances(&self) -> Vec<whisper_cli::Utternace> {
+    self
+      .utterances
+      .iter()
+      .map(|u| whisper_cli::Utternace { start: u.start, stop: u.stop, text: u.text.clone() })
+      .collect()
+  }
+
+  /// Replay a log. Lines that don't parse (a write torn by a crash) are skipped,
+  /// so the record ends at the last chunk that was fully written. A chunk that
+  /// does not move the offset forward (a stale run's write) is skipped too.
+  fn replay(log: &str) -> Option<Self> {
+    let mut entries = log.lines().filter_map(|line| serde_json::from_str::<Entry>(line).ok());
+    let Some(Entry::Start { video_id, url, at }) = entries.next() else { return None };
+    let mut record = Self { video_id, url, offset_secs: 0, complete: false, updated_at: at, utterances: Vec::new() };
+    for entry in entries {
+      match entry {
+        Entry::Start { .. } => {}
+        Entry::Chunk { next_offset, .. } if next_offset <= record.offset_secs => {}
+        Entry::Chunk { next_offset, mut utterances, at } => {
+          record.utterances.append(&mut utterances);
+          record.offset_secs = next_offset;
+          record.updated_at = at;
+        }
+        Entry::Complete { at } => {
+          record.complete = true;
+          record.updated_at = at;
+        }
+      }
+    }
+    Some(record)
+  }
+}
+
+/// Return the store directory: `<cache_dir>/yp/transcripts`.
+fn store_dir() -> Option<PathBuf> {
+  directories::ProjectDirs::from("", "", "yp").map(|d| d.cache_dir().join("transcripts"))
+}
+
+/// Return the record path for a video, rejecting IDs that could escape the store directory.
+fn record_path(video_id: &str) -> Option<PathBuf> {
+  if video_id.is_empty() || !video_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+    return None;
+  }
+  store_dir().map(|d| d.join(format!("{video_id}.jsonl")))
+}
+
+/// Load the record for a video, if one exists and parses.
+pub fn load(video_id: &str) -> Option<TranscriptRecord> {
+  let path = record_path(video_id)?;
+  let content = fs::read_to_string(path).ok()?;
+  TranscriptRecord::replay(&content)
+}
+
+/// Hold the video's log for one pipeline run, across processes; released on drop.
+///
+/// Two runs appending to one log would interleave their chunks, so a second
+/// run fails instead of waiting for the first.
+pub fn lock(video_id: &str) -> Result<Option<fs::File>> {
+  let Some(path) = record_path(video_id) else { return Ok(None) };
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).context("Failed to create transcript store directory")?;
+  }
+  let file = fs::OpenOptions::new()
+    .create(true)
+    .write(true)
+    .truncate(false)
+    .open(path.with_extension("lock"))
+    .context("Failed to open transcript record lock")?;
+  match file.try_lock() {
+    Ok(()) => Ok(Some(file)),
+    Err(fs::TryLockError::WouldBlock) => Err(error::fail(
+      ErrorKind::Internal,
+      format!("{video_id} is already being transcribed by another yp process; try again once it finishes"),
+    )),
+    Err(fs::TryLockError::Error(e)) => Err(anyhow::anyhow!(e).context("Failed to lock transcript record")),
+  }
+}
+
+/// Append one entry to the video's log, starting the log if there is none.
+fn append(video_id: &str, url: &str, entry: &Entry) -> Result<()> {
+  let Some(path) = record_path(video_id) else { return Ok(()) }; // silently skip if no cache dir
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).context("Failed to create transcript store directory")?;
+  }
+  let mut file = fs::OpenOptions::new()
+    .read(true)
+    .append(true)
+    .create(true)
+    .open(&path)
+    .context("Failed to open transcript record")?;
+
+  let mut lines = String::new();
+  let len = file.metadata().context("Failed to read transcript record")?.len();
+  if len == 0 {
+    let start = Entry::Start { video_id: video_id.to_string(), url: url.to_string(), at: now() };
+    lines.push_str(&serde_json::to_string(&start).context("Failed to serialize transcript record")?);
+    lines.push('\n');
+  } else {
+    // Start on a fresh line after a write torn by a crash.
+    let mut last = [0u8];
+    file.seek(SeekFrom::End(-1)).context("Failed to read transcript record")?;
+    file.read_exact(&mut last).context("Failed to read transcript record")?;
+    if last[0] != b'\n' {
+      lines.push('\n');
+    }
+  }
+  lines.push_str(&serde_json::to_string(entry).context("Failed to serialize transcript record")?);
+  lines.push('\n');
+  // One write, so concurrent readers see whole lines.
+  file.write_all(lines.as_bytes()).context("Failed to write transcript record")
+}
+
+fn now() -> String {
+  chrono::Local::now().to_rfc3339()
+}
+
+/// Append a transcribed chunk to the video's record and advance its offset.
+///
+/// `next_offset` is the offset of the chunk *after* this one, so a resumed job
+/// never re-transcribes audio that is already stored.
+pub fn record_chunk(video_id: &str, url: &str, next_offset: u32, utterances: &[whisper_cli::Utternace]) -> Result<()> {
+  let utterances =
+    utterances.iter().map(|u| StoredUtterance { start: u.start, stop: u.stop, text: u.text.clone() }).collect();
+  append(video_id, url, &Entry::Chunk { next_offset, utterances, at: now() })
+}
+
+/// Mark the video's record as complete and index it (no-op if there is no record).
+pub fn mark_complete(video_id: &str) -> Result<()> {
+  let Some(record) = load(video_id) else { return Ok(()) };
+  append(video_id, &record.url, &Entry::Complete { at: now() })?;
+  let Some(record) = load(video_id) else { return Ok(()) };
+  if let Err(e) = crate::index::update(&record) {
+    warn!(err = %e, video_id = %video_id, "store: failed to update search index");
+  }
+  Ok(())
+}
+
+/// Delete the video's record. Returns `true` if a record was removed.
+///
+/// Fails while a pipeline run holds the record.
+pub fn discard(video_id: &str) -> Result<bool> {
+  let Some(path) = record_path(video_id) else { return Ok(false) };
+  let _lock = lock(video_id)?;
+  if let Err(e) = crate::index::remove(video_id) {
+    warn!(err = %e, video_id = %video_id, "store: failed to update search index");
+  }
+  match fs::remove_file(&path) {
+    Ok(()) => Ok(true),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+    Err(e) => Err(anyhow::anyhow!(e).context("Failed to remove transcript record")),
+  }
+}
+
+/// List all stored records, most recently updated first.
+pub fn list() -> Vec<TranscriptRecord> {
+  let Some(dir) = store_dir() else { return Vec::new() };
+  let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+  let mut records: Vec<TranscriptRecord> = entries
+    .flatten()
+    .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+    .filter_map(|e| fs::read_to_string(e.path()).ok())
+    .filter_map(|content| TranscriptRecord::replay(&content))
+    .collect();
+  records.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+  records
+}
+
+/// Bytes read from the end of a log to find its completion line.
+const TAIL_BYTES: u64 = 256;
+
+/// The completion time in a log's last line, if the log is complete.
+fn completed_at(tail: &str) -> Option<String> {
+  let last = tail.lines().rev().find(|line| !line.trim().is_empty())?;
+  match serde_json::from_str(last) {
+    Ok(Entry::Complete { at }) => Some(at),
+    _ => None,
+  }
+}
+
+/// Map every complete record's ID to its `updated_at`, reading only the end of each log.
+pub fn completed() -> HashMap<String, String> {
+  let Some(dir) = store_dir() else { return HashMap::new() };
+  let Ok(entries) = fs::read_dir(dir) else { return HashMap::new() };
+  entries
+    .flatten()
+    .map(|e| e.path())
+    .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+    .filter_map(|p| {
+      let id = p.file_stem()?.to_str()?.to_string();
+      let mut file = fs::File::open(&p).ok()?;
+      let len = file.metadata().ok()?.len();
+      file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES))).ok()?;
+      let mut tail = Vec::new();
+      file.read_to_end(&mut tail).ok()?;
+      Some((id, completed_at(&String::from_utf8_lossy(&tail))?))
+    })
+    .collect()
+}
+
+/// List records for jobs that were interrupted before reaching the end of the stream.
+pub fn incomplete() -> Vec<TranscriptRecord> {
+  list().into_iter().filter(|r| !r.complete).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn record_path_rejects_traversal() {
+    assert!(record_path("../etc/passwd").is_none());
+    assert!(record_path("a/b").is_none());
+    assert!(record_path("").is_none());
+  }
+
+  #[test]
+  fn record_path_accepts_video_ids() {
+    if let Some(path) = record_path("dQw4w9WgXcQ") {
+      assert!(path.ends_with("transcripts/dQw4w9WgXcQ.jsonl"));
+    }
+  }
+
+  #[test]
+  fn log_replays_chunks_and_skips_torn_lines() {
+    let log = concat!(
+      r#"{"entry":"start","video_id":"abc","url":"https://youtube.com/watch?v=abc","at":"t0"}"#,
+      "\n",
+      r#"{"entry":"chunk","next_offset":30,"utterances":[{"start":100,"stop":250,"text":"Hello"}],"at":"t1"}"#,
+      "\n",
+      r#"{"entry":"chunk","next_offset":60,"utter"#,
+      "\n",
+      r#"{"entry":"chunk","next_offset":60,"utterances":[],"at":"t2"}"#,
+      "\n",
+    );
+    let record = TranscriptRecord::replay(log).unwrap();
+    assert_eq!(record.video_id, "abc");
+    assert_eq!(record.offset_secs, 60);
+    assert!(!record.complete);
+    assert_eq!(record.updated_at, "t2");
+    let utterances = record.to_utterances();
+    assert_eq!(utterances.len(), 1);
+    assert_eq!(utterances[0].start, 100);
+    assert_eq!(utterances[0].stop, 250);
+
+    let complete = format!("{log}{}\n", r#"{"entry":"complete","at":"t3"}"#);
+    assert!(TranscriptRecord::replay(&complete).unwrap().complete);
+    assert_eq!(completed_at(&complete).as_deref(), Some("t3"));
+    assert_eq!(completed_at(log), None);
+    assert!(TranscriptRecord::replay("").is_none());
+  }
+
+  #[test]
+  fn replay_skips_chunks_that_do_not_move_forward() {
+    let log = concat!(
+      r#"{"entry":"start","video_id":"abc","url":"u","at":"t0"}"#,
+      "\n",
+      r#"{"entry":"chunk","next_offset":30,"utterances":[{"start":100,"stop":250,"text":"A"}],"at":"t1"}"#,
+      "\n",
+      r#"{"entry":"chunk","next_offset":60,"utterances":[{"start":3100,"stop":3200,"text":"B"}],"at":"t2"}"#,
+      "\n",
+      // A second run that started from offset 0 at the same time.
+      r#"{"entry":"chunk","next_offset":30,"utterances":[{"start":100,"stop":250,"text":"A"}],"at":"t3"}"#,
+      "\n",
+    );
+    let record = TranscriptRecord::replay(log).unwrap();
+    assert_eq!(record.offset_secs, 60);
+    assert_eq!(record.updated_at, "t2");
+    let texts: Vec<&str> = record.utterances.iter().map(|u| u.text.as_str()).collect();
+    assert_eq!(texts, ["A", "B"]);
+  }
+
+  #[test]
+  fn a_held_record_cannot_be_locked_or_discarded_again() {
+    let video_id = format!("yp_lock_test_{}", std::process::id());
+    let Ok(Some(held)) = lock(&video_id) else { return }; // no cache dir
+    assert_eq!(error::kind_of(&lock(&video_id).unwrap_err()), ErrorKind::Internal);
+    assert!(discard(&video_id).is_err());
+    drop(held);
+    assert!(!discard(&video_id).unwrap());
+    if let Some(path) = record_path(&video_id) {
+      let _ = fs::remove_file(path.with_extension("lock"));
+    }
+  }
+
+  #[test]
+  fn load_missing_record() {
+    assert!(load("yp_nonexistent_video_12345").is_none());
+  }
+}
@@ -1,13 +1,140 @@
 //! Transcript classify-reduce pipeline.
 //!
-//! Takes raw whisper utterances and produces a structured summary:
-//! 1. **Classify** — tag each utterance (`NonSpeech`, Filler, Repetition, `TopicShift`, `KeySegment`, Normal)
-//! 2. **Filter** — suppress noise (non-speech, filler, repetition)
-//! 3. **Reduce** — compress into bounded topics + key segments
+//! Takes whisper utterances (usually merged into sentences by `segment`) and
+//! produces a structured summary:
+//! 1. **Classify** — tag each utterance (`NonSpeech`, Filler, Repetition, `TopicShift`, Question, `KeySegment`, Normal);
+//!    `boilerplate` then retags a channel's recurring intro/outro as Boilerplate
+//! 2. **Filter** — suppress noise (non-speech, filler, repetition, boilerplate)
+//! 3. **Reduce** — compress into bounded topics + key segments + question/answer pairs
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, ErrorKind};
+use crate::keywords::{self, Keyword};
+use crate::llm::LlmSummary;
+use crate::player::VideoDetails;
+use crate::textrank;
 
-use serde::Serialize;
+// ---------------------------------------------------------------------------
+// Classifier configuration
+// ---------------------------------------------------------------------------
 
-use crate::player::VideoDetails;
+/// Thresholds for `classify` and caps for `reduce`.
+///
+/// Loaded from the `[classifier]` table in `prefs.toml` (missing keys keep
+/// their defaults) and overridable per run with `--classifier key=value`.
+/// Music channels typically want a shorter `topic_gap_secs`; lectures a
+/// longer `topic_max_secs` and lower `key_min_density`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClassifierConfig {
+  /// Jaccard similarity above which an utterance counts as a repetition.
+  pub repetition_similarity: f64,
+  /// Number of recent utterances compared for repetition.
+  pub repetition_window: usize,
+  /// Share of filler words above which an utterance counts as filler.
+  pub filler_ratio: f64,
+  /// Silence (seconds) before an utterance that marks a topic shift.
+  pub topic_gap_secs: f64,
+  /// Speech (seconds) accumulated since the last shift that forces a new topic.
+  pub topic_max_secs: f64,
+  /// Minimum word count for a key segment.
+  pub key_min_words: usize,
+  /// Minimum unique-word density for a key segment.
+  pub key_min_density: f64,
+  /// Maximum number of topic segments in the output.
+  pub max_topics: usize,
+  /// Maximum number of key segments in the output.
+  pub max_key_segments: usize,
+  /// Maximum TextRank sentences in each topic summary.
+  pub topic_summary_sentences: usize,
+  /// TextRank sentences in the whole-video abstract.
+  pub abstract_sentences: usize,
+  /// Keyphrases per topic segment.
+  pub topic_keywords: usize,
+  /// Keyphrases for the whole video.
+  pub video_keywords: usize,
+  /// Longest answer (seconds of speech after a question) kept in a Q&A pair.
+  pub qa_answer_secs: f64,
+  /// Maximum number of question/answer pairs in the output.
+  pub max_qa_pairs: usize,
+  /// Videos a phrase must appear in to count as channel boilerplate...
+  pub boilerplate_min_videos: usize,
+  /// ...and the share of the channel's transcripts it must appear in.
+  pub boilerplate_min_share: f64,
+  /// Share of an utterance's word shingles that must be boilerplate to suppress it.
+  pub boilerplate_coverage: f64,
+}
+
+impl Default for ClassifierConfig {
+  fn default() -> Self {
+    Self {
+      repetition_similarity: 0.85,
+      repetition_window: 10,
+      filler_ratio: 0.5,
+      topic_gap_secs: 5.0,
+      topic_max_secs: 120.0,
+      key_min_words: 12,
+      key_min_density: 0.7,
+      max_topics: 30,
+      max_key_segments: 50,
+      topic_summary_sentences: 3,
+      abstract_sentences: 5,
+      topic_keywords: 5,
+      video_keywords: 15,
+      qa_answer_secs: 60.0,
+      max_qa_pairs: 20,
+      boilerplate_min_videos: 3,
+      boilerplate_min_share: 0.3,
+      boilerplate_coverage: 0.6,
+    }
+  }
+}
+
+impl ClassifierConfig {
+  /// Load the `[classifier]` table from the user config, or defaults (an error if the config is broken).
+  pub fn load() -> Result<Self> {
+    Ok(crate::config::Config::read()?.classifier.unwrap_or_default())
+  }
+
+  /// Apply `key=value` overrides in order (later ones win).
+  pub fn apply_overrides(&mut self, overrides: &[String]) -> Result<()> {
+    for item in overrides {
+      let (key, value) = item.split_once('=').ok_or_else(|| {
+        error::fail(ErrorKind::InvalidInput, format!("Invalid classifier override `{item}`: expected key=value"))
+      })?;
+      self.set(key.trim(), value.trim()).map_err(|e| error::or_kind(e, ErrorKind::InvalidInput))?;
+    }
+    Ok(())
+  }
+
+  /// Set a single field by name, validating the value against the field's type.
+  fn set(&mut self, key: &str, value: &str) -> Result<()> {
+    let mut obj = serde_json::to_value(&*self).context("Failed to serialize classifier config")?;
+    let slot = obj.get_mut(key).ok_or_else(|| {
+      let known: Vec<String> = self.settings().into_iter().map(|(k, _)| k).collect();
+      anyhow!("Unknown classifier setting `{key}` (known: {})", known.join(", "))
+    })?;
+    *slot =
+      serde_json::from_str(value).map_err(|_| anyhow!("Invalid value `{value}` for classifier setting `{key}`"))?;
+    *self = serde_json::from_value(obj).map_err(|e| anyhow!("Invalid value `{value}` for `{key}`: {e}"))?;
+    Ok(())
+  }
+
+  /// The settings as sorted `(key, value)` pairs.
+  fn settings(&self) -> Vec<(String, String)> {
+    match serde_json::to_value(self) {
+      Ok(serde_json::Value::Object(map)) => map.into_iter().map(|(k, v)| (k, v.to_string())).collect(),
+      _ => Vec::new(),
+    }
+  }
+
+  /// One-line `key=value` rendering for the output `_hint`.
+  pub fn describe(&self) -> String {
+    self.settings().into_iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>().join(", ")
+  }
+}
 
 // ---------------------------------------------------------------------------
 // Classification types
@@ -23,8 +150,12 @@ pub enum UtteranceClass {
   Filler,
   /// Structurally similar to a recent utterance.
   Repetition,
+  /// The channel's recurring intro/outro, learned across its videos (see `boilerplate`).
+  Boilerplate,
   /// Semantic boundary — significant time gap or periodic marker.
   TopicShift,
+  /// A question: question mark, or a wh-word or inverted auxiliary up front.
+  Question,
   /// High information density (long, many unique words).
   KeySegment,
   /// Standard spoken content.
@@ -95,7 +226,7 @@ const NON_SPEECH_PATTERNS: &[&str] = &[
 // ---------------------------------------------------------------------------
 
 /// Normalize text for comparison: lowercase, strip punctuation, collapse whitespace.
-fn normalize(text: &str) -> String {
+pub(crate) fn normalize(text: &str) -> String {
   text
     .to_lowercase()
     .chars()
@@ -124,7 +255,7 @@ fn word_similarity(a: &str, b: &str) -> f64 {
 /// Checks against the **lowercased original text** (not the normalized form)
 /// because non-speech patterns like `[Music]` contain brackets that
 /// `normalize()` strips.
-fn is_non_speech(text: &str) -> bool {
+pub(crate) fn is_non_speech(text: &str) -> bool {
   let lower = text.to_lowercase();
   let trimmed = lower.trim();
   if trimmed.is_empty() {
@@ -133,9 +264,9 @@ fn is_non_speech(text: &str) -> bool {
   NON_SPEECH_PATTERNS.iter().any(|p| trimmed.contains(p))
 }
 
-/// Check if >50% of words are filler tokens.
+/// Check if more than `threshold` of the words are filler tokens.
 #[allow(clippy::cast_precision_loss)]
-fn is_filler(normalized: &str) -> bool {
+fn is_filler(normalized: &str, threshold: f64) -> bool {
   let words: Vec<&str> = normalized.split_whitespace().collect();
   if words.is_empty() {
     return false;
@@ -158,7 +289,80 @@ fn is_filler(normalized: &str) -> bool {
   // Use word count as denominator; filler_total can exceed words.len() due to
   // overlap between phrase and single-word counts, so cap the ratio at 1.0.
   let ratio = (filler_total as f64 / words.len() as f64).min(1.0);
-  ratio > 0.5
+  ratio > threshold
+}
+
+/// Question words that open a wh-question.
+const WH_WORDS: &[&str] = &["who", "whom", "whose", "what", "when", "where", "why", "how", "which"];
+
+/// Auxiliaries and modals that open an inverted (yes/no) question, or follow a wh-word.
+const AUXILIARIES: &[&str] = &[
+  "is",
+  "are",
+  "was",
+  "were",
+  "am",
+  "do",
+  "does",
+  "did",
+  "can",
+  "could",
+  "will",
+  "would",
+  "should",
+  "shall",
+  "may",
+  "might",
+  "must",
+  "have",
+  "has",
+  "had",
+  "isn't",
+  "aren't",
+  "wasn't",
+  "weren't",
+  "don't",
+  "doesn't",
+  "didn't",
+  "can't",
+  "couldn't",
+  "won't",
+  "wouldn't",
+  "shouldn't",
+  "haven't",
+  "hasn't",
+];
+
+/// Subjects that follow the auxiliary in an inverted question ("do *you*", "is *it*").
+const SUBJECTS: &[&str] = &[
+  "i", "you", "we", "they", "he", "she", "it", "there", "this", "that", "these", "those", "anyone", "anybody",
+  "someone", "somebody", "everyone", "people", "the", "your", "my", "our",
+];
+
+/// Words after "how" that make a question without an auxiliary ("how many layers").
+const HOW_MODIFIERS: &[&str] = &["many", "much", "long", "often", "far", "come"];
+
+/// Whether an utterance reads as a question.
+///
+/// Whisper often drops the question mark, so besides a trailing `?` this
+/// accepts a wh-word followed by an auxiliary ("how do you…", "what's…") and
+/// subject-auxiliary inversion ("do you…", "can we…"). Very short utterances
+/// ("right?", "okay?") are tag questions, not real ones.
+fn is_question(text: &str) -> bool {
+  let words: Vec<String> = textrank::words(text);
+  if words.len() < 3 {
+    return false;
+  }
+  if text.trim_end().trim_end_matches(['"', '\'', ')', '”', '’']).ends_with('?') {
+    return true;
+  }
+  let (first, second) = (words[0].as_str(), words[1].as_str());
+  if let Some(wh) = first.split('\'').next()
+    && WH_WORDS.contains(&wh)
+  {
+    return first.contains('\'') || AUXILIARIES.contains(&second) || (wh == "how" && HOW_MODIFIERS.contains(&second));
+  }
+  AUXILIARIES.contains(&first) && SUBJECTS.contains(&second)
 }
 
 /// Classify a sequence of raw whisper utterances.
@@ -166,9 +370,10 @@ fn is_filler(normalized: &str) -> bool {
 /// `utterances` should be `(start_centiseconds, stop_centiseconds, text)` triples
 /// as produced by `whisper_cli::Utternace`.
 #[allow(clippy::cast_precision_loss)]
-pub fn classify(utterances: &[(i64, i64, String)]) -> Vec<ClassifiedUtterance> {
+pub fn classify(utterances: &[(i64, i64, String)], cfg: &ClassifierConfig) -> Vec<ClassifiedUtterance> {
   // Sliding window of recent normalized forms for repetition detection.
-  let mut recent_window: Vec<String> = Vec::with_capacity(10);
+  let window = cfg.repetition_window.max(1);
+  let mut recent_window: Vec<String> = Vec::with_capacity(window);
   let mut last_end_secs: f64 = 0.0;
   let mut since_last_topic: f64 = 0.0;
 
@@ -186,17 +391,18 @@ pub fn classify(utterances: &[(i64, i64, String)]) -> Vec<ClassifiedUtterance> {
       }
 
       // Priority 2: Filler
-      if is_filler(&normalized) {
+      if is_filler(&normalized, cfg.filler_ratio) {
         last_end_secs = end;
         since_last_topic += end - start;
         return ClassifiedUtterance { start, end, text: text.clone(), class: UtteranceClass::Filler };
       }
 
-      // Priority 3: Repetition (Jaccard similarity > 0.85 with any recent utterance)
-      let is_repetition = recent_window.iter().any(|prev| word_similarity(&normalized, prev) > 0.85);
+      // Priority 3: Repetition (Jaccard similarity above threshold with any recent utterance)
+      let is_repetition =
+        recent_window.iter().any(|prev| word_similarity(&normalized, prev) > cfg.repetition_similarity);
       if is_repetition {
         // Still add to window so we can detect chains of repetition
-        if recent_window.len() >= 10 {
+        if recent_window.len() >= window {
           recent_window.remove(0);
         }
         recent_window.push(normalized);
@@ -205,12 +411,12 @@ pub fn classify(utterances: &[(i64, i64, String)]) -> Vec<ClassifiedUtterance> {
         return ClassifiedUtterance { start, end, text: text.clone(), class: UtteranceClass::Repetition };
       }
 
-      // Priority 4: Topic shift (time gap > 5s or accumulated time > 120s)
+      // Priority 4: Topic shift (long time gap or too much accumulated time)
       let gap = start - last_end_secs;
-      let is_topic_shift = gap > 5.0 || since_last_topic > 120.0;
+      let is_topic_shift = gap > cfg.topic_gap_secs || since_last_topic > cfg.topic_max_secs;
       if is_topic_shift {
         since_last_topic = 0.0;
-        if recent_window.len() >= 10 {
+        if recent_window.len() >= window {
           recent_window.remove(0);
         }
         recent_window.push(normalized);
@@ -218,14 +424,25 @@ pub fn classify(utterances: &[(i64, i64, String)]) -> Vec<ClassifiedUtterance> {
         return ClassifiedUtterance { start, end, text: text.clone(), class: UtteranceClass::TopicShift };
       }
 
-      // Priority 5: Key segment (long utterance with high unique-word density)
+      // Priority 5: Question (interrogative cues)
+      if is_question(text) {
+        if recent_window.len() >= window {
+          recent_window.remove(0);
+        }
+        recent_window.push(normalized);
+        last_end_secs = end;
+        since_last_topic += end - start;
+        return ClassifiedUtterance { start, end, text: text.clone(), class: UtteranceClass::Question };
+      }
+
+      // Priority 6: Key segment (long utterance with high unique-word density)
       let words: Vec<&str> = normalized.split_whitespace().collect();
       let unique: std::collections::HashSet<&&str> = words.iter().collect();
       let density = if words.is_empty() { 0.0 } else { unique.len() as f64 / words.len() as f64 };
-      let is_key = words.len() >= 12 && density >= 0.7;
+      let is_key = words.len() >= cfg.key_min_words && density >= cfg.key_min_density;
       let class = if is_key { UtteranceClass::KeySegment } else { UtteranceClass::Normal };
 
-      if recent_window.len() >= 10 {
+      if recent_window.len() >= window {
         recent_window.remove(0);
       }
       recent_window.push(normalized);
@@ -246,9 +463,11 @@ pub fn classify(utterances: &[(i64, i64, String)]) -> Vec<ClassifiedUtterance> {
 pub struct TopicSegment {
   pub start_secs: f64,
   pub end_secs: f64,
-  /// Representative sentence (first utterance in the segment).
+  /// Extractive summary: the segment's most central sentences by TextRank.
   pub summary: String,
   pub utterance_count: u64,
+  /// Keyphrases that set this topic apart from the rest of the video.
+  pub keywords: Vec<Keyword>,
 }
 
 /// A high-information moment with timestamp and text.
@@ -258,6 +477,17 @@ pub struct KeySegment {
   pub text: String,
 }
 
+/// A question and the speech that follows it, up to the next question or topic shift.
+#[derive(Debug, Clone, Serialize)]
+pub struct QaPair {
+  pub asked_at_secs: f64,
+  pub question: String,
+  /// Empty when the question was followed directly by another question or the end.
+  pub answer: String,
+  pub answer_start_secs: f64,
+  pub answer_end_secs: f64,
+}
+
 /// Summary statistics from the reduce phase.
 #[derive(Debug, Clone, Serialize)]
 pub struct SummaryStats {
@@ -266,8 +496,15 @@ pub struct SummaryStats {
   pub suppressed_utterances: u64,
   pub filler_ratio: f64,
   pub non_speech_secs: f64,
+  /// Extractive abstract of the whole video (TextRank over all kept sentences).
+  #[serde(rename = "abstract")]
+  pub abstract_text: String,
+  /// Keyphrases for the whole video, best first.
+  pub keywords: Vec<Keyword>,
   pub topics: Vec<TopicSegment>,
   pub key_segments: Vec<KeySegment>,
+  /// Questions paired with their answers, in order.
+  pub qa_pairs: Vec<QaPair>,
 }
 
 /// Full summary output: hint + video metadata + summary + filtered utterances.
@@ -276,27 +513,124 @@ pub struct SummaryOutput {
   pub _hint: String,
   pub video: VideoDetails,
   pub summary: SummaryStats,
+  /// LLM-generated abstract, takeaways and topic titles (`--llm` only).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub llm: Option<LlmSummary>,
   pub utterances: Vec<ClassifiedUtterance>,
 }
 
+#[cfg(test)]
+impl SummaryOutput {
+  /// An empty summary of a video titled `title` spanning `0..end` seconds, for
+  /// tests to fill in: the one literal to extend when these structs grow a field.
+  pub(crate) fn fixture(title: &str, end: f64) -> Self {
+    Self {
+      _hint: String::new(),
+      video: VideoDetails {
+        url: String::new(),
+        title: title.to_string(),
+        uploader: None,
+        duration: None,
+        upload_date: None,
+        view_count: None,
+        tags: Vec::new(),
+      },
+      summary: SummaryStats {
+        time_range: (0.0, end),
+        total_utterances: 0,
+        suppressed_utterances: 0,
+        filler_ratio: 0.0,
+        non_speech_secs: 0.0,
+        abstract_text: String::new(),
+        keywords: Vec::new(),
+        topics: Vec::new(),
+        key_segments: Vec::new(),
+        qa_pairs: Vec::new(),
+      },
+      llm: None,
+      utterances: Vec::new(),
+    }
+  }
+}
+
 // ---------------------------------------------------------------------------
 // Reduce logic
 // ---------------------------------------------------------------------------
 
-/// Maximum number of topic segments in the output.
-const MAX_TOPICS: usize = 30;
+/// Whether an utterance survives suppression (Normal, `TopicShift`, Question, `KeySegment`).
+pub(crate) fn is_kept(class: &UtteranceClass) -> bool {
+  matches!(
+    class,
+    UtteranceClass::Normal | UtteranceClass::TopicShift | UtteranceClass::Question | UtteranceClass::KeySegment
+  )
+}
 
-/// Maximum number of key segments in the output.
-const MAX_KEY_SEGMENTS: usize = 50;
+/// Whether an utterance asks a question. A question after a long pause is
+/// tagged `TopicShift` (topic boundaries take priority) but still counts here.
+fn asks(u: &ClassifiedUtterance) -> bool {
+  u.class == UtteranceClass::Question || (u.class == UtteranceClass::TopicShift && is_question(&u.text))
+}
+
+/// Pair each question with the kept speech after it.
+///
+/// The answer runs until the next question, the next topic shift (a shift
+/// right after the question still counts — speakers pause before answering),
+/// or `cfg.qa_answer_secs` of speech. Suppressed utterances inside the span are
+/// skipped.
+fn qa_pairs(classified: &[ClassifiedUtterance], cfg: &ClassifierConfig) -> Vec<QaPair> {
+  let mut pairs = Vec::new();
+  for (i, q) in classified.iter().enumerate() {
+    if !asks(q) {
+      continue;
+    }
+    let mut answer: Vec<&ClassifiedUtterance> = Vec::new();
+    for u in &classified[i + 1..] {
+      let ends_answer = asks(u)
+        || (u.class == UtteranceClass::TopicShift && !answer.is_empty())
+        || answer.first().is_some_and(|first| u.end - first.start > cfg.qa_answer_secs);
+      if ends_answer {
+        break;
+      }
+      if is_kept(&u.class) {
+        answer.push(u);
+      }
+    }
+    pairs.push(QaPair {
+      asked_at_secs: q.start,
+      question: q.text.trim().to_string(),
+      answer: answer.iter().map(|u| u.text.trim()).collect::<Vec<_>>().join(" "),
+      answer_start_secs: answer.first().map_or(q.end, |u| u.start),
+      answer_end_secs: answer.last().map_or(q.end, |u| u.end),
+    });
+    if pairs.len() >= cfg.max_qa_pairs {
+      break;
+    }
+  }
+  pairs
+}
+
+/// Summarize one topic with its top TextRank sentences: one per ~5 kept
+/// sentences, capped at `cfg.topic_summary_sentences`. Falls back to the
+/// topic's first utterance when every sentence in it was suppressed.
+fn topic_summary(first: &str, sentences: &[(f64, &str)], cfg: &ClassifierConfig) -> String {
+  if sentences.is_empty() {
+    return first.to_string();
+  }
+  let texts: Vec<&str> = sentences.iter().map(|(_, text)| *text).collect();
+  let count = (texts.len() / 5).clamp(1, cfg.topic_summary_sentences.max(1));
+  textrank::summarize(&texts, count)
+}
 
 /// Reduce classified utterances into a bounded summary.
 ///
-/// - Suppresses `NonSpeech`, Filler, and Repetition
+/// - Suppresses `NonSpeech`, Filler, Repetition, and Boilerplate
 /// - Groups utterances into topic segments (split at `TopicShift` boundaries)
-/// - Extracts key segments
-/// - Caps output to `MAX_TOPICS` topics and `MAX_KEY_SEGMENTS` key moments
+/// - Summarizes each topic and the whole video with TextRank
+/// - Extracts key segments and pairs questions with their answers
+/// - Caps output to `cfg.max_topics` topics, `cfg.max_key_segments` key moments
+///   and `cfg.max_qa_pairs` Q&A pairs
 #[allow(clippy::cast_precision_loss)]
-pub fn reduce(video: &VideoDetails, classified: &[ClassifiedUtterance]) -> SummaryOutput {
+pub fn reduce(video: &VideoDetails, classified: &[ClassifiedUtterance], cfg: &ClassifierConfig) -> SummaryOutput {
   let total_utterances = classified.len() as u64;
 
   // Compute statistics
@@ -314,7 +648,7 @@ pub fn reduce(video: &VideoDetails, classified: &[ClassifiedUtterance]) -> Summa
         filler_count += 1;
         suppressed += 1;
       }
-      UtteranceClass::Repetition => {
+      UtteranceClass::Repetition | UtteranceClass::Boilerplate => {
         suppressed += 1;
       }
       _ => {}
@@ -332,7 +666,9 @@ pub fn reduce(video: &VideoDetails, classified: &[ClassifiedUtterance]) -> Summa
   // Build topic segments: split at TopicShift boundaries
   let mut topics: Vec<TopicSegment> = Vec::new();
   let mut current_topic_start: Option<f64> = None;
-  let mut current_topic_summary: Option<String> = None;
+  let mut current_topic_first: &str = "";
+  let mut current_topic_texts: Vec<(f64, &str)> = Vec::new();
+  let mut topic_sentences: Vec<Vec<(f64, &str)>> = Vec::new();
   let mut current_topic_count: u64 = 0;
 
   for u in classified {
@@ -346,22 +682,28 @@ pub fn reduce(video: &VideoDetails, classified: &[ClassifiedUtterance]) -> Summa
         topics.push(TopicSegment {
           start_secs: start,
           end_secs: u.start,
-          summary: current_topic_summary.take().unwrap_or_default(),
+          summary: topic_summary(current_topic_first, &current_topic_texts, cfg),
           utterance_count: current_topic_count,
+          keywords: Vec::new(),
         });
+        topic_sentences.push(std::mem::take(&mut current_topic_texts));
       }
       // Start new topic
       current_topic_start = Some(u.start);
-      current_topic_summary = Some(u.text.clone());
+      current_topic_first = &u.text;
+      current_topic_texts.clear();
       current_topic_count = 1;
     } else {
       if current_topic_start.is_none() {
         // First non-topic-shift utterance starts an implicit first topic
         current_topic_start = Some(u.start);
-        current_topic_summary = Some(u.text.clone());
+        current_topic_first = &u.text;
       }
       current_topic_count += 1;
     }
+    if is_kept(&u.class) {
+      current_topic_texts.push((u.start, &u.text));
+    }
   }
 
   // Close the last topic
@@ -369,13 +711,20 @@ pub fn reduce(video: &VideoDetails, classified: &[ClassifiedUtterance]) -> Summa
     topics.push(TopicSegment {
       start_secs: start,
       end_secs: time_range.1,
-      summary: current_topic_summary.unwrap_or_default(),
+      summary: topic_summary(current_topic_first, &current_topic_texts, cfg),
       utterance_count: current_topic_count,
+      keywords: Vec::new(),
     });
+    topic_sentences.push(current_topic_texts);
+  }
+
+  // Keyphrases per topic (IDF across all topics, before capping)
+  for (topic, keywords) in topics.iter_mut().zip(keywords::topic_keywords(&topic_sentences, cfg.topic_keywords)) {
+    topic.keywords = keywords;
   }
 
   // Cap topics
-  topics.truncate(MAX_TOPICS);
+  topics.truncate(cfg.max_topics);
 
   // Extract key segments
   let mut key_segments: Vec<KeySegment> = classified
@@ -383,18 +732,30 @@ pub fn reduce(video: &VideoDetails, classified: &[ClassifiedUtterance]) -> Summa
     .filter(|u| u.class == UtteranceClass::KeySegment)
     .map(|u| KeySegment { at_secs: u.start, text: u.text.clone() })
     .collect();
-  key_segments.truncate(MAX_KEY_SEGMENTS);
+  key_segments.truncate(cfg.max_key_segments);
 
-  // Build filtered utterance list (keep Normal, TopicShift, KeySegment)
-  let utterances: Vec<ClassifiedUtterance> = classified
-    .iter()
-    .filter(|u| matches!(u.class, UtteranceClass::Normal | UtteranceClass::TopicShift | UtteranceClass::KeySegment))
-    .cloned()
-    .collect();
+  let qa_pairs = qa_pairs(classified, cfg);
 
+  // Build filtered utterance list (keep Normal, TopicShift, Question, KeySegment)
+  let utterances: Vec<ClassifiedUtterance> = classified.iter().filter(|u| is_kept(&u.class)).cloned().collect();
+
+  let kept_texts: Vec<&str> = utterances.iter().map(|u| u.text.as_str()).collect();
+  let abstract_text = textrank::summarize(&kept_texts, cfg.abstract_sentences);
+  let kept_sentences: Vec<(f64, &str)> = utterances.iter().map(|u| (u.start, u.text.as_str())).collect();
+  let keywords = keywords::video_keywords(&kept_sentences, cfg.video_keywords);
+
+  let qa_note = if qa_pairs.is_empty() {
+    String::new()
+  } else {
+    let n = qa_pairs.len();
+    format!("{n} question{} paired with answers in summary.qa_pairs. ", if n == 1 { " is" } else { "s are" })
+  };
   let hint = format!(
-    "YouTube video transcript summary. Summarize mode: filler, music, silence, and repeated utterances suppressed. \
-     {suppressed} of {total_utterances} utterances omitted. Full transcript available with --raw."
+    "YouTube video transcript summary. Summarize mode: filler, music, silence, repeated utterances, and channel \
+     boilerplate suppressed. \
+     {suppressed} of {total_utterances} utterances omitted. Full transcript available with --raw. {qa_note}\
+     Classifier settings: {}.",
+    cfg.describe()
   );
 
   SummaryOutput {
@@ -406,13 +767,603 @@ pub fn reduce(video: &VideoDetails, classified: &[ClassifiedUtterance]) -> Summa
       suppressed_utterances: suppressed,
       filler_ratio,
       non_speech_secs,
+      abstract_text,
+      keywords,
       topics,
       key_segments,
+      qa_pairs,
     },
+    llm: None,
     utterances,
   }
 }
 
+// ---------------------------------------------------------------------------
+// Token budget
+// ---------------------------------------------------------------------------
+
+/// Rough token estimate for `len` characters of output: ~4 characters per token.
+fn estimate_tokens(len: usize) -> usize {
+  len.div_ceil(4)
+}
+
+/// Characters set aside for the budget note appended to `_hint`.
+const BUDGET_HINT_RESERVE: usize = 256;
+
+/// Smallest `k` in `0..=max` for which `fits(k)` holds, assuming that once it
+/// holds it keeps holding; `max` when it never does.
+fn fewest(max: usize, fits: impl Fn(usize) -> bool) -> usize {
+  let (mut lo, mut hi) = (0, max);
+  while lo < hi {
+    let mid = lo + (hi - lo) / 2;
+    if fits(mid) { hi = mid } else { lo = mid + 1 }
+  }
+  lo
+}
+
+/// `output` without the utterances at `indices`.
+fn without_utterances(output: &SummaryOutput, indices: &[usize]) -> SummaryOutput {
+  let mut drop = vec![false; output.utterances.len()];
+  for &i in indices {
+    drop[i] = true;
+  }
+  let mut trimmed = output.clone();
+  let mut keep = drop.iter().map(|d| !d);
+  trimmed.utterances.retain(|_| keep.next().unwrap_or(true));
+  trimmed
+}
+
+/// What `apply_token_budget` removed to fit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BudgetReport {
+  pub max_tokens: usize,
+  pub estimated_tokens: usize,
+  pub dropped_normal: usize,
+  pub shortened_topics: usize,
+  pub dropped_other: usize,
+}
+
+impl BudgetReport {
+  /// Whether the output still exceeds the budget after every trimming step.
+  pub fn over_budget(&self) -> bool {
+    self.estimated_tokens > self.max_tokens
+  }
+}
+
+/// Cut a topic summary down to its first sentence (or 160 characters).
+pub(crate) fn first_sentence(text: &str) -> String {
+  let end = text.find(['.', '!', '?']).map_or(text.len(), |i| i + 1);
+  let sentence = &text[..end];
+  if sentence.chars().count() <= 160 {
+    return sentence.trim().to_string();
+  }
+  let cut: String = sentence.chars().take(159).collect();
+  format!("{}…", cut.trim_end())
+}
+
+/// Trim a summary until its rendering in `format` fits `max_tokens`.
+///
+/// Steps, each only taken while still over budget:
+/// 1. Drop `Normal` utterances, lowest TextRank score first
+/// 2. Shorten topic summaries to one sentence and drop per-topic keywords
+/// 3. Drop the remaining `TopicShift`/`Question`/`KeySegment` utterances, latest first
+///
+/// Utterance steps are skipped for Markdown and text without `with_transcript`,
+/// which don't print utterances. Video metadata, the abstract,
+/// `summary.key_segments` and `summary.qa_pairs` are always kept. The outcome
+/// is appended to `_hint`.
+pub fn apply_token_budget(
+  output: &mut SummaryOutput,
+  max_tokens: usize,
+  format: SummaryFormat,
+  with_transcript: bool,
+) -> BudgetReport {
+  // Work in characters of the printed output (tokens ≈ chars / 4).
+  let rendered_len = |o: &SummaryOutput| render(o, format, with_transcript).map_or(0, |r| r.len());
+  let len = rendered_len(output);
+  let mut report = BudgetReport { max_tokens, estimated_tokens: estimate_tokens(len), ..BudgetReport::default() };
+  if len <= max_tokens.saturating_mul(4) {
+    return report;
+  }
+  let max_len = max_tokens.saturating_mul(4).saturating_sub(BUDGET_HINT_RESERVE);
+  let fits = |o: &SummaryOutput| rendered_len(o) <= max_len;
+  let prints_utterances = with_transcript || matches!(format, SummaryFormat::Json | SummaryFormat::Yaml);
+
+  // 1. Normal utterances, least central first: as few as fit.
+  if prints_utterances {
+    let texts: Vec<&str> = output.utterances.iter().map(|u| u.text.as_str()).collect();
+    let scores = textrank::rank(&texts);
+    let mut normal: Vec<usize> =
+      (0..output.utterances.len()).filter(|&i| output.utterances[i].class == UtteranceClass::Normal).collect();
+    normal.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]).then(b.cmp(&a)));
+    let count = fewest(normal.len(), |k| fits(&without_utterances(output, &normal[..k])));
+    *output = without_utterances(output, &normal[..count]);
+    report.dropped_normal = count;
+  }
+
+  // 2. Shorter topics.
+  for i in 0..output.summary.topics.len() {
+    if fits(output) {
+      break;
+    }
+    let topic = &mut output.summary.topics[i];
+    topic.summary = first_sentence(&topic.summary);
+    topic.keywords.clear();
+    report.shortened_topics += 1;
+  }
+
+  // 3. Whatever utterances remain, from the end (key segments survive in the summary).
+  if prints_utterances && !fits(output) {
+    let total = output.utterances.len();
+    let count = fewest(total, |k| {
+      let mut trimmed = output.clone();
+      trimmed.utterances.truncate(total - k);
+      fits(&trimmed)
+    });
+    output.utterances.truncate(total - count);
+    report.dropped_other = count;
+  }
+
+  output._hint.push_str(&format!(
+    " Token budget {max_tokens}: dropped {} normal utterances (least central first), shortened {} topics, \
+     dropped {} other utterances.",
+    report.dropped_normal, report.shortened_topics, report.dropped_other
+  ));
+  report.estimated_tokens = estimate_tokens(rendered_len(output));
+  if report.over_budget() {
+    output._hint.push_str(&format!(
+      " Still ~{} tokens: metadata, abstract and key segments are never trimmed.",
+      report.estimated_tokens
+    ));
+  }
+  report
+}
+
+// ---------------------------------------------------------------------------
+// Rendering
+// ---------------------------------------------------------------------------
+
+/// Output format for a `SummaryOutput`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SummaryFormat {
+  /// Pretty-printed JSON (the default, for machines).
+  #[default]
+  Json,
+  /// Markdown note: title block, metadata table, linked topics and key moments.
+  #[value(alias = "markdown")]
+  Md,
+  /// Plain text for terminals and emails.
+  #[value(alias = "text")]
+  Txt,
+  /// The JSON structure as YAML.
+  #[value(alias = "yml")]
+  Yaml,
+}
+
+impl SummaryFormat {
+  /// What goes between rendered documents in a multi-video run (piped IDs or
+  /// `--latest`). JSON has none: those runs print one JSONL record per video.
+  pub fn separator(self) -> Option<&'static str> {
+    match self {
+      SummaryFormat::Json => None,
+      SummaryFormat::Yaml => Some("---\n"),
+      SummaryFormat::Md | SummaryFormat::Txt => Some("\n---\n\n"),
+    }
+  }
+}
+
+/// Format seconds as `m:ss` or `h:mm:ss`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn timestamp(secs: f64) -> String {
+  let total = secs.max(0.0) as u64;
+  let (h, m, s) = (total / 3600, (total % 3600) / 60, total % 60);
+  if h > 0 { format!("{h}:{m:02}:{s:02}") } else { format!("{m}:{s:02}") }
+}
+
+/// Link to a moment in the video (`&t=123s`), or `None` without a URL.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn moment_url(url: &str, secs: f64) -> Option<String> {
+  if url.is_empty() {
+    return None;
+  }
+  let sep = if url.contains('?') { '&' } else { '?' };
+  Some(format!("{url}{sep}t={}s", secs.max(0.0) as u64))
+}
+
+/// yt-dlp's `20240301` as `2024-03-01`; anything else unchanged.
+pub(crate) fn display_date(date: &str) -> String {
+  if date.len() == 8 && date.chars().all(|c| c.is_ascii_digit()) {
+    format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..])
+  } else {
+    date.to_string()
+  }
+}
+
+/// Label/value rows describing the video, skipping unknown fields.
+fn metadata_rows(video: &VideoDetails) -> Vec<(&'static str, String)> {
+  let mut rows = Vec::new();
+  if let Some(uploader) = &video.uploader {
+    rows.push(("Channel", uploader.clone()));
+  }
+  if let Some(date) = &video.upload_date {
+    rows.push(("Uploaded", display_date(date)));
+  }
+  if let Some(duration) = &video.duration {
+    rows.push(("Duration", duration.clone()));
+  }
+  if let Some(views) = &video.view_count {
+    rows.push(("Views", views.clone()));
+  }
+  if !video.tags.is_empty() {
+    rows.push(("Tags", video.tags.join(", ")));
+  }
+  if !video.url.is_empty() {
+    rows.push(("URL", video.url.clone()));
+  }
+  rows
+}
+
+/// The LLM title for the topic starting at `start_secs`, if `--llm` produced one.
+pub(crate) fn topic_title(output: &SummaryOutput, start_secs: f64) -> Option<&str> {
+  let llm = output.llm.as_ref()?;
+  llm.topic_titles.iter().find(|t| (t.start_secs - start_secs).abs() < 0.5).map(|t| t.title.as_str())
+}
+
+/// One-line footer with the classifier's bookkeeping.
+fn stats_line(summary: &SummaryStats) -> String {
+  format!(
+    "{} of {} utterances kept · {:.0}% filler · {:.0}s non-speech",
+    summary.total_utterances.saturating_sub(summary.suppressed_utterances),
+    summary.total_utterances,
+    summary.filler_ratio * 100.0,
+    summary.non_speech_secs
+  )
+}
+
+/// Render a summary in the requested format.
+///
+/// `with_transcript` appends the filtered transcript to Markdown and text
+/// output; JSON and YAML always carry it in `utterances`.
+pub fn render(output: &SummaryOutput, format: SummaryFormat, with_transcript: bool) -> Result<String> {
+  match format {
+    SummaryFormat::Json => {
+      serde_json::to_string_pretty(&crate::schema::versioned(output)).context("Failed to serialize summary")
+    }
+    SummaryFormat::Yaml => to_yaml(&crate::schema::versioned(output)),
+    SummaryFormat::Md => Ok(to_markdown(output, with_transcript)),
+    SummaryFormat::Txt => Ok(to_text(output, with_transcript)),
+  }
+}
+
+/// A failed video's document in a multi-video run: its `{video_id, status:
+/// "error", error}` record in the summary's format.
+pub fn render_error(video_id: &str, error: &serde_json::Value, format: SummaryFormat) -> Result<String> {
+  let record = serde_json::json!({ "video_id": video_id, "status": "error", "error": error });
+  let (code, message) = (error["code"].as_str().unwrap_or_default(), error["message"].as_str().unwrap_or_default());
+  match format {
+    SummaryFormat::Json => {
+      serde_json::to_string(&crate::schema::versioned(&record)).context("Failed to serialize error record")
+    }
+    SummaryFormat::Yaml => to_yaml(&crate::schema::versioned(&record)),
+    SummaryFormat::Md => Ok(format!("# {video_id}\n\n**Error** (`{code}`): {message}\n")),
+    SummaryFormat::Txt => Ok(format!("{video_id}\n  Error ({code}): {message}\n")),
+  }
+}
+
+/// Escape text for a Markdown table cell.
+pub(crate) fn md_cell(text: &str) -> String {
+  text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// A timestamp, linked to that moment of the video when the URL is known.
+pub(crate) fn md_timestamp(url: &str, secs: f64) -> String {
+  match moment_url(url, secs) {
+    Some(link) => format!("[{}]({link})", timestamp(secs)),
+    None => timestamp(secs),
+  }
+}
+
+/// The filtered transcript grouped into paragraphs, for `--with-transcript` exports.
+#[allow(clippy::cast_possible_truncation)]
+fn transcript_paragraphs(output: &SummaryOutput) -> Vec<crate::segment::Paragraph> {
+  let sentences: Vec<(i64, i64, String)> = output
+    .utterances
+    .iter()
+    .map(|u| ((u.start * 100.0).round() as i64, (u.end * 100.0).round() as i64, u.text.clone()))
+    .collect();
+  crate::segment::paragraphs(&sentences)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn cs_to_secs(cs: i64) -> f64 {
+  cs as f64 / 100.0
+}
+
+/// Markdown note ready to paste into Obsidian or a PR description.
+pub fn to_markdown(output: &SummaryOutput, with_transcript: bool) -> String {
+  let video = &output.video;
+  let summary = &output.summary;
+  let mut md = format!("# {}\n\n", video.title.trim());
+
+  let rows = metadata_rows(video);
+  if !rows.is_empty() {
+    md.push_str("| | |\n|---|---|\n");
+    for (label, value) in rows {
+      md.push_str(&format!("| {label} | {} |\n", md_cell(&value)));
+    }
+    md.push('\n');
+  }
+
+  let abstract_text = output.llm.as_ref().map_or(summary.abstract_text.as_str(), |llm| llm.abstract_text.as_str());
+  if !abstract_text.is_empty() {
+    md.push_str(&format!("## Summary\n\n{abstract_text}\n\n"));
+  }
+
+  if let Some(llm) = &output.llm
+    && !llm.takeaways.is_empty()
+  {
+    md.push_str("## Takeaways\n\n");
+    for takeaway in &llm.takeaways {
+      md.push_str(&format!("- {takeaway}\n"));
+    }
+    md.push('\n');
+  }
+
+  if !summary.keywords.is_empty() {
+    let keywords: Vec<String> = summary.keywords.iter().map(|k| format!("`{}`", k.phrase)).collect();
+    md.push_str(&format!("**Keywords:** {}\n\n", keywords.join(" · ")));
+  }
+
+  if !summary.topics.is_empty() {
+    md.push_str("## Topics\n\n");
+    for (i, topic) in summary.topics.iter().enumerate() {
+      let at = md_timestamp(&video.url, topic.start_secs);
+      match topic_title(output, topic.start_secs) {
+        Some(title) => md.push_str(&format!("{}. {at} **{title}** — {}\n", i + 1, topic.summary)),
+        None => md.push_str(&format!("{}. {at} {}\n", i + 1, topic.summary)),
+      }
+      if !topic.keywords.is_empty() {
+        let keywords: Vec<&str> = topic.keywords.iter().map(|k| k.phrase.as_str()).collect();
+        md.push_str(&format!("   *{}*\n", keywords.join(", ")));
+      }
+    }
+    md.push('\n');
+  }
+
+  if !summary.key_segments.is_empty() {
+    md.push_str("## Key moments\n\n");
+    for segment in &summary.key_segments {
+      md.push_str(&format!("- {} {}\n", md_timestamp(&video.url, segment.at_secs), segment.text.trim()));
+    }
+    md.push('\n');
+  }
+
+  if !summary.qa_pairs.is_empty() {
+    md.push_str("## Q&A\n\n");
+    for pair in &summary.qa_pairs {
+      md.push_str(&format!("- {} **Q:** {}\n", md_timestamp(&video.url, pair.asked_at_secs), pair.question));
+      if !pair.answer.is_empty() {
+        md.push_str(&format!("  **A:** {}\n", pair.answer));
+      }
+    }
+    md.push('\n');
+  }
+
+  if with_transcript && !output.utterances.is_empty() {
+    md.push_str("## Transcript\n\n");
+    for p in transcript_paragraphs(output) {
+      md.push_str(&format!("{} {}\n\n", md_timestamp(&video.url, cs_to_secs(p.start)), p.text()));
+    }
+  }
+
+  md.push_str(&format!("---\n*{}*\n", stats_line(summary)));
+  md
+}
+
+/// Plain-text rendering for terminals, emails and chat.
+pub fn to_text(output: &SummaryOutput, with_transcript: bool) -> String {
+  let video = &output.video;
+  let summary = &output.summary;
+  let title = video.title.trim();
+  let mut txt = format!("{title}\n{}\n", "=".repeat(unicode_width::UnicodeWidthStr::width(title).max(3)));
+  for (label, value) in metadata_rows(video) {
+    txt.push_str(&format!("{label}: {value}\n"));
+  }
+  txt.push('\n');
+
+  let abstract_text = output.llm.as_ref().map_or(summary.abstract_text.as_str(), |llm| llm.abstract_text.as_str());
+  if !abstract_text.is_empty() {
+    txt.push_str(&format!("SUMMARY\n{abstract_text}\n\n"));
+  }
+
+  if let Some(llm) = &output.llm
+    && !llm.takeaways.is_empty()
+  {
+    txt.push_str("TAKEAWAYS\n");
+    for takeaway in &llm.takeaways {
+      txt.push_str(&format!("  * {takeaway}\n"));
+    }
+    txt.push('\n');
+  }
+
+  if !summary.keywords.is_empty() {
+    let keywords: Vec<&str> = summary.keywords.iter().map(|k| k.phrase.as_str()).collect();
+    txt.push_str(&format!("KEYWORDS\n{}\n\n", keywords.join(", ")));
+  }
+
+  if !summary.topics.is_empty() {
+    txt.push_str("TOPICS\n");
+    for topic in &summary.topics {
+      let range = format!("[{}-{}]", timestamp(topic.start_secs), timestamp(topic.end_secs));
+      match topic_title(output, topic.start_secs) {
+        Some(title) => txt.push_str(&format!("  {range} {title}: {}\n", topic.summary)),
+        None => txt.push_str(&format!("  {range} {}\n", topic.summary)),
+      }
+    }
+    txt.push('\n');
+  }
+
+  if !summary.key_segments.is_empty() {
+    txt.push_str("KEY MOMENTS\n");
+    for segment in &summary.key_segments {
+      txt.push_str(&format!("  [{}] {}\n", timestamp(segment.at_secs), segment.text.trim()));
+    }
+    txt.push('\n');
+  }
+
+  if !summary.qa_pairs.is_empty() {
+    txt.push_str("Q&A\n");
+    for pair in &summary.qa_pairs {
+      let at = format!("[{}]", timestamp(pair.asked_at_secs));
+      txt.push_str(&format!("  {at} Q: {}\n", pair.question));
+      if !pair.answer.is_empty() {
+        txt.push_str(&format!("  {} A: {}\n", " ".repeat(at.len()), pair.answer));
+      }
+    }
+    txt.push('\n');
+  }
+
+  if with_transcript && !output.utterances.is_empty() {
+    txt.push_str("TRANSCRIPT\n");
+    for p in transcript_paragraphs(output) {
+      txt.push_str(&format!("  [{}] {}\n", timestamp(cs_to_secs(p.start)), p.text()));
+    }
+    txt.push('\n');
+  }
+
+  txt.push_str(&format!("{}\n", stats_line(summary)));
+  txt
+}
+
+/// A YAML scalar for `s`: plain when unambiguous, otherwise double-quoted
+/// (a JSON string literal is a valid YAML double-quoted scalar).
+fn yaml_string(s: &str) -> String {
+  const INDICATORS: [char; 21] =
+    ['-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@', '`', '.', '+'];
+  let reserved =
+    matches!(s.to_lowercase().as_str(), "true" | "false" | "null" | "~" | "yes" | "no" | "on" | "off" | "y" | "n");
+  let plain = !s.is_empty()
+    && s.trim() == s
+    && !reserved
+    && !s.starts_with(INDICATORS)
+    && !s.starts_with(|c: char| c.is_ascii_digit())
+    && !s.contains(": ")
+    && !s.contains(" #")
+    && !s.ends_with(':')
+    && !s.chars().any(char::is_control);
+  if plain { s.to_string() } else { serde_json::Value::String(s.to_string()).to_string() }
+}
+
+/// A JSON document with YAML-rendered scalars and object keys in serialization
+/// order (`serde_json::Value` would sort them alphabetically).
+enum YamlNode {
+  Scalar(String),
+  Seq(Vec<YamlNode>),
+  Map(Vec<(String, YamlNode)>),
+}
+
+impl<'de> Deserialize<'de> for YamlNode {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+    struct NodeVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for NodeVisitor {
+      type Value = YamlNode;
+
+      fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a JSON value")
+      }
+      fn visit_unit<E>(self) -> std::result::Result<YamlNode, E> {
+        Ok(YamlNode::Scalar("null".to_string()))
+      }
+      fn visit_bool<E>(self, v: bool) -> std::result::Result<YamlNode, E> {
+        Ok(YamlNode::Scalar(v.to_string()))
+      }
+      fn visit_i64<E>(self, v: i64) -> std::result::Result<YamlNode, E> {
+        Ok(YamlNode::Scalar(v.to_string()))
+      }
+      fn visit_u64<E>(self, v: u64) -> std::result::Result<YamlNode, E> {
+        Ok(YamlNode::Scalar(v.to_string()))
+      }
+      fn visit_f64<E>(self, v: f64) -> std::result::Result<YamlNode, E> {
+        // serde_json's float formatting keeps the `.0` that `f64::to_string` drops.
+        Ok(YamlNode::Scalar(serde_json::Value::from(v).to_string()))
+      }
+      fn visit_str<E>(self, v: &str) -> std::result::Result<YamlNode, E> {
+        Ok(YamlNode::Scalar(yaml_string(v)))
+      }
+      fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<YamlNode, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+          items.push(item);
+        }
+        Ok(YamlNode::Seq(items))
+      }
+      fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> std::result::Result<YamlNode, A::Error> {
+        let mut entries = Vec::new();
+        while let Some((key, value)) = map.next_entry::<String, YamlNode>()? {
+          entries.push((yaml_string(&key), value));
+        }
+        Ok(YamlNode::Map(entries))
+      }
+    }
+
+    deserializer.deserialize_any(NodeVisitor)
+  }
+}
+
+impl YamlNode {
+  /// A scalar or empty collection, which fits on the same line as its key.
+  fn inline(&self) -> Option<&str> {
+    match self {
+      YamlNode::Scalar(s) => Some(s),
+      YamlNode::Seq(items) if items.is_empty() => Some("[]"),
+      YamlNode::Map(entries) if entries.is_empty() => Some("{}"),
+      _ => None,
+    }
+  }
+
+  /// Append block-style YAML at `indent` spaces.
+  fn write(&self, out: &mut String, indent: usize) {
+    let pad = " ".repeat(indent);
+    match self {
+      YamlNode::Map(entries) if !entries.is_empty() => {
+        for (key, item) in entries {
+          match item.inline() {
+            Some(scalar) => out.push_str(&format!("{pad}{key}: {scalar}\n")),
+            None => {
+              out.push_str(&format!("{pad}{key}:\n"));
+              item.write(out, indent + 2);
+            }
+          }
+        }
+      }
+      YamlNode::Seq(items) if !items.is_empty() => {
+        for item in items {
+          match item.inline() {
+            Some(scalar) => out.push_str(&format!("{pad}- {scalar}\n")),
+            None => {
+              // Render the nested block two deeper, then hang its first line on the dash.
+              let mut nested = String::new();
+              item.write(&mut nested, indent + 2);
+              out.push_str(&format!("{pad}- {}", &nested[indent + 2..]));
+            }
+          }
+        }
+      }
+      _ => out.push_str(&format!("{pad}{}\n", self.inline().unwrap_or_default())),
+    }
+  }
+}
+
+/// Block-style YAML for any serializable value, keeping field order.
+pub fn to_yaml<T: Serialize>(value: &T) -> Result<String> {
+  let json = serde_json::to_string(value).context("Failed to serialize to JSON")?;
+  let node: YamlNode = serde_json::from_str(&json).context("Failed to convert JSON to YAML")?;
+  let mut out = String::new();
+  node.write(&mut out, 0);
+  Ok(out)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -463,18 +1414,18 @@ mod tests {
 
   #[test]
   fn filler_mostly_filler_words() {
-    assert!(is_filler("um uh like yeah so"));
+    assert!(is_filler("um uh like yeah so", 0.5));
   }
 
   #[test]
   fn filler_substantive_text() {
-    assert!(!is_filler("the production techniques used in this recording are fascinating"));
+    assert!(!is_filler("the production techniques used in this recording are fascinating", 0.5));
   }
 
   #[test]
   fn filler_mixed_below_threshold() {
     // "actually" is filler, but 1/7 < 50%
-    assert!(!is_filler("i actually think the right approach is better"));
+    assert!(!is_filler("i actually think the right approach is better", 0.5));
   }
 
   // --- word_similarity ---
@@ -500,7 +1451,7 @@ mod tests {
   #[test]
   fn classify_non_speech() {
     let utterances = vec![make_utterance(0, 200, "[Music]")];
-    let result = classify(&utterances);
+    let result = classify(&utterances, &ClassifierConfig::default());
     assert_eq!(result.len(), 1);
     assert_eq!(result[0].class, UtteranceClass::NonSpeech);
   }
@@ -508,7 +1459,7 @@ mod tests {
   #[test]
   fn classify_filler() {
     let utterances = vec![make_utterance(0, 200, "um uh like yeah so basically")];
-    let result = classify(&utterances);
+    let result = classify(&utterances, &ClassifierConfig::default());
     assert_eq!(result.len(), 1);
     assert_eq!(result[0].class, UtteranceClass::Filler);
   }
@@ -516,7 +1467,7 @@ mod tests {
   #[test]
   fn classify_normal() {
     let utterances = vec![make_utterance(0, 300, "Today we discuss music theory")];
-    let result = classify(&utterances);
+    let result = classify(&utterances, &ClassifierConfig::default());
     assert_eq!(result.len(), 1);
     assert_eq!(result[0].class, UtteranceClass::Normal);
   }
@@ -527,7 +1478,7 @@ mod tests {
       make_utterance(0, 200, "First topic here"),
       make_utterance(800, 1000, "Second topic after a gap"), // 6s gap > 5s threshold
     ];
-    let result = classify(&utterances);
+    let result = classify(&utterances, &ClassifierConfig::default());
     assert_eq!(result.len(), 2);
     assert_eq!(result[1].class, UtteranceClass::TopicShift);
   }
@@ -538,7 +1489,7 @@ mod tests {
       make_utterance(0, 200, "This is a specific phrase about music"),
       make_utterance(200, 400, "This is a specific phrase about music"), // exact repeat
     ];
-    let result = classify(&utterances);
+    let result = classify(&utterances, &ClassifierConfig::default());
     assert_eq!(result.len(), 2);
     assert_eq!(result[1].class, UtteranceClass::Repetition);
   }
@@ -549,11 +1500,90 @@ mod tests {
     let text = "The recording process involved layering twelve different guitar tracks \
                 with unique effects pedals creating an atmospheric soundscape";
     let utterances = vec![make_utterance(0, 500, text)];
-    let result = classify(&utterances);
+    let result = classify(&utterances, &ClassifierConfig::default());
     assert_eq!(result.len(), 1);
     assert_eq!(result[0].class, UtteranceClass::KeySegment);
   }
 
+  // --- questions ---
+
+  #[test]
+  fn question_detection() {
+    assert!(is_question("Which brush works best for this?"));
+    assert!(is_question("How do you keep the edges soft"));
+    assert!(is_question("What's the best paper for glazing"));
+    assert!(is_question("How many layers can it take"));
+    assert!(is_question("Do you stretch the paper first"));
+    assert!(is_question("Can we use masking fluid here"));
+    assert!(!is_question("What we do next is mix the paint."));
+    assert!(!is_question("How the glaze dries matters."));
+    assert!(!is_question("It is a cold pressed paper."));
+    assert!(!is_question("Right?"));
+  }
+
+  #[test]
+  fn classify_tags_questions() {
+    let utterances = vec![
+      make_utterance(0, 300, "Today we talk about glazing layers."),
+      make_utterance(300, 600, "How long should each layer dry"),
+    ];
+    let result = classify(&utterances, &ClassifierConfig::default());
+    assert_eq!(result[1].class, UtteranceClass::Question);
+  }
+
+  #[test]
+  fn reduce_pairs_questions_with_answers() {
+    let utterances = vec![
+      make_utterance(0, 300, "Welcome to the live Q and A session."),
+      make_utterance(1000, 1300, "How long should each glaze dry?"),
+      make_utterance(1400, 1700, "Until the sheen is completely gone from the paper."),
+      make_utterance(1700, 1800, "Um, yeah."),
+      make_utterance(1800, 2100, "A hair dryer helps on humid days."),
+      make_utterance(2200, 2500, "Do you stretch cotton paper?"),
+      make_utterance(2600, 2900, "Can it buckle otherwise?"),
+      make_utterance(2900, 3200, "Yes, heavy washes make thin paper buckle."),
+      make_utterance(4000, 4300, "Next topic, brushes and how to care for them."),
+    ];
+    let cfg = ClassifierConfig::default();
+    let video = VideoDetails {
+      url: "https://youtube.com/watch?v=qa".to_string(),
+      title: "Live Q&A".to_string(),
+      uploader: None,
+      duration: None,
+      upload_date: None,
+      view_count: None,
+      tags: vec![],
+    };
+    let output = reduce(&video, &classify(&utterances, &cfg), &cfg);
+    let pairs = &output.summary.qa_pairs;
+    assert_eq!(pairs.len(), 3);
+    assert_eq!(pairs[0].question, "How long should each glaze dry?");
+    assert_eq!(pairs[0].answer, "Until the sheen is completely gone from the paper. A hair dryer helps on humid days.");
+    assert!((pairs[0].answer_start_secs - 14.0).abs() < f64::EPSILON);
+    assert!((pairs[0].answer_end_secs - 21.0).abs() < f64::EPSILON);
+    // Followed directly by another question: no answer.
+    assert!(pairs[1].answer.is_empty());
+    // The answer stops at the topic shift.
+    assert_eq!(pairs[2].answer, "Yes, heavy washes make thin paper buckle.");
+    assert!(output.utterances.iter().any(|u| u.class == UtteranceClass::Question));
+    assert!(output._hint.contains("3 questions are paired"));
+  }
+
+  #[test]
+  fn qa_pairs_respect_caps() {
+    let utterances: Vec<_> = (0..10)
+      .flat_map(|i| {
+        [
+          make_utterance(i * 400, i * 400 + 100, &format!("Why does the paint bloom in wash {i}?")),
+          make_utterance(i * 400 + 100, i * 400 + 300, &format!("Water creeps back into drying wash {i}.")),
+        ]
+      })
+      .collect();
+    let cfg = ClassifierConfig { max_qa_pairs: 4, qa_answer_secs: 1.0, ..ClassifierConfig::default() };
+    let pairs = qa_pairs(&classify(&utterances, &cfg), &cfg);
+    assert_eq!(pairs.len(), 4);
+  }
+
   // --- reduce ---
 
   #[test]
@@ -564,7 +1594,7 @@ mod tests {
       make_utterance(400, 600, "um uh like yeah so basically"),
       make_utterance(600, 800, "Today we discuss recording"),
     ];
-    let classified = classify(&utterances);
+    let classified = classify(&utterances, &ClassifierConfig::default());
     let video = VideoDetails {
       url: "https://youtube.com/watch?v=test".to_string(),
       title: "Test Video".to_string(),
@@ -574,7 +1604,7 @@ mod tests {
       view_count: None,
       tags: vec![],
     };
-    let output = reduce(&video, &classified);
+    let output = reduce(&video, &classified, &ClassifierConfig::default());
 
     assert_eq!(output.summary.total_utterances, 4);
     assert!(output.summary.suppressed_utterances >= 2); // music + filler at minimum
@@ -596,7 +1626,7 @@ mod tests {
       make_utterance(1000, 1200, "Now a completely different topic"), // time gap triggers TopicShift
       make_utterance(1200, 1400, "Continuing the second topic"),
     ];
-    let classified = classify(&utterances);
+    let classified = classify(&utterances, &ClassifierConfig::default());
     let video = VideoDetails {
       url: "https://youtube.com/watch?v=test".to_string(),
       title: "Test".to_string(),
@@ -606,14 +1636,14 @@ mod tests {
       view_count: None,
       tags: vec![],
     };
-    let output = reduce(&video, &classified);
+    let output = reduce(&video, &classified, &ClassifierConfig::default());
 
     assert!(output.summary.topics.len() >= 2, "expected at least 2 topics, got {}", output.summary.topics.len());
   }
 
   #[test]
   fn reduce_empty_input() {
-    let classified = classify(&[]);
+    let classified = classify(&[], &ClassifierConfig::default());
     let video = VideoDetails {
       url: "https://youtube.com/watch?v=test".to_string(),
       title: "Empty".to_string(),
@@ -623,7 +1653,7 @@ mod tests {
       view_count: None,
       tags: vec![],
     };
-    let output = reduce(&video, &classified);
+    let output = reduce(&video, &classified, &ClassifierConfig::default());
 
     assert_eq!(output.summary.total_utterances, 0);
     assert_eq!(output.summary.suppressed_utterances, 0);
@@ -635,7 +1665,7 @@ mod tests {
   #[test]
   fn reduce_hint_contains_counts() {
     let utterances = vec![make_utterance(0, 200, "[Music]"), make_utterance(200, 400, "Hello world")];
-    let classified = classify(&utterances);
+    let classified = classify(&utterances, &ClassifierConfig::default());
     let video = VideoDetails {
       url: "x".to_string(),
       title: "x".to_string(),
@@ -645,7 +1675,7 @@ mod tests {
       view_count: None,
       tags: vec![],
     };
-    let output = reduce(&video, &classified);
+    let output = reduce(&video, &classified, &ClassifierConfig::default());
 
     assert!(output._hint.contains("1 of 2 utterances omitted"));
   }
@@ -655,8 +1685,321 @@ mod tests {
   #[test]
   fn classify_converts_centiseconds_to_seconds() {
     let utterances = vec![make_utterance(1500, 2000, "Five seconds of content")];
-    let result = classify(&utterances);
+    let result = classify(&utterances, &ClassifierConfig::default());
     assert!((result[0].start - 15.0).abs() < f64::EPSILON);
     assert!((result[0].end - 20.0).abs() < f64::EPSILON);
   }
+
+  // --- classifier config ---
+
+  #[test]
+  fn classifier_overrides_apply() {
+    let mut cfg = ClassifierConfig::default();
+    cfg.apply_overrides(&["topic_gap_secs=2.5".to_string(), "max_topics = 5".to_string()]).unwrap();
+    assert!((cfg.topic_gap_secs - 2.5).abs() < f64::EPSILON);
+    assert_eq!(cfg.max_topics, 5);
+  }
+
+  #[test]
+  fn classifier_override_rejects_unknown_key() {
+    let err = ClassifierConfig::default().apply_overrides(&["nope=1".to_string()]).unwrap_err();
+    assert!(err.to_string().contains("Unknown classifier setting `nope`"));
+  }
+
+  #[test]
+  fn classifier_override_rejects_bad_value() {
+    let mut cfg = ClassifierConfig::default();
+    assert!(cfg.apply_overrides(&["key_min_words=lots".to_string()]).is_err());
+    assert!(cfg.apply_overrides(&["key_min_words=-3".to_string()]).is_err());
+    assert!(cfg.apply_overrides(&["key_min_words".to_string()]).is_err());
+    assert_eq!(cfg, ClassifierConfig::default());
+  }
+
+  #[test]
+  fn classifier_partial_toml_keeps_defaults() {
+    let cfg: ClassifierConfig = toml::from_str("topic_gap_secs = 3.0").unwrap();
+    assert!((cfg.topic_gap_secs - 3.0).abs() < f64::EPSILON);
+    assert_eq!(cfg.max_key_segments, 50);
+  }
+
+  #[test]
+  fn classify_respects_topic_gap_setting() {
+    let utterances = vec![make_utterance(0, 100, "First thing here"), make_utterance(400, 500, "Second thing there")];
+    let default = classify(&utterances, &ClassifierConfig::default());
+    assert_ne!(default[1].class, UtteranceClass::TopicShift);
+    let cfg = ClassifierConfig { topic_gap_secs: 2.0, ..ClassifierConfig::default() };
+    let tuned = classify(&utterances, &cfg);
+    assert_eq!(tuned[1].class, UtteranceClass::TopicShift);
+  }
+
+  #[test]
+  fn reduce_hint_echoes_settings() {
+    let cfg = ClassifierConfig { max_topics: 7, ..ClassifierConfig::default() };
+    let video = VideoDetails {
+      url: "x".to_string(),
+      title: "x".to_string(),
+      uploader: None,
+      duration: None,
+      upload_date: None,
+      view_count: None,
+      tags: vec![],
+    };
+    let output = reduce(&video, &[], &cfg);
+    assert!(output._hint.contains("max_topics=7"));
+  }
+
+  // --- textrank summaries ---
+
+  #[test]
+  fn reduce_topic_summary_skips_filler_opening() {
+    let utterances = vec![
+      make_utterance(0, 200, "So, yeah, anyway, um, like, okay."),
+      make_utterance(200, 600, "Watercolor glazing builds depth through thin transparent layers."),
+      make_utterance(600, 1000, "Each glazing layer must dry before the next transparent wash."),
+    ];
+    let classified = classify(&utterances, &ClassifierConfig::default());
+    let video = VideoDetails {
+      url: "x".to_string(),
+      title: "x".to_string(),
+      uploader: None,
+      duration: None,
+      upload_date: None,
+      view_count: None,
+      tags: vec![],
+    };
+    let output = reduce(&video, &classified, &ClassifierConfig::default());
+    assert_eq!(output.summary.topics.len(), 1);
+    assert!(!output.summary.topics[0].summary.contains("anyway"));
+    assert!(output.summary.topics[0].summary.contains("glazing"));
+    assert!(!output.summary.abstract_text.is_empty());
+    let json = serde_json::to_value(&output.summary).unwrap();
+    assert!(json.get("abstract").is_some());
+  }
+
+  // --- keywords ---
+
+  #[test]
+  fn reduce_emits_keywords() {
+    let utterances = vec![
+      make_utterance(0, 400, "Masking fluid keeps the highlights white."),
+      make_utterance(400, 800, "Peel the masking fluid off once everything is dry."),
+    ];
+    let classified = classify(&utterances, &ClassifierConfig::default());
+    let video = VideoDetails {
+      url: "x".to_string(),
+      title: "x".to_string(),
+      uploader: None,
+      duration: None,
+      upload_date: None,
+      view_count: None,
+      tags: vec![],
+    };
+    let output = reduce(&video, &classified, &ClassifierConfig::default());
+    assert_eq!(output.summary.keywords[0].phrase, "masking fluid");
+    assert!(output.summary.keywords[0].first_at_secs.abs() < f64::EPSILON);
+    assert!(!output.summary.topics[0].keywords.is_empty());
+  }
+
+  // --- token budget ---
+
+  fn budget_fixture() -> SummaryOutput {
+    let utterances: Vec<(i64, i64, String)> = (0..60)
+      .map(|i| {
+        let text = format!("Sentence {i} covers watercolor technique {i} with paper.");
+        (i * 300, i * 300 + 280, text)
+      })
+      .collect();
+    let classified = classify(&utterances, &ClassifierConfig::default());
+    let video = VideoDetails {
+      url: "x".to_string(),
+      title: "x".to_string(),
+      uploader: None,
+      duration: None,
+      upload_date: None,
+      view_count: None,
+      tags: vec![],
+    };
+    reduce(&video, &classified, &ClassifierConfig::default())
+  }
+
+  #[test]
+  fn budget_noop_when_under() {
+    let mut output = budget_fixture();
+    let before = output.utterances.len();
+    let report = apply_token_budget(&mut output, 1_000_000, SummaryFormat::Json, false);
+    assert_eq!(report.dropped_normal, 0);
+    assert_eq!(output.utterances.len(), before);
+    assert!(!output._hint.contains("Token budget"));
+  }
+
+  #[test]
+  fn budget_drops_normal_first() {
+    let mut output = budget_fixture();
+    let printed = |o: &SummaryOutput| estimate_tokens(render(o, SummaryFormat::Json, false).unwrap().len());
+    let full = printed(&output);
+    let key_segments = output.summary.key_segments.len();
+    let report = apply_token_budget(&mut output, full * 3 / 4, SummaryFormat::Json, false);
+    assert!(report.dropped_normal > 0);
+    assert_eq!(report.shortened_topics, 0);
+    assert!(!report.over_budget());
+    assert!(printed(&output) <= full * 3 / 4);
+    assert_eq!(output.summary.key_segments.len(), key_segments);
+    assert!(output._hint.contains("Token budget"));
+    // Remaining utterances stay in chronological order.
+    assert!(output.utterances.windows(2).all(|w| w[0].start <= w[1].start));
+  }
+
+  #[test]
+  fn budget_reports_unreachable_target() {
+    let mut output = budget_fixture();
+    let report = apply_token_budget(&mut output, 10, SummaryFormat::Json, false);
+    assert!(report.over_budget());
+    assert!(output.utterances.is_empty());
+    assert!(output._hint.contains("never trimmed"));
+  }
+
+  #[test]
+  fn error_records_render_in_every_format() {
+    let error = serde_json::json!({ "code": "not_found", "message": "Video unavailable" });
+    let json = render_error("abc", &error, SummaryFormat::Json).unwrap();
+    let record: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(record["status"], "error");
+    assert_eq!(record["error"]["code"], "not_found");
+    assert!(render_error("abc", &error, SummaryFormat::Yaml).unwrap().contains("status: error"));
+    assert_eq!(
+      render_error("abc", &error, SummaryFormat::Md).unwrap(),
+      "# abc\n\n**Error** (`not_found`): Video unavailable\n"
+    );
+    assert_eq!(
+      render_error("abc", &error, SummaryFormat::Txt).unwrap(),
+      "abc\n  Error (not_found): Video unavailable\n"
+    );
+  }
+
+  #[test]
+  fn budget_measures_the_printed_format() {
+    // Markdown without the transcript doesn't print utterances: only topics can shrink.
+    let mut output = budget_fixture();
+    let before = output.utterances.len();
+    let full = estimate_tokens(render(&output, SummaryFormat::Md, false).unwrap().len());
+    let report = apply_token_budget(&mut output, full - 1, SummaryFormat::Md, false);
+    assert_eq!(report.dropped_normal, 0);
+    assert_eq!(output.utterances.len(), before);
+    assert!(report.shortened_topics > 0);
+    assert_eq!(report.estimated_tokens, estimate_tokens(render(&output, SummaryFormat::Md, false).unwrap().len()));
+  }
+
+  #[test]
+  fn first_sentence_truncates() {
+    assert_eq!(first_sentence("One. Two."), "One.");
+    assert!(first_sentence(&"word ".repeat(100)).ends_with('…'));
+  }
+
+  // --- rendering ---
+
+  fn render_fixture() -> SummaryOutput {
+    let utterances = vec![
+      make_utterance(0, 400, "Today we paint a misty mountain landscape with glazing."),
+      make_utterance(450, 900, "Each glaze has to dry completely before the next layer goes on top of it."),
+      make_utterance(2000, 2400, "Lift the paint with a thirsty brush to suggest mist between the ridges."),
+    ];
+    let video = VideoDetails {
+      url: "https://www.youtube.com/watch?v=abc".to_string(),
+      title: "Misty | Mountains".to_string(),
+      uploader: Some("Pastel Sketchbook".to_string()),
+      duration: Some("0:24".to_string()),
+      upload_date: Some("20240301".to_string()),
+      view_count: None,
+      tags: vec!["watercolor".to_string()],
+    };
+    reduce(&video, &classify(&utterances, &ClassifierConfig::default()), &ClassifierConfig::default())
+  }
+
+  #[test]
+  fn timestamp_formats() {
+    assert_eq!(timestamp(65.0), "1:05");
+    assert_eq!(timestamp(3725.0), "1:02:05");
+  }
+
+  #[test]
+  fn moment_url_appends_time() {
+    assert_eq!(
+      moment_url("https://www.youtube.com/watch?v=abc", 20.7).as_deref(),
+      Some("https://www.youtube.com/watch?v=abc&t=20s")
+    );
+    assert_eq!(moment_url("https://youtu.be/abc", 5.0).as_deref(), Some("https://youtu.be/abc?t=5s"));
+    assert_eq!(moment_url("", 5.0), None);
+  }
+
+  #[test]
+  fn markdown_has_title_table_and_linked_topics() {
+    let md = to_markdown(&render_fixture(), false);
+    assert!(md.starts_with("# Misty | Mountains\n"));
+    assert!(md.contains("| Uploaded | 2024-03-01 |"));
+    assert!(md.contains("| URL | https://www.youtube.com/watch?v=abc |"));
+    assert!(md.contains("## Topics\n\n1. [0:00](https://www.youtube.com/watch?v=abc&t=0s) "));
+    assert!(md.contains("[0:20](https://www.youtube.com/watch?v=abc&t=20s)"));
+    assert!(md.contains("## Key moments"));
+    assert!(!md.contains("## Transcript"));
+    assert!(to_markdown(&render_fixture(), true).contains("## Transcript"));
+  }
+
+  #[test]
+  fn markdown_escapes_table_cells() {
+    let mut output = render_fixture();
+    output.video.uploader = Some("A | B".to_string());
+    assert!(to_markdown(&output, false).contains("| Channel | A \\| B |"));
+  }
+
+  #[test]
+  fn text_lists_topics_with_ranges() {
+    let txt = to_text(&render_fixture(), true);
+    assert!(txt.starts_with("Misty | Mountains\n=================\n"));
+    assert!(txt.contains("TOPICS\n  [0:00-"));
+    assert!(txt.contains("TRANSCRIPT\n  [0:00] Today we paint"));
+    assert!(!txt.contains("http://") && !txt.contains("&t="));
+  }
+
+  #[test]
+  fn yaml_quotes_only_ambiguous_strings() {
+    assert_eq!(yaml_string("plain words"), "plain words");
+    assert_eq!(yaml_string("yes"), "\"yes\"");
+    assert_eq!(yaml_string("20240301"), "\"20240301\"");
+    assert_eq!(yaml_string("key: value"), "\"key: value\"");
+    assert_eq!(yaml_string("- dash"), "\"- dash\"");
+    assert_eq!(yaml_string("two\nlines"), "\"two\\nlines\"");
+    assert_eq!(yaml_string(""), "\"\"");
+  }
+
+  #[test]
+  fn yaml_nests_maps_and_lists_in_field_order() {
+    #[derive(Serialize)]
+    struct Doc {
+      title: &'static str,
+      tags: Vec<&'static str>,
+      empty: Vec<u8>,
+      topics: Vec<serde_json::Value>,
+    }
+    let doc = Doc {
+      title: "Glazing",
+      tags: vec!["a", "b"],
+      empty: vec![],
+      topics: vec![serde_json::json!({"start": 1.0, "keywords": [{"phrase": "x"}]})],
+    };
+    assert_eq!(
+      to_yaml(&doc).unwrap(),
+      "title: Glazing\ntags:\n  - a\n  - b\nempty: []\ntopics:\n  - keywords:\n      - phrase: x\n    start: 1.0\n"
+    );
+  }
+
+  #[test]
+  fn render_json_matches_serde() {
+    let output = render_fixture();
+    let json = render(&output, SummaryFormat::Json, false).unwrap();
+    assert!(json.starts_with(&format!("{{\n  \"schema_version\": {},\n", crate::schema::SCHEMA_VERSION)));
+    let mut expected = serde_json::to_value(&output).unwrap();
+    expected["schema_version"] = serde_json::json!(crate::schema::SCHEMA_VERSION);
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&json).unwrap(), expected);
+    assert!(render(&output, SummaryFormat::Yaml, false).unwrap().contains("\nvideo:\n  url: "));
+  }
 }
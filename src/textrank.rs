@@ -0,0 +1,265 @@
+//! Local extractive summarization with TextRank.
+//!
+//! Sentences become TF-IDF vectors over their non-stopword tokens; pairwise
+//! cosine similarity forms a weighted graph, and PageRank over that graph
+//! scores how central each sentence is. The top-scoring sentences, kept in
+//! their original order, make the summary. Everything is plain arithmetic:
+//! no model downloads and no network calls (see ADR 0004).
+
+use std::collections::HashMap;
+
+/// English stopwords plus spoken-transcript filler, ignored when comparing sentences.
+const STOPWORDS: &str = "\
+  a about above after again against all also am an and any are aren't as at be because been before being below \
+  between both but by can could did didn't do does doesn't doing don't down during each even few for from further \
+  get go going got gonna had has have having he her here hers herself him himself his how i if in into is isn't it \
+  it's its itself just let let's me more most much my myself no nor not now of off on once one only or other our \
+  ours ourselves out over own really same say she should so some such than that that's the their theirs them \
+  themselves then there there's these they thing things this those through to too under until up us very want was \
+  wasn't we we're were what what's when where which while who whom why will with would you you're your yours \
+  yourself yourselves \
+  um uh erm hmm like basically right yeah actually literally obviously anyway well okay ok kind sort know mean \
+  wanna gotta \
+  use used using make makes made need needs see look put take think come came today";
+
+/// PageRank damping factor.
+const DAMPING: f64 = 0.85;
+
+/// PageRank stops after this many iterations or once scores move less than `EPSILON`.
+const MAX_ITERATIONS: usize = 50;
+const EPSILON: f64 = 1e-6;
+
+/// Whether `word` (lowercase) is a stopword.
+pub(crate) fn is_stopword(word: &str) -> bool {
+  static SET: std::sync::OnceLock<std::collections::HashSet<&'static str>> = std::sync::OnceLock::new();
+  SET.get_or_init(|| STOPWORDS.split_whitespace().collect()).contains(word)
+}
+
+/// Split text into lowercase words, keeping inner apostrophes ("don't").
+pub(crate) fn words(text: &str) -> Vec<String> {
+  text
+    .to_lowercase()
+    .split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’'))
+    .map(|w| w.trim_matches(['\'', '’']).replace('’', "'"))
+    .filter(|w| !w.is_empty())
+    .collect()
+}
+
+/// Content tokens of `text`: lowercase words minus stopwords and single characters.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+  words(text).into_iter().filter(|w| w.chars().count() > 1 && !is_stopword(w)).collect()
+}
+
+/// TF-IDF vectors for a set of token lists, with IDF computed over the set itself.
+#[allow(clippy::cast_precision_loss)]
+fn tfidf_vectors(docs: &[Vec<String>]) -> Vec<HashMap<&str, f64>> {
+  let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+  for doc in docs {
+    let mut seen: Vec<&str> = doc.iter().map(String::as_str).collect();
+    seen.sort_unstable();
+    seen.dedup();
+    for term in seen {
+      *doc_freq.entry(term).or_default() += 1;
+    }
+  }
+
+  let n = docs.len() as f64;
+  docs
+    .iter()
+    .map(|doc| {
+      let mut tf: HashMap<&str, f64> = HashMap::new();
+      for term in doc {
+        *tf.entry(term.as_str()).or_default() += 1.0;
+      }
+      for (term, weight) in &mut tf {
+        // Smoothed IDF keeps terms shared by every sentence from zeroing out.
+        let idf = ((1.0 + n) / (1.0 + doc_freq[term] as f64)).ln() + 1.0;
+        *weight *= idf;
+      }
+      tf
+    })
+    .collect()
+}
+
+/// Cosine similarity between two sparse vectors.
+fn cosine(a: &HashMap<&str, f64>, b: &HashMap<&str, f64>) -> f64 {
+  let dot: f64 = a.iter().filter_map(|(term, wa)| b.get(term).map(|wb| wa * wb)).sum();
+  let norm_a = a.values().map(|w| w * w).sum::<f64>().sqrt();
+  let norm_b = b.values().map(|w| w * w).sum::<f64>().sqrt();
+  if norm_a == 0.0 || norm_b == 0.0 { 0.0 } else { dot / (norm_a * norm_b) }
+}
+
+/// Most sentences ranked as one graph; the graph is quadratic in its size.
+const MAX_RANKED: usize = 300;
+
+/// Score each sentence by TextRank centrality. Scores sum to roughly 1.
+///
+/// Past `MAX_RANKED` sentences (multi-hour videos), only the sentences closest
+/// to the TF-IDF centroid of the whole text are ranked; the rest score below
+/// every ranked sentence, in centroid order.
+#[allow(clippy::cast_precision_loss)]
+pub fn rank(sentences: &[&str]) -> Vec<f64> {
+  let n = sentences.len();
+  if n == 0 {
+    return Vec::new();
+  }
+
+  let tokens: Vec<Vec<String>> = sentences.iter().map(|s| tokenize(s)).collect();
+  let vectors = tfidf_vectors(&tokens);
+  let mut scores = if n <= MAX_RANKED {
+    pagerank(&vectors.iter().collect::<Vec<_>>())
+  } else {
+    let mut centroid: HashMap<&str, f64> = HashMap::new();
+    for vector in &vectors {
+      for (term, weight) in vector {
+        *centroid.entry(term).or_default() += weight;
+      }
+    }
+    let closeness: Vec<f64> = vectors.iter().map(|v| cosine(v, &centroid)).collect();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| closeness[b].total_cmp(&closeness[a]));
+    let candidates = &order[..MAX_RANKED];
+
+    // PageRank never scores a sentence below `(1 - DAMPING) / MAX_RANKED`.
+    let floor = (1.0 - DAMPING) / MAX_RANKED as f64;
+    let mut scores: Vec<f64> = closeness.iter().map(|c| c * floor / 2.0).collect();
+    let ranked = pagerank(&candidates.iter().map(|&i| &vectors[i]).collect::<Vec<_>>());
+    for (&i, score) in candidates.iter().zip(ranked) {
+      scores[i] = score;
+    }
+    scores
+  };
+
+  // Sentences with no content words can't be central; push them to the bottom.
+  for (score, toks) in scores.iter_mut().zip(&tokens) {
+    if toks.is_empty() {
+      *score = 0.0;
+    }
+  }
+  scores
+}
+
+/// PageRank over the cosine-similarity graph of `vectors`.
+#[allow(clippy::cast_precision_loss)]
+fn pagerank(vectors: &[&HashMap<&str, f64>]) -> Vec<f64> {
+  let n = vectors.len();
+  let mut weights = vec![vec![0.0; n]; n];
+  for i in 0..n {
+    for j in (i + 1)..n {
+      let sim = cosine(vectors[i], vectors[j]);
+      weights[i][j] = sim;
+      weights[j][i] = sim;
+    }
+  }
+  let out_weight: Vec<f64> = weights.iter().map(|row| row.iter().sum()).collect();
+
+  let base = (1.0 - DAMPING) / n as f64;
+  let mut scores = vec![1.0 / n as f64; n];
+  for _ in 0..MAX_ITERATIONS {
+    let next: Vec<f64> = (0..n)
+      .map(|i| {
+        let incoming: f64 = (0..n)
+          .filter(|&j| out_weight[j] > 0.0 && weights[j][i] > 0.0)
+          .map(|j| weights[j][i] / out_weight[j] * scores[j])
+          .sum();
+        base + DAMPING * incoming
+      })
+      .collect();
+    let delta: f64 = next.iter().zip(&scores).map(|(a, b)| (a - b).abs()).sum();
+    scores = next;
+    if delta < EPSILON {
+      break;
+    }
+  }
+  scores
+}
+
+/// Indices of the `count` highest-ranked sentences, returned in original order.
+pub fn top_sentences(sentences: &[&str], count: usize) -> Vec<usize> {
+  let scores = rank(sentences);
+  let mut order: Vec<usize> = (0..sentences.len()).collect();
+  // Stable sort: ties keep the earlier sentence first.
+  order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+  order.truncate(count);
+  order.sort_unstable();
+  order
+}
+
+/// Extractive summary: the `count` most central sentences joined in original order.
+pub fn summarize(sentences: &[&str], count: usize) -> String {
+  top_sentences(sentences, count).into_iter().map(|i| sentences[i].trim()).collect::<Vec<_>>().join(" ")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn tokenize_drops_stopwords_and_fillers() {
+    assert_eq!(tokenize("So, yeah, anyway… the Watercolor brush!"), vec!["watercolor", "brush"]);
+  }
+
+  #[test]
+  fn words_keep_contractions() {
+    assert_eq!(words("Don’t stop"), vec!["don't", "stop"]);
+  }
+
+  #[test]
+  fn rank_empty() {
+    assert!(rank(&[]).is_empty());
+  }
+
+  #[test]
+  fn central_sentence_ranks_highest() {
+    let sentences = [
+      "Watercolor paper needs to be stretched before painting.",
+      "Stretch the watercolor paper with tape so the painting stays flat.",
+      "So, yeah, anyway.",
+      "Painting on flat watercolor paper keeps the washes even.",
+    ];
+    let scores = rank(&sentences);
+    assert!(scores[2].abs() < f64::EPSILON);
+    let best = top_sentences(&sentences, 1)[0];
+    assert_ne!(best, 2);
+  }
+
+  #[test]
+  fn top_sentences_keep_original_order() {
+    let sentences = ["alpha beta gamma", "beta gamma delta", "gamma delta epsilon", "unrelated words entirely"];
+    let top = top_sentences(&sentences, 2);
+    assert_eq!(top.len(), 2);
+    assert!(top[0] < top[1]);
+  }
+
+  #[test]
+  fn summarize_skips_filler_sentence() {
+    let sentences = ["So, yeah, anyway.", "Layering glazes builds depth.", "Glazes dry between layers."];
+    let summary = summarize(&sentences, 1);
+    assert!(!summary.contains("anyway"));
+  }
+
+  #[test]
+  fn long_texts_rank_only_the_closest_sentences() {
+    let mut sentences: Vec<String> =
+      (0..MAX_RANKED).map(|i| format!("Glazing watercolor layers step {i} keeps the washes clean.")).collect();
+    sentences.push("Completely unrelated trivia about trains.".to_string());
+    sentences.push("Um.".to_string());
+    let refs: Vec<&str> = sentences.iter().map(String::as_str).collect();
+    let scores = rank(&refs);
+    assert_eq!(scores.len(), refs.len());
+    let outlier = scores[MAX_RANKED];
+    assert!(scores[..MAX_RANKED].iter().all(|&s| s > outlier));
+    assert!(scores[MAX_RANKED + 1].abs() < f64::EPSILON);
+  }
+
+  #[test]
+  fn cosine_of_disjoint_vectors_is_zero() {
+    let docs = vec![tokenize("red apples"), tokenize("blue oceans")];
+    let v = tfidf_vectors(&docs);
+    assert!(cosine(&v[0], &v[1]).abs() < f64::EPSILON);
+  }
+}
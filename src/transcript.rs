@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -6,6 +7,8 @@ use tokio::task::JoinHandle;
 use tracing::{info, warn};
 
 use crate::constants::constants;
+use crate::error::ErrorKind;
+use crate::store;
 
 // --- Auto-transcription ---
 
@@ -19,8 +22,93 @@ pub enum TranscriptEvent {
   ChunkTranscribed(Vec<whisper_cli::Utternace>),
   /// All chunks transcribed — pipeline complete.
   Transcribed,
-  /// Pipeline failed with an error message.
-  Failed(String),
+  /// Pipeline failed, with the kind of failure and its message.
+  Failed(ErrorKind, String),
+}
+
+/// A lazily-loaded whisper instance shared between pipeline runs.
+pub type WhisperCache = Arc<StdMutex<Option<whisper_cli::Whisper>>>;
+
+/// A fixed set of whisper instances shared by concurrent pipelines.
+///
+/// Each pipeline checks out one instance for its whole run; when all are in
+/// use, further jobs wait. Instances load lazily on first use and stay loaded
+/// across jobs, so a batch pays the model load cost once per slot.
+pub struct WhisperPool {
+  slots: StdMutex<Vec<WhisperCache>>,
+  permits: tokio::sync::Semaphore,
+}
+
+/// A whisper instance checked out of a [`WhisperPool`]; returned on drop.
+pub struct PooledWhisper<'a> {
+  pool: &'a WhisperPool,
+  cache: Option<WhisperCache>,
+  _permit: tokio::sync::SemaphorePermit<'a>,
+}
+
+impl WhisperPool {
+  pub fn new(size: usize) -> Self {
+    let size = size.max(1);
+    Self {
+      slots: StdMutex::new((0..size).map(|_| Arc::new(StdMutex::new(None))).collect()),
+      permits: tokio::sync::Semaphore::new(size),
+    }
+  }
+
+  /// Wait for a free instance and check it out.
+  pub async fn acquire(&self) -> PooledWhisper<'_> {
+    // Safety: the semaphore is never closed.
+    let permit = self.permits.acquire().await.expect("whisper pool semaphore closed");
+    // Safety: the lock is only held for a push/pop, never across an await.
+    let cache = self.slots.lock().expect("whisper pool mutex poisoned").pop();
+    PooledWhisper { pool: self, cache, _permit: permit }
+  }
+}
+
+impl PooledWhisper<'_> {
+  /// The checked-out instance, ready to hand to `spawn_transcription_pipeline`.
+  pub fn cache(&self) -> WhisperCache {
+    // A permit guarantees a slot; fall back to a fresh instance defensively.
+    self.cache.clone().unwrap_or_else(|| Arc::new(StdMutex::new(None)))
+  }
+}
+
+impl Drop for PooledWhisper<'_> {
+  fn drop(&mut self) {
+    if let Some(cache) = self.cache.take()
+      && let Ok(mut slots) = self.pool.slots.lock()
+    {
+      slots.push(cache);
+    }
+  }
+}
+
+/// Best-effort estimate of the memory available for new whisper instances, in bytes.
+///
+/// Linux: `MemAvailable` from `/proc/meminfo`. macOS: half of `hw.memsize`
+/// (the kernel doesn't expose a cheap "available" figure). `None` if unknown.
+pub fn available_memory_bytes() -> Option<u64> {
+  if let Ok(meminfo) = std::fs::read_to_string("/proc/meminfo") {
+    return parse_meminfo_available(&meminfo);
+  }
+  let output = std::process::Command::new("sysctl").args(["-n", "hw.memsize"]).output().ok()?;
+  let total: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+  Some(total / 2)
+}
+
+/// Parse the `MemAvailable` line (in kB) from `/proc/meminfo` content.
+fn parse_meminfo_available(meminfo: &str) -> Option<u64> {
+  let line = meminfo.lines().find(|l| l.starts_with("MemAvailable:"))?;
+  let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+  Some(kb * 1024)
+}
+
+/// Number of whisper instances to run side by side: as many as fit in
+/// available memory, capped at `jobs` and never less than one.
+pub fn whisper_pool_size(jobs: usize) -> usize {
+  let per_instance = constants().whisper_instance_mb * 1024 * 1024;
+  let fit = available_memory_bytes().map_or(1, |avail| usize::try_from(avail / per_instance.max(1)).unwrap_or(1));
+  fit.clamp(1, jobs.max(1))
 }
 
 /// Auto-transcription state machine.
@@ -109,19 +197,19 @@ pub async fn resolve_stream_url(ipc_socket: Option<&str>, youtube_url: &str) ->
   let output = tokio::process::Command::new("yt-dlp")
     .args(["-g", "--format", "bestaudio", youtube_url])
     .stdin(std::process::Stdio::null())
-    .stderr(std::process::Stdio::null())
+    .stderr(std::process::Stdio::piped())
     .output()
     .await
     .map_err(|e| {
       if e.kind() == std::io::ErrorKind::NotFound {
-        anyhow::anyhow!("yt-dlp not found. Install with: brew install yt-dlp")
+        crate::error::fail(ErrorKind::DependencyMissing, "yt-dlp not found. Install with: brew install yt-dlp")
       } else {
         anyhow::anyhow!("Failed to start yt-dlp: {e}")
       }
     })?;
 
   if !output.status.success() {
-    return Err(anyhow::anyhow!("yt-dlp -g failed with status {}", output.status));
+    return Err(crate::error::yt_dlp_failed("-g", &output.stderr));
   }
 
   let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -225,21 +313,62 @@ impl Drop for SuppressStdio {
   }
 }
 
+/// Pipelines started by this process, numbering their chunk files.
+static PIPELINE_RUNS: AtomicU64 = AtomicU64::new(0);
+
+/// How long a run waits for the video's record lock: long enough for a run of
+/// the same video that was just cancelled (e.g. the TUI restarting it) to be dropped.
+const RECORD_LOCK_WAIT: Duration = Duration::from_secs(2);
+
 /// Run the chunked transcription pipeline as an async task.
 ///
 /// Stages:
 /// 1. Resolve CDN stream URL (mpv IPC fast path, or yt-dlp fallback)
 /// 2. Download whisper model if needed
 /// 3. Loop: download 30s chunk via ffmpeg → transcribe → send utterances → next chunk
+///
+/// Progress is checkpointed to the transcript store after every chunk, so a
+/// job can be resumed later by passing the stored offset as `start_offset`.
+/// The run holds the video's record lock throughout; it fails if another run
+/// keeps holding it or the stored offset is no longer `start_offset`.
 #[allow(clippy::too_many_lines)]
 pub fn spawn_transcription_pipeline(
   tx: mpsc::UnboundedSender<TranscriptEvent>,
   url: String,
-  whisper_cache: Arc<StdMutex<Option<whisper_cli::Whisper>>>,
+  whisper_cache: WhisperCache,
   ipc_socket: Option<String>,
   duration_hint: Option<u32>,
+  start_offset: u32,
 ) -> JoinHandle<()> {
   tokio::spawn(async move {
+    let video_id = crate::cli::extract_video_id(&url);
+
+    // Hold the video's log for the whole run, so no other run (in this
+    // process or another) appends to it in between.
+    let deadline = std::time::Instant::now() + RECORD_LOCK_WAIT;
+    let record_lock = loop {
+      match store::lock(&video_id) {
+        Err(_) if std::time::Instant::now() < deadline => tokio::time::sleep(Duration::from_millis(100)).await,
+        result => break result,
+      }
+    };
+    let _record_lock = match record_lock {
+      Ok(lock) => lock,
+      Err(e) => {
+        let _ = tx.send(TranscriptEvent::Failed(crate::error::kind_of(&e), format!("{e:#}")));
+        return;
+      }
+    };
+    // The caller read the checkpoint before the lock was taken; another run may have moved it since.
+    let stored = store::load(&video_id).map_or((0, false), |r| (r.offset_secs, r.complete));
+    if stored != (start_offset, false) {
+      let _ = tx.send(TranscriptEvent::Failed(
+        ErrorKind::Internal,
+        format!("The stored transcript of {video_id} changed while starting; run again to pick it up"),
+      ));
+      return;
+    }
+
     // Stage 1: Resolve the direct CDN stream URL.
     let stream_url = match resolve_stream_url(ipc_socket.as_deref(), &url).await {
       Ok(resolved) => {
@@ -248,7 +377,11 @@ pub fn spawn_transcription_pipeline(
       }
       Err(e) => {
         tracing::error!(err = %e, "transcript: failed to resolve stream URL");
-        let _ = tx.send(TranscriptEvent::Failed(format!("{e:#}")));
+        let kind = match crate::error::kind_of(&e) {
+          ErrorKind::Internal => ErrorKind::TranscriptionFailed,
+          kind => kind,
+        };
+        let _ = tx.send(TranscriptEvent::Failed(kind, format!("{e:#}")));
         return;
       }
     };
@@ -261,7 +394,7 @@ pub fn spawn_transcription_pipeline(
     if !model_path.exists() {
       info!("transcript: whisper model not found, downloading");
       if let Err(e) = download_whisper_model(&tx, &model_path).await {
-        let _ = tx.send(TranscriptEvent::Failed(format!("Model download failed: {e:#}")));
+        let _ = tx.send(TranscriptEvent::Failed(ErrorKind::Network, format!("Model download failed: {e:#}")));
         return;
       }
     }
@@ -269,8 +402,13 @@ pub fn spawn_transcription_pipeline(
     // Stage 3: Chunked download + transcription loop.
     // Each iteration: ffmpeg downloads chunk_secs of audio → whisper transcribes → send utterances.
     let chunk_secs = constants().chunk_secs;
-    let chunk_path = std::env::temp_dir().join(format!("yp-chunk-{}.wav", std::process::id()));
-    let mut offset_secs: u32 = 0;
+    // Per-run chunk file so concurrent pipelines (batch mode, the server) don't clobber each other.
+    let run = PIPELINE_RUNS.fetch_add(1, Ordering::Relaxed);
+    let chunk_path = std::env::temp_dir().join(format!("yp-chunk-{}-{run}-{video_id}.wav", std::process::id()));
+    let mut offset_secs: u32 = start_offset;
+    if start_offset > 0 {
+      info!(offset = start_offset, video_id = %video_id, "transcript: resuming from checkpoint");
+    }
 
     loop {
       // If we know the video duration, skip chunks that start past the end.
@@ -312,10 +450,12 @@ pub fn spawn_transcription_pipeline(
         .stdin(std::process::Stdio::null())
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
+        // An aborted pipeline (cancelled request) must not leave ffmpeg running.
+        .kill_on_drop(true)
         .spawn();
 
       // Timeout: if ffmpeg hangs (e.g. seeking past end of an HTTP stream),
-      // kill it and treat as end-of-stream. 90s is generous — most CDN chunks
+      // kill it and treat it like a non-zero exit. 90s is generous — most CDN chunks
       // download in <5s, but slow connections or first-chunk setup can be slow.
       let ffmpeg_timeout = std::time::Duration::from_secs(90);
       let ffmpeg_start = std::time::Instant::now();
@@ -328,30 +468,50 @@ pub fn spawn_transcription_pipeline(
               info!(offset = offset_secs, elapsed = ?ffmpeg_start.elapsed(), "transcript: ffmpeg chunk downloaded");
             }
             Ok(Ok(status)) => {
-              // Non-zero exit likely means we've gone past the end of the stream
-              info!(offset = offset_secs, code = ?status.code(), elapsed = ?ffmpeg_start.elapsed(), "transcript: ffmpeg exited non-zero, assuming end of stream");
+              // Non-zero exit near the known end of the video means we've gone past the end
+              // of the stream; anywhere else it's a failure, and the record stays incomplete.
+              info!(offset = offset_secs, code = ?status.code(), elapsed = ?ffmpeg_start.elapsed(), "transcript: ffmpeg exited non-zero");
+              if stream_interrupted(offset_secs, chunk_secs, duration_hint) {
+                let _ = std::fs::remove_file(&chunk_path);
+                let _ = tx.send(TranscriptEvent::Failed(
+                  ErrorKind::Network,
+                  format!("Audio stream interrupted at {offset_secs}s"),
+                ));
+                return;
+              }
               break;
             }
             Ok(Err(e)) => {
               warn!(offset = offset_secs, err = %e, elapsed = ?ffmpeg_start.elapsed(), "transcript: ffmpeg wait failed");
-              let _ = tx.send(TranscriptEvent::Failed(format!("Failed to wait for ffmpeg: {e}")));
+              let _ = tx.send(TranscriptEvent::Failed(
+                ErrorKind::TranscriptionFailed,
+                format!("Failed to wait for ffmpeg: {e}"),
+              ));
               return;
             }
             Err(_) => {
-              // Timeout — ffmpeg hung, likely past end of stream on an HTTP URL
-              warn!(offset = offset_secs, timeout = ?ffmpeg_timeout, elapsed = ?ffmpeg_start.elapsed(), "transcript: ffmpeg timed out, assuming end of stream");
+              // Timeout — ffmpeg hung: past the end of an HTTP stream, or a stalled connection
+              warn!(offset = offset_secs, timeout = ?ffmpeg_timeout, elapsed = ?ffmpeg_start.elapsed(), "transcript: ffmpeg timed out");
               let _ = child.kill().await;
+              if stream_interrupted(offset_secs, chunk_secs, duration_hint) {
+                let _ = std::fs::remove_file(&chunk_path);
+                let _ = tx.send(TranscriptEvent::Failed(
+                  ErrorKind::Network,
+                  format!("Audio stream timed out at {offset_secs}s"),
+                ));
+                return;
+              }
               break;
             }
           }
         }
         Err(e) => {
-          let msg = if e.kind() == std::io::ErrorKind::NotFound {
-            "ffmpeg not found. Install with: brew install ffmpeg".to_string()
+          let failure = if e.kind() == std::io::ErrorKind::NotFound {
+            (ErrorKind::DependencyMissing, "ffmpeg not found. Install with: brew install ffmpeg".to_string())
           } else {
-            format!("Failed to start ffmpeg: {e}")
+            (ErrorKind::TranscriptionFailed, format!("Failed to start ffmpeg: {e}"))
           };
-          let _ = tx.send(TranscriptEvent::Failed(msg));
+          let _ = tx.send(TranscriptEvent::Failed(failure.0, failure.1));
           return;
         }
       }
@@ -369,6 +529,7 @@ pub fn spawn_transcription_pipeline(
       if chunk_size < min_chunk_bytes {
         info!(offset = offset_secs, size = chunk_size, "transcript: chunk too short for whisper, skipping");
         offset_secs = offset_secs.saturating_add(chunk_secs);
+        checkpoint(&video_id, &url, offset_secs, &[]);
         continue;
       }
 
@@ -416,9 +577,12 @@ pub fn spawn_transcription_pipeline(
       })
       .await;
 
+      let next_offset = offset_secs.saturating_add(chunk_secs);
+
       match transcribe_result {
         Ok(Ok(utterances)) => {
           info!(segments = utterances.len(), offset = offset_secs, "transcript: chunk transcribed");
+          checkpoint(&video_id, &url, next_offset, &utterances);
           if !utterances.is_empty() {
             let _ = tx.send(TranscriptEvent::ChunkTranscribed(utterances));
           }
@@ -427,20 +591,87 @@ pub fn spawn_transcription_pipeline(
           // Skip failed chunk and continue — don't abort the pipeline.
           // Whisper can fail on short/silent chunks (e.g. GenericError(-3)).
           warn!(err = %e, offset = offset_secs, "transcript: chunk transcription failed, skipping");
+          checkpoint(&video_id, &url, next_offset, &[]);
         }
         Err(e) => {
           // spawn_blocking panicked (whisper.cpp internal crash on bad input).
           // Skip this chunk and continue the pipeline.
           warn!(err = %e, offset = offset_secs, "transcript: chunk task panicked, skipping");
+          checkpoint(&video_id, &url, next_offset, &[]);
         }
       }
 
-      offset_secs = offset_secs.saturating_add(chunk_secs);
+      offset_secs = next_offset;
     }
 
     // Clean up chunk file and signal completion
     let _ = std::fs::remove_file(&chunk_path);
     info!(total_offset = offset_secs, "transcript: all chunks processed");
+    if let Err(e) = store::mark_complete(&video_id) {
+      warn!(err = %e, video_id = %video_id, "transcript: failed to mark checkpoint complete");
+    }
     let _ = tx.send(TranscriptEvent::Transcribed);
   })
 }
+
+/// Persist a chunk to the transcript store. Failures are logged, never fatal:
+/// a missing checkpoint only costs a restart from an earlier offset.
+fn checkpoint(video_id: &str, url: &str, next_offset: u32, utterances: &[whisper_cli::Utternace]) {
+  if let Err(e) = store::record_chunk(video_id, url, next_offset, utterances) {
+    warn!(err = %e, video_id = %video_id, offset = next_offset, "transcript: failed to write checkpoint");
+  }
+}
+
+/// Whether an ffmpeg failure at `offset_secs` happened before the known end of the
+/// video — i.e. the stream was interrupted (network loss, expired CDN URL) rather
+/// than exhausted. Without a duration hint the end can't be told apart from an
+/// interruption, so every failure counts as one: a clean end of stream shows up
+/// as an empty chunk instead, and only that marks the record complete.
+fn stream_interrupted(offset_secs: u32, chunk_secs: u32, duration_hint: Option<u32>) -> bool {
+  duration_hint.is_none_or(|dur| offset_secs.saturating_add(chunk_secs) < dur)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn stream_interrupted_mid_video() {
+    assert!(stream_interrupted(60, 30, Some(600)));
+  }
+
+  #[test]
+  fn stream_interrupted_last_chunk_is_end() {
+    assert!(!stream_interrupted(590, 30, Some(600)));
+  }
+
+  #[test]
+  fn stream_interrupted_without_hint() {
+    assert!(stream_interrupted(60, 30, None));
+  }
+
+  #[test]
+  fn meminfo_available_parsed() {
+    let meminfo = "MemTotal:       16318412 kB\nMemFree:         1203044 kB\nMemAvailable:    8159206 kB\n";
+    assert_eq!(parse_meminfo_available(meminfo), Some(8_159_206 * 1024));
+  }
+
+  #[test]
+  fn meminfo_missing_available() {
+    assert_eq!(parse_meminfo_available("MemTotal: 1 kB\n"), None);
+  }
+
+  #[test]
+  fn whisper_pool_size_bounds() {
+    assert_eq!(whisper_pool_size(0), 1);
+    assert!(whisper_pool_size(4) <= 4);
+  }
+
+  #[tokio::test]
+  async fn whisper_pool_reuses_slots() {
+    let pool = WhisperPool::new(1);
+    let first = pool.acquire().await.cache();
+    let second = pool.acquire().await.cache();
+    assert!(Arc::ptr_eq(&first, &second));
+  }
+}
@@ -0,0 +1,373 @@
+//! Transcript files from disk: yp JSON/JSONL, SubRip and WebVTT.
+//!
+//! `yp summarize --from-transcript` runs the classify-reduce pipeline on a
+//! transcript that already exists instead of transcribing the video again.
+//! Accepted inputs:
+//!
+//! - **JSONL** -- `yp transcript` output, raw (`{start,end,text}`) or classified,
+//!   in seconds; lines with `stop` instead of `end` are whisper centiseconds
+//! - **JSON** -- a document with an `utterances` array: `yp summarize --raw`
+//!   output or a transcript store record. Its `video` object, if any, supplies
+//!   the metadata when `--info` is not given
+//! - **SRT** / **VTT** -- captions from elsewhere. Inline tags are stripped and
+//!   the rolling lines of YouTube auto-captions are collapsed
+//!
+//! The format comes from the file extension, falling back to sniffing the content.
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::player::VideoDetails;
+use crate::summarize;
+
+/// Supported transcript file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+  Json,
+  Jsonl,
+  Srt,
+  Vtt,
+}
+
+/// A transcript read from disk.
+pub struct TranscriptFile {
+  pub utterances: Vec<whisper_cli::Utternace>,
+  /// Video metadata embedded in the file (`yp summarize --raw` output).
+  pub video: Option<VideoDetails>,
+}
+
+/// Read and parse a transcript file.
+pub fn load(path: &Path) -> Result<TranscriptFile> {
+  let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+  let format = detect_format(path, &content);
+  let file = parse(&content, format).with_context(|| format!("Failed to parse {}", path.display()))?;
+  if file.utterances.is_empty() {
+    bail!("No utterances found in {}", path.display());
+  }
+  Ok(file)
+}
+
+/// Pick a format from the extension, or from the content when the extension is unknown.
+pub fn detect_format(path: &Path, content: &str) -> Format {
+  match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+    Some("srt") => return Format::Srt,
+    Some("vtt") => return Format::Vtt,
+    Some("jsonl" | "ndjson") => return Format::Jsonl,
+    _ => {}
+  }
+  let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+  if trimmed.starts_with("WEBVTT") {
+    Format::Vtt
+  } else if trimmed.starts_with('{') || trimmed.starts_with('[') {
+    // A single JSON document parses whole; JSONL does not (unless it is one line).
+    if serde_json::from_str::<Value>(trimmed).is_ok() { Format::Json } else { Format::Jsonl }
+  } else {
+    Format::Srt
+  }
+}
+
+/// Parse transcript content in the given format.
+pub fn parse(content: &str, format: Format) -> Result<TranscriptFile> {
+  let content = content.trim_start_matches('\u{feff}');
+  match format {
+    Format::Json => parse_json(content),
+    Format::Jsonl => Ok(TranscriptFile { utterances: parse_jsonl(content)?, video: None }),
+    Format::Srt | Format::Vtt => Ok(TranscriptFile { utterances: parse_cues(content), video: None }),
+  }
+}
+
+/// Load video metadata from `yp info` output or a yt-dlp `--dump-json` file.
+pub fn load_info(path: &Path) -> Result<VideoDetails> {
+  let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+  let value: Value =
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {} as JSON", path.display()))?;
+  details_from_json(&value).ok_or_else(|| anyhow!("{} has no video title", path.display()))
+}
+
+/// Placeholder metadata for a transcript without an info file: the file stem as title.
+pub fn details_from_path(path: &Path) -> VideoDetails {
+  let title = path.file_stem().and_then(|s| s.to_str()).unwrap_or("transcript").to_string();
+  VideoDetails {
+    url: String::new(),
+    title,
+    uploader: None,
+    duration: None,
+    upload_date: None,
+    view_count: None,
+    tags: Vec::new(),
+  }
+}
+
+/// Map a video info object onto `VideoDetails`.
+///
+/// Accepts yp's own field names and yt-dlp's (`webpage_url`, `duration_string`,
+/// numeric `duration`/`view_count`).
+fn details_from_json(value: &Value) -> Option<VideoDetails> {
+  let str_field = |key: &str| value.get(key).and_then(Value::as_str).filter(|s| !s.is_empty()).map(str::to_string);
+  let title = str_field("title")?;
+  let url = str_field("webpage_url")
+    .or_else(|| str_field("url"))
+    .or_else(|| {
+      str_field("video_id").or_else(|| str_field("id")).map(|id| format!("https://www.youtube.com/watch?v={id}"))
+    })
+    .unwrap_or_default();
+  let duration = str_field("duration_string").or_else(|| match value.get("duration") {
+    Some(Value::String(s)) => Some(s.clone()),
+    Some(Value::Number(n)) => n.as_f64().map(summarize::timestamp),
+    _ => None,
+  });
+  let view_count = match value.get("view_count") {
+    Some(Value::String(s)) => Some(s.clone()),
+    Some(Value::Number(n)) => Some(crate::youtube::format_view_count(&n.to_string())),
+    _ => None,
+  };
+  let tags = value
+    .get("tags")
+    .and_then(Value::as_array)
+    .map(|tags| tags.iter().filter_map(Value::as_str).map(str::to_string).collect())
+    .unwrap_or_default();
+  Some(VideoDetails {
+    url,
+    title,
+    uploader: str_field("uploader").or_else(|| str_field("channel")),
+    duration,
+    upload_date: str_field("upload_date"),
+    view_count,
+    tags,
+  })
+}
+
+// ---------------------------------------------------------------------------
+// JSON / JSONL
+// ---------------------------------------------------------------------------
+
+/// Convert seconds to whisper centiseconds.
+#[allow(clippy::cast_possible_truncation)]
+fn secs_to_cs(secs: f64) -> i64 {
+  (secs * 100.0).round() as i64
+}
+
+/// One utterance object: `{start,end,text}` in seconds or `{start,stop,text}` in centiseconds.
+fn utterance_from_json(value: &Value) -> Option<whisper_cli::Utternace> {
+  let text = value.get("text")?.as_str()?.trim().to_string();
+  let start = value.get("start")?.as_f64()?;
+  let (start, stop) = if let Some(stop) = value.get("stop").and_then(Value::as_f64) {
+    (secs_to_cs(start / 100.0), secs_to_cs(stop / 100.0))
+  } else {
+    (secs_to_cs(start), secs_to_cs(value.get("end")?.as_f64()?))
+  };
+  Some(whisper_cli::Utternace { start, stop, text })
+}
+
+fn parse_jsonl(content: &str) -> Result<Vec<whisper_cli::Utternace>> {
+  let mut out = Vec::new();
+  for (i, line) in content.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() {
+      continue;
+    }
+    let value: Value = serde_json::from_str(line).with_context(|| format!("Line {}: invalid JSON", i + 1))?;
+    let utt = utterance_from_json(&value)
+      .ok_or_else(|| anyhow!("Line {}: expected an object with start, end (or stop) and text", i + 1))?;
+    out.push(utt);
+  }
+  Ok(out)
+}
+
+fn parse_json(content: &str) -> Result<TranscriptFile> {
+  let value: Value = serde_json::from_str(content).context("Invalid JSON")?;
+  let items = match &value {
+    Value::Array(items) => items,
+    Value::Object(obj) => obj
+      .get("utterances")
+      .and_then(Value::as_array)
+      .ok_or_else(|| anyhow!("Expected an `utterances` array (yp summarize --raw output or a store record)"))?,
+    _ => bail!("Expected a JSON object or array"),
+  };
+  let utterances = items
+    .iter()
+    .enumerate()
+    .map(|(i, item)| {
+      utterance_from_json(item).ok_or_else(|| anyhow!("Utterance {}: expected start, end (or stop) and text", i + 1))
+    })
+    .collect::<Result<_>>()?;
+  let video = value.get("video").and_then(details_from_json);
+  Ok(TranscriptFile { utterances, video })
+}
+
+// ---------------------------------------------------------------------------
+// SRT / VTT
+// ---------------------------------------------------------------------------
+
+/// Parse a caption timestamp (`01:02:03,456`, `01:02:03.456` or `02:03.456`) to centiseconds.
+fn parse_timestamp(ts: &str) -> Option<i64> {
+  let (hms, frac) = ts.trim().split_once([',', '.'])?;
+  let parts: Vec<i64> = hms.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+  let secs = match parts.as_slice() {
+    [h, m, s] => h * 3600 + m * 60 + s,
+    [m, s] => m * 60 + s,
+    _ => return None,
+  };
+  let millis: i64 = format!("{frac:0<3}").get(..3)?.parse().ok()?;
+  Some(secs * 100 + millis / 10)
+}
+
+/// Parse a `start --> end [settings]` cue timing line.
+fn parse_timing(line: &str) -> Option<(i64, i64)> {
+  let (start, rest) = line.split_once("-->")?;
+  let end = rest.split_whitespace().next()?;
+  Some((parse_timestamp(start)?, parse_timestamp(end)?))
+}
+
+/// Remove `<...>` markup (`<i>`, `<c>`, VTT inline timestamps) and decode common entities.
+fn strip_tags(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut in_tag = false;
+  for c in text.chars() {
+    match c {
+      '<' => in_tag = true,
+      '>' if in_tag => in_tag = false,
+      c if !in_tag => out.push(c),
+      _ => {}
+    }
+  }
+  out.replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">").replace("&nbsp;", " ")
+}
+
+/// Parse SRT or VTT cues. Both are blank-line separated blocks with a `-->` timing line.
+///
+/// YouTube auto-captions repeat the previous cue's last line at the top of the
+/// next cue (and add 10 ms "snapshot" cues); lines already emitted by the
+/// previous cue are dropped so every line appears once.
+fn parse_cues(content: &str) -> Vec<whisper_cli::Utternace> {
+  let mut out: Vec<whisper_cli::Utternace> = Vec::new();
+  let mut previous_lines: Vec<String> = Vec::new();
+  let normalized = content.replace("\r\n", "\n");
+
+  for block in normalized.split("\n\n") {
+    let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+    let Some((start, stop)) = lines.next().and_then(parse_timing) else { continue };
+    let cue_lines: Vec<String> =
+      lines.map(|l| strip_tags(l).split_whitespace().collect::<Vec<_>>().join(" ")).filter(|l| !l.is_empty()).collect();
+    let new_lines: Vec<&String> = cue_lines.iter().filter(|l| !previous_lines.contains(l)).collect();
+    if !cue_lines.is_empty() {
+      previous_lines.clone_from(&cue_lines);
+    }
+    if new_lines.is_empty() {
+      continue;
+    }
+    let text = new_lines.iter().map(|l| l.as_str()).collect::<Vec<_>>().join(" ");
+    out.push(whisper_cli::Utternace { start, stop, text });
+  }
+  out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn triples(utts: &[whisper_cli::Utternace]) -> Vec<(i64, i64, &str)> {
+    utts.iter().map(|u| (u.start, u.stop, u.text.as_str())).collect()
+  }
+
+  #[test]
+  fn parses_srt() {
+    let srt = "1\r\n00:00:01,000 --> 00:00:03,500\r\nHello <i>there</i>.\r\n\r\n\
+               2\r\n00:00:04,000 --> 00:00:06,250\r\nSecond line\r\ncontinues here.\r\n";
+    let file = parse(srt, Format::Srt).unwrap();
+    assert_eq!(triples(&file.utterances), vec![(100, 350, "Hello there."), (400, 625, "Second line continues here.")]);
+  }
+
+  #[test]
+  fn parses_vtt_with_settings_and_notes() {
+    let vtt = "WEBVTT\nKind: captions\n\nNOTE a comment\n\nintro\n00:01.000 --> 00:02.500 align:start position:0%\n\
+               <c.colorE5E5E5>First</c> cue\n\n01:00:00.000 --> 01:00:01.000\nLate &amp; last\n";
+    let file = parse(vtt, Format::Vtt).unwrap();
+    assert_eq!(triples(&file.utterances), vec![(100, 250, "First cue"), (360_000, 360_100, "Late & last")]);
+  }
+
+  #[test]
+  fn collapses_youtube_rolling_captions() {
+    let vtt = "WEBVTT\n\n00:00:00.000 --> 00:00:02.000\nwe start with water\n\n\
+               00:00:02.000 --> 00:00:02.010\nwe start with water\n\n\
+               00:00:02.010 --> 00:00:04.000\nwe start with water\nthen add<00:00:02.500><c> pigment</c>\n";
+    let file = parse(vtt, Format::Vtt).unwrap();
+    assert_eq!(triples(&file.utterances), vec![(0, 200, "we start with water"), (201, 400, "then add pigment")]);
+  }
+
+  #[test]
+  fn parses_raw_and_classified_jsonl() {
+    let jsonl = "{\"start\":1.5,\"end\":3.0,\"text\":\" Hi.\"}\n\n\
+                 {\"start\":3.0,\"end\":4.25,\"text\":\"More.\",\"class\":\"normal\"}\n";
+    let file = parse(jsonl, Format::Jsonl).unwrap();
+    assert_eq!(triples(&file.utterances), vec![(150, 300, "Hi."), (300, 425, "More.")]);
+  }
+
+  #[test]
+  fn jsonl_error_names_the_line() {
+    let err = parse("{\"start\":1,\"end\":2,\"text\":\"a\"}\n{\"start\":1}\n", Format::Jsonl).err().unwrap();
+    assert!(err.to_string().contains("Line 2"), "{err}");
+  }
+
+  #[test]
+  fn parses_raw_summary_json_with_video() {
+    let json = r#"{"_hint":"...","video":{"url":"u","title":"Glazing","uploader":null,"duration":"4:05",
+      "upload_date":null,"view_count":null,"tags":["art"]},"utterances":[{"start":0.0,"end":1.0,"text":"Go."}]}"#;
+    let file = parse(json, Format::Json).unwrap();
+    assert_eq!(triples(&file.utterances), vec![(0, 100, "Go.")]);
+    let video = file.video.unwrap();
+    assert_eq!(video.title, "Glazing");
+    assert_eq!(video.duration.as_deref(), Some("4:05"));
+    assert_eq!(video.tags, vec!["art"]);
+  }
+
+  #[test]
+  fn parses_store_record_centiseconds() {
+    let json = r#"{"video_id":"abc","url":"u","offset_secs":0,"complete":true,"updated_at":"",
+      "utterances":[{"start":120,"stop":340,"text":"Stored."}]}"#;
+    let file = parse(json, Format::Json).unwrap();
+    assert_eq!(triples(&file.utterances), vec![(120, 340, "Stored.")]);
+    assert!(file.video.is_none());
+  }
+
+  #[test]
+  fn detects_format() {
+    assert_eq!(detect_format(Path::new("a.SRT"), ""), Format::Srt);
+    assert_eq!(detect_format(Path::new("a.txt"), "WEBVTT\n"), Format::Vtt);
+    assert_eq!(detect_format(Path::new("a.txt"), "{\"a\":1}\n{\"a\":2}\n"), Format::Jsonl);
+    assert_eq!(detect_format(Path::new("a.json"), "{\"utterances\":[]}"), Format::Json);
+    assert_eq!(detect_format(Path::new("captions"), "1\n00:00:01,000 --> 00:00:02,000\nx\n"), Format::Srt);
+  }
+
+  #[test]
+  fn timestamps() {
+    assert_eq!(parse_timestamp("00:00:01,000"), Some(100));
+    assert_eq!(parse_timestamp("01:02:03.456"), Some(372_345));
+    assert_eq!(parse_timestamp("02:03.4"), Some(12_340));
+    assert_eq!(parse_timestamp("garbage"), None);
+  }
+
+  #[test]
+  fn details_from_yt_dlp_json() {
+    let value = serde_json::json!({
+      "id": "abc123", "title": "Wet on wet", "channel": "Painter", "duration": 3723,
+      "view_count": 1500, "upload_date": "20240102", "tags": ["watercolor"],
+    });
+    let details = details_from_json(&value).unwrap();
+    assert_eq!(details.url, "https://www.youtube.com/watch?v=abc123");
+    assert_eq!(details.uploader.as_deref(), Some("Painter"));
+    assert_eq!(details.duration.as_deref(), Some("1:02:03"));
+    assert_eq!(details.upload_date.as_deref(), Some("20240102"));
+    assert!(details_from_json(&serde_json::json!({"id": "x"})).is_none());
+  }
+
+  #[test]
+  fn details_from_path_uses_stem() {
+    assert_eq!(details_from_path(Path::new("/tmp/My talk.en.vtt")).title, "My talk.en");
+  }
+}
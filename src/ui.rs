@@ -265,7 +265,7 @@ fn render_pip(frame: &mut Frame, app: &mut App) {
 }
 
 fn render_main(frame: &mut Frame, app: &mut App, area: Rect) {
-  if matches!(app.mode, AppMode::Results | AppMode::Filter) && !app.search_results.is_empty() {
+  if matches!(app.mode, AppMode::Results | AppMode::Filter) && app.result_count() > 0 {
     render_results(frame, app, area);
   } else if app.player.is_playing() {
     render_player(frame, app, area);
@@ -284,7 +284,10 @@ fn render_welcome(frame: &mut Frame, theme: &Theme, area: Rect) {
       Style::default().fg(theme.fg),
     )),
     Line::from(""),
-    Line::from(Span::styled("Type a search query or @channel below.", Style::default().fg(theme.muted))),
+    Line::from(Span::styled(
+      "Type a search query, @channel, or ?words to search transcripts below.",
+      Style::default().fg(theme.muted),
+    )),
   ];
   let paragraph = Paragraph::new(text).alignment(Alignment::Center).block(
     Block::bordered()
@@ -455,7 +458,7 @@ fn render_player(frame: &mut Frame, app: &mut App, area: Rect) {
   clippy::cast_precision_loss,
   clippy::cast_possible_wrap
 )]
-fn render_transcript(frame: &mut Frame, app: &App, area: Rect) {
+fn render_transcript(frame: &mut Frame, app: &mut App, area: Rect) {
   let theme = app.theme();
 
   let mut block = Block::bordered()
@@ -529,22 +532,28 @@ fn render_transcript(frame: &mut Frame, app: &App, area: Rect) {
     return;
   }
 
-  let title = Line::from(Span::styled(" Transcript ", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)));
+  let title_text = if app.transcript_paragraphs { " Transcript · paragraphs " } else { " Transcript " };
+  let title = Line::from(Span::styled(title_text, Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)));
   block = block.title(title);
 
   // Determine current playback time for highlighting
   let current_time_cs: Option<i64> =
     app.player.get_last_mpv_status().and_then(|s| crate::parse_mpv_time_secs(&s)).map(|secs| (secs * 100.0) as i64); // Convert seconds to centiseconds
 
-  // Find the active utterance index
-  let active_idx: Option<usize> =
-    current_time_cs.and_then(|t| app.utterances.iter().position(|u| t >= u.start && t < u.stop));
+  // Timed blocks to display: raw utterances, or sentences grouped into paragraphs.
+  let blocks: Vec<(i64, i64, String)> = if app.transcript_paragraphs {
+    app.paragraphs().iter().map(|p| (p.start, p.stop, p.text())).collect()
+  } else {
+    app.utterances.iter().map(|u| (u.start, u.stop, u.text.trim().to_string())).collect()
+  };
+
+  // Find the active block index
+  let active_idx: Option<usize> = current_time_cs.and_then(|t| blocks.iter().position(|b| t >= b.0 && t < b.1));
 
   let mut lines: Vec<Line> = Vec::new();
-  // Track which line index in `lines` corresponds to the active utterance
+  // Track which line index in `lines` corresponds to the active block
   let mut active_line_idx: Option<usize> = None;
-  for (i, utterance) in app.utterances.iter().enumerate() {
-    let text = utterance.text.trim();
+  for (i, (_, _, text)) in blocks.iter().enumerate() {
     if text.is_empty() {
       continue;
     }
@@ -561,10 +570,10 @@ fn render_transcript(frame: &mut Frame, app: &App, area: Rect) {
       active_line_idx = Some(lines.len());
     }
 
-    lines.push(Line::from(Span::styled(text.to_string(), style)));
+    lines.push(Line::from(Span::styled(text.clone(), style)));
 
-    // Add blank line between utterances (except after last)
-    if i < app.utterances.len().saturating_sub(1) {
+    // Add blank line between blocks (except after last)
+    if i < blocks.len().saturating_sub(1) {
       lines.push(Line::from(""));
     }
   }
@@ -748,7 +757,6 @@ fn render_results(frame: &mut Frame, app: &mut App, area: Rect) {
     .iter()
     .enumerate()
     .filter_map(|(display_idx, &actual_idx)| {
-      let entry = app.search_results.get(actual_idx)?;
       let is_selected = Some(display_idx) == app.list_state.selected();
       let fg = if is_selected { theme.highlight_fg } else { theme.fg };
       let bg = if is_selected {
@@ -760,6 +768,12 @@ fn render_results(frame: &mut Frame, app: &mut App, area: Rect) {
       };
       let normal_style = Style::default().fg(fg);
 
+      if let Some(ref search) = app.transcript_search {
+        let hit = search.hits.get(actual_idx)?;
+        return Some(ListItem::new(hit_line(hit, inner_w, filter_needle, normal_style, match_style, theme)).bg(bg));
+      }
+      let entry = app.search_results.get(actual_idx)?;
+
       // Build right-side metadata: "tags  date" or just "date" or just "tags"
       let date_str = entry.upload_date.as_deref().unwrap_or("");
       let tags_limited: String;
@@ -841,7 +855,14 @@ fn render_results(frame: &mut Frame, app: &mut App, area: Rect) {
     })
     .collect();
 
-  let title = if is_filtering {
+  let title = if let Some(ref search) = app.transcript_search {
+    let total = search.hits.len();
+    if is_filtering {
+      format!(" Transcripts — '{}' /{} ({}/{total} hits) ", search.query, app.filter, app.filtered_indices.len())
+    } else {
+      format!(" Transcripts — '{}' ({total} hits) ", search.query)
+    }
+  } else if is_filtering {
     let filtered = app.filtered_indices.len();
     let total = app.search_results.len();
     if is_channel {
@@ -872,6 +893,31 @@ fn render_results(frame: &mut Frame, app: &mut App, area: Rect) {
   frame.render_stateful_widget(list, area, &mut app.list_state);
 }
 
+/// One transcript hit: "timestamp  snippet", with the video title right-aligned.
+fn hit_line(
+  hit: &crate::index::Hit,
+  inner_w: usize,
+  filter: &str,
+  normal_style: Style,
+  match_style: Style,
+  theme: &Theme,
+) -> Line<'static> {
+  let muted_style = Style::default().fg(theme.muted);
+  let title = truncate_str(hit.title.as_deref().unwrap_or(&hit.video_id), inner_w / 3);
+  let title_w = title.chars().count();
+  let prefix = format!("{}  ", hit.timestamp);
+  let snippet_max = inner_w.saturating_sub(prefix.chars().count()).saturating_sub(title_w).saturating_sub(2);
+  let snippet = truncate_str(&hit.snippet, snippet_max);
+  let gap =
+    inner_w.saturating_sub(prefix.chars().count()).saturating_sub(snippet.chars().count()).saturating_sub(title_w);
+
+  let mut spans = vec![Span::styled(prefix, muted_style)];
+  spans.extend(highlight_text(&snippet, filter, normal_style, match_style));
+  spans.push(Span::raw(" ".repeat(gap)));
+  spans.extend(highlight_text(&title, filter, muted_style, match_style));
+  Line::from(spans)
+}
+
 fn render_status(frame: &mut Frame, app: &App, area: Rect) {
   let theme = app.theme();
 
@@ -952,19 +998,26 @@ fn render_input(frame: &mut Frame, app: &mut App, area: Rect) {
 #[allow(clippy::cast_possible_truncation)]
 fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
   let theme = app.theme();
-  let has_results = !app.search_results.is_empty();
+  let has_results = app.result_count() > 0;
   let is_playing = app.player.is_playing();
   let transcript_busy =
     matches!(app.transcript_state, TranscriptState::ExtractingAudio { .. } | TranscriptState::Transcribing { .. });
   let has_transcript = !app.utterances.is_empty() || transcript_busy;
   let transcript_hint = if transcript_busy {
     ("^a", "Cancel")
+  } else if app.transcript_resumable {
+    ("^a", "Resume")
   } else if has_transcript {
     if app.transcript_visible { ("^a", "Hide") } else { ("^a", "Show") }
   } else {
     ("^a", "Transcript")
   };
   let wiki_hint: (&str, &str) = if app.wiki_visible { ("^w", "Hide Wiki") } else { ("^w", "Wiki") };
+  let clip_hint: (&str, &str) = match (app.clip_in, app.clip_out) {
+    (Some(_), Some(_)) => ("^x", "Export clip"),
+    (Some(_), None) => ("^e", "Mark out"),
+    _ => ("^b", "Mark in"),
+  };
   let keys: Vec<(&str, &str)> = match app.mode {
     AppMode::Input => {
       let mut k = vec![("Enter", "Search"), ("^t", "Theme"), ("^f", "Frame")];
@@ -974,6 +1027,7 @@ fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
         if crate::window::pip_supported() {
           k.push(("^m", "PiP"));
         }
+        k.push(clip_hint);
         k.push(("^s", "Stop"));
         k.push(("^o", "Open"));
       }
@@ -995,6 +1049,7 @@ fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
         if crate::window::pip_supported() {
           k.push(("^m", "PiP"));
         }
+        k.push(clip_hint);
         k.push(("^s", "Stop"));
         k.push(("^o", "Open"));
       }
@@ -1013,6 +1068,7 @@ fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
         if crate::window::pip_supported() {
           k.push(("^m", "PiP"));
         }
+        k.push(clip_hint);
         k.push(("^s", "Stop"));
         k.push(("^o", "Open"));
       }
@@ -0,0 +1,219 @@
+//! Channel watching for `yp watch`: which uploads have already been announced.
+//!
+//! Every poll lists a channel's newest few videos; IDs not seen before are new
+//! uploads. Seen IDs are kept per channel in `<data_dir>/yp/watch.json`, so a
+//! restarted watcher carries on where it stopped. An ID only counts as seen
+//! once its action and `--exec` hook have finished, so a crash repeats an
+//! upload rather than losing it. The first poll of a channel is a baseline:
+//! it records what is already there and announces nothing.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Seen IDs kept per channel. Far more than a poll lists, so a video that
+/// drops out of the listing and comes back is still recognized.
+const SEEN_PER_CHANNEL: usize = 500;
+
+/// `[watch]` table: defaults for `yp watch`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+  /// Channels watched when none are given on the command line.
+  pub channels: Vec<String>,
+  /// Shell command run for every new upload (see `--exec`).
+  pub exec: Option<String>,
+}
+
+impl WatchConfig {
+  /// Load the `[watch]` table from the user config, or defaults (an error if the config is broken).
+  pub fn load() -> Result<Self> {
+    Ok(crate::config::Config::read()?.watch.unwrap_or_default())
+  }
+}
+
+/// What to do with each new upload besides announcing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchAction {
+  Transcribe,
+  Summarize,
+}
+
+/// Seen video IDs per channel, as persisted between runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WatchState {
+  channels: BTreeMap<String, ChannelState>,
+  #[serde(skip)]
+  path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ChannelState {
+  /// Newest first.
+  seen: Vec<String>,
+  /// Last successful poll (RFC 3339).
+  checked_at: String,
+}
+
+fn state_path() -> Option<PathBuf> {
+  directories::ProjectDirs::from("", "", "yp").map(|d| d.data_dir().join("watch.json"))
+}
+
+impl WatchState {
+  /// Load the saved state (empty when there is none yet).
+  pub fn load() -> Result<Self> {
+    match state_path() {
+      Some(path) => Self::load_from(&path),
+      None => Ok(Self::default()),
+    }
+  }
+
+  fn load_from(path: &Path) -> Result<Self> {
+    let mut state: Self = match fs::read_to_string(path) {
+      Ok(content) => serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {} (delete it to start over)", path.display()))?,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+      Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    state.path = Some(path.to_path_buf());
+    Ok(state)
+  }
+
+  /// Write the state atomically via temp file + rename.
+  pub fn save(&self) -> Result<()> {
+    let Some(path) = &self.path else { return Ok(()) };
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).context("Failed to create data directory")?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(self).context("Failed to serialize watch state")?;
+    fs::write(&tmp, json).context("Failed to write watch state")?;
+    fs::rename(&tmp, path).context("Failed to rename watch state")
+  }
+
+  /// Whether `channel` has been polled before.
+  pub fn knows(&self, channel: &str) -> bool {
+    self.channels.contains_key(channel)
+  }
+
+  /// Compare one poll's listing (newest first) with what was seen and return
+  /// the new IDs, oldest first. A channel's first listing records everything
+  /// and returns nothing; afterwards new IDs stay unseen until [`mark_seen`],
+  /// so a watcher that dies mid-poll handles them again on restart.
+  ///
+  /// [`mark_seen`]: Self::mark_seen
+  pub fn observe(&mut self, channel: &str, listed: &[String]) -> Vec<String> {
+    let baseline = !self.knows(channel);
+    let state = self.channels.entry(channel.to_string()).or_default();
+    state.checked_at = chrono::Local::now().to_rfc3339();
+    if baseline {
+      state.seen = listed.iter().take(SEEN_PER_CHANNEL).cloned().collect();
+      return Vec::new();
+    }
+    listed.iter().rev().filter(|id| !state.seen.contains(id)).cloned().collect()
+  }
+
+  /// Record `video_id` as handled, once its action and hook have finished.
+  pub fn mark_seen(&mut self, channel: &str, video_id: &str) {
+    let state = self.channels.entry(channel.to_string()).or_default();
+    if !state.seen.iter().any(|id| id == video_id) {
+      state.seen.insert(0, video_id.to_string());
+      state.seen.truncate(SEEN_PER_CHANNEL);
+    }
+  }
+}
+
+/// Parse a poll interval: `90s`, `30m`, `2h`, `1h30m`, or bare seconds.
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+  let s = s.trim();
+  let secs = match s.parse::<u64>() {
+    Ok(secs) => secs,
+    Err(_) => unit_secs(s)?,
+  };
+  if secs == 0 {
+    return Err(format!("invalid interval `{s}`: must be longer than zero"));
+  }
+  Ok(Duration::from_secs(secs))
+}
+
+/// Sum of `<number><unit>` parts, e.g. `1h30m`.
+fn unit_secs(s: &str) -> Result<u64, String> {
+  let mut total = 0u64;
+  let mut digits = String::new();
+  for c in s.chars() {
+    if c.is_ascii_digit() {
+      digits.push(c);
+      continue;
+    }
+    let unit = match c {
+      's' => 1,
+      'm' => 60,
+      'h' => 3600,
+      'd' => 86_400,
+      _ => return Err(format!("invalid interval `{s}` (use e.g. 90s, 30m, 2h or 1h30m)")),
+    };
+    let n: u64 = digits.parse().map_err(|_| format!("invalid interval `{s}`: `{c}` needs a number before it"))?;
+    total = total.saturating_add(n.saturating_mul(unit));
+    digits.clear();
+  }
+  if !digits.is_empty() {
+    return Err(format!("invalid interval `{s}`: `{digits}` needs a unit (s, m, h or d)"));
+  }
+  Ok(total)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| (*id).to_string()).collect()
+  }
+
+  #[test]
+  fn intervals() {
+    assert_eq!(parse_interval("30m"), Ok(Duration::from_secs(1800)));
+    assert_eq!(parse_interval("1h30m"), Ok(Duration::from_secs(5400)));
+    assert_eq!(parse_interval("90"), Ok(Duration::from_secs(90)));
+    assert!(parse_interval("30x").is_err());
+    assert!(parse_interval("1h30").is_err());
+    assert!(parse_interval("0m").is_err());
+    assert!(parse_interval("0").is_err());
+  }
+
+  #[test]
+  fn first_poll_is_a_baseline_then_new_ids_come_oldest_first() {
+    let mut state = WatchState::default();
+    assert!(state.observe("@a", &ids(&["c", "b", "a"])).is_empty());
+    assert!(state.knows("@a"));
+    let new = state.observe("@a", &ids(&["e", "d", "c", "b", "a"]));
+    assert_eq!(new, ids(&["d", "e"]));
+    for id in &new {
+      state.mark_seen("@a", id);
+    }
+    assert!(state.observe("@a", &ids(&["e", "d", "c"])).is_empty());
+    // Another channel has its own baseline.
+    assert!(state.observe("@b", &ids(&["e"])).is_empty());
+  }
+
+  #[test]
+  fn unhandled_ids_come_back_after_a_restart() {
+    let path = std::env::temp_dir().join(format!("yp-watch-test-{}.json", std::process::id()));
+    let mut state = WatchState::load_from(&path).unwrap();
+    state.observe("@a", &ids(&["b", "a"]));
+    assert_eq!(state.observe("@a", &ids(&["d", "c", "b", "a"])), ids(&["c", "d"]));
+    state.mark_seen("@a", "c");
+    state.save().unwrap();
+
+    let mut restarted = WatchState::load_from(&path).unwrap();
+    assert_eq!(restarted.observe("@a", &ids(&["d", "c", "b", "a"])), ids(&["d"]));
+    let _ = fs::remove_file(&path);
+  }
+}
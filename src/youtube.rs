@@ -8,6 +8,7 @@ use tokio::process::{Child as TokioChild, Command};
 use tokio::sync::mpsc;
 
 use crate::constants::constants;
+use crate::error::ErrorKind;
 use crate::player::VideoDetails;
 
 // --- Shared Helpers ---
@@ -29,7 +30,10 @@ async fn run_yt_dlp(args: &[&str], context: &str) -> Result<Output> {
     .await
     .map_err(|e| {
       if e.kind() == std::io::ErrorKind::NotFound {
-        anyhow!("yt-dlp not found. Install it with: brew install yt-dlp (macOS) or pip install yt-dlp")
+        crate::error::fail(
+          ErrorKind::DependencyMissing,
+          "yt-dlp not found. Install it with: brew install yt-dlp (macOS) or pip install yt-dlp",
+        )
       } else {
         anyhow!(e).context(format!("Failed to execute yt-dlp for {context}"))
       }
@@ -359,7 +363,10 @@ pub async fn fetch_video_frames(video_id: &str) -> Result<FrameSource> {
     .spawn()
     .map_err(|e| {
       if e.kind() == std::io::ErrorKind::NotFound {
-        anyhow!("ffmpeg not found. Install it with: brew install ffmpeg (macOS)")
+        crate::error::fail(
+          ErrorKind::DependencyMissing,
+          "ffmpeg not found. Install it with: brew install ffmpeg (macOS)",
+        )
       } else {
         anyhow!(e).context("Failed to spawn ffmpeg for video frame extraction")
       }
@@ -557,7 +564,7 @@ pub async fn list_channel_videos(channel_url: &str, start: usize, count: Option<
   let output = run_yt_dlp(&args, "channel listing").await.context("Failed to run yt-dlp for channel listing")?;
 
   if !output.status.success() {
-    return Err(anyhow!("yt-dlp channel listing failed: {}", String::from_utf8_lossy(&output.stderr)));
+    return Err(crate::error::yt_dlp_failed("channel listing", &output.stderr));
   }
 
   let stdout_str = String::from_utf8(output.stdout).context("yt-dlp output non-UTF8")?;
@@ -586,7 +593,7 @@ pub async fn search_youtube(query: &str) -> Result<Vec<SearchEntry>> {
   .context("Failed to run yt-dlp for search")?;
 
   if !output.status.success() {
-    return Err(anyhow!("yt-dlp search failed: {}", String::from_utf8_lossy(&output.stderr)));
+    return Err(crate::error::yt_dlp_failed("search", &output.stderr));
   }
 
   let stdout_str = String::from_utf8(output.stdout).context("yt-dlp output non-UTF8")?;
@@ -639,7 +646,7 @@ pub async fn get_video_info(video_id: &str) -> Result<VideoDetails> {
       .unwrap_or_default();
     Ok(VideoDetails { url, title, uploader, duration, upload_date, view_count, tags })
   } else {
-    Err(anyhow!("yt-dlp failed to get video info: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    Err(crate::error::yt_dlp_failed("video info", &output.stderr))
   }
 }
 